@@ -0,0 +1,194 @@
+use std::{
+    fmt,
+    time::{Duration, Instant},
+};
+
+use cpu_time::ThreadTime;
+use serde::Serialize;
+
+use crate::{
+    eval::{Evaluation, Operation},
+    result::PatternError,
+};
+
+/// How many times the frontends run an evaluation when benchmarking, unless
+/// told otherwise.
+pub const DEFAULT_RUNS: usize = 100;
+
+/// The spread of one kind of time over the runs of a [`Benchmark`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct Stats {
+    pub min: Duration,
+    pub median: Duration,
+    pub p99: Duration,
+}
+
+impl Stats {
+    fn of(mut times: Vec<Duration>) -> Self {
+        times.sort();
+        // the smallest time that at least 99% of the runs took no longer than
+        let p99 = (times.len() * 99).div_ceil(100);
+        Stats { min: times[0], median: times[times.len() / 2], p99: times[p99 - 1] }
+    }
+}
+
+/// Wall clock and CPU time for one phase of the runs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct Timings {
+    pub wall: Stats,
+    pub cpu: Stats,
+}
+
+/// Samples `f` once per run, returning its last result.
+fn timed<T>(runs: usize, mut f: impl FnMut() -> T) -> (Timings, T) {
+    let mut wall = Vec::with_capacity(runs);
+    let mut cpu = Vec::with_capacity(runs);
+    let mut last = None;
+    for _ in 0..runs {
+        let (w, c) = (Instant::now(), ThreadTime::now());
+        let t = f();
+        cpu.push(c.elapsed());
+        wall.push(w.elapsed());
+        last = Some(t);
+    }
+    let timings = Timings { wall: Stats::of(wall), cpu: Stats::of(cpu) };
+    (timings, last.expect("at least one run"))
+}
+
+/// How long an evaluation takes, compiling and searching timed apart.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Benchmark {
+    pub operation: Operation,
+    /// The pattern with its flags inline.
+    pub pattern: String,
+    pub runs: usize,
+    /// Size of the haystack.
+    pub bytes: usize,
+    /// How many results each search produced.
+    pub results: usize,
+    pub compile: Timings,
+    pub search: Timings,
+    /// Haystack bytes per median search, in millions per second; `None` when
+    /// the median search took too little time to measure.
+    pub throughput: Option<f64>,
+}
+
+impl Benchmark {
+    /// Compiles and then runs `eval` `runs` times each, after one run of
+    /// both that is not timed so caches are warm.  The runs happen on this
+    /// thread, whose CPU time is what gets measured.
+    pub fn run(eval: &Evaluation, runs: usize) -> Result<Self, PatternError> {
+        let runs = runs.max(1);
+        let re = eval.prepare()?;
+        eval.run_compiled(&re, &|| false);
+
+        let (compile, re) = timed(runs, || eval.prepare().expect("it compiled before"));
+        let (search, outcome) = timed(runs, || {
+            eval.run_compiled(&re, &|| false).expect("never cancelled")
+        });
+        let bytes = eval.haystack().len();
+        Ok(Benchmark {
+            operation: eval.operation,
+            pattern: eval.effective_pattern(),
+            runs,
+            bytes,
+            results: outcome.rows(),
+            compile,
+            search,
+            throughput: throughput(bytes, search.wall.median),
+        })
+    }
+}
+
+/// Millions of bytes per second when `bytes` are searched in `time`.
+fn throughput(bytes: usize, time: Duration) -> Option<f64> {
+    if time.is_zero() {
+        return None;
+    }
+    Some(bytes as f64 / time.as_secs_f64() / 1e6)
+}
+
+impl fmt::Display for Benchmark {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "Benchmark of {} with pattern:\n\"{}\"",
+            self.operation, self.pattern
+        )?;
+        writeln!(
+            f,
+            "On {} bytes, {} results, {} runs\n",
+            self.bytes, self.results, self.runs
+        )?;
+        writeln!(f, "{:<13}{:>12}{:>12}{:>12}", "", "min", "median", "p99")?;
+        for (what, s) in [
+            ("compile wall", self.compile.wall),
+            ("compile CPU", self.compile.cpu),
+            ("search wall", self.search.wall),
+            ("search CPU", self.search.cpu),
+        ]
+        .iter()
+        {
+            writeln!(
+                f,
+                "{:<13}{:>12}{:>12}{:>12}",
+                what,
+                format!("{:.1?}", s.min),
+                format!("{:.1?}", s.median),
+                format!("{:.1?}", s.p99)
+            )?;
+        }
+        match self.throughput {
+            Some(mb) => write!(f, "\nThroughput: {:.1} MB/s at the median search time", mb),
+            None => write!(
+                f,
+                "\nThroughput: the median search was too quick to measure"
+            ),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn micros(times: impl Iterator<Item = u64>) -> Vec<Duration> {
+        times.map(Duration::from_micros).collect()
+    }
+
+    #[test]
+    fn p99_is_the_time_99_percent_of_runs_stay_within() {
+        let stats = Stats::of(micros((1..=100).rev()));
+        assert_eq!(stats.min, Duration::from_micros(1));
+        assert_eq!(stats.median, Duration::from_micros(51));
+        assert_eq!(stats.p99, Duration::from_micros(99));
+        // 99% of 101 runs is 99.99, so it takes all 100 fastest
+        assert_eq!(Stats::of(micros(1..=101)).p99, Duration::from_micros(100));
+        assert_eq!(Stats::of(micros(1..=200)).p99, Duration::from_micros(198));
+    }
+
+    #[test]
+    fn a_single_run_is_every_statistic() {
+        let stats = Stats::of(micros(std::iter::once(7)));
+        let t = Duration::from_micros(7);
+        assert_eq!(stats, Stats { min: t, median: t, p99: t });
+    }
+
+    #[test]
+    fn throughput_needs_a_measurable_time() {
+        assert_eq!(throughput(2_000_000, Duration::from_secs(1)), Some(2.0));
+        assert_eq!(throughput(1000, Duration::from_micros(1)), Some(1000.0));
+        assert_eq!(throughput(1000, Duration::ZERO), None);
+        assert_eq!(throughput(0, Duration::ZERO), None);
+    }
+
+    #[test]
+    fn a_benchmark_counts_bytes_and_results() {
+        let eval = Evaluation::new(Operation::Find, r"\d", "a1b2c3");
+        let b = Benchmark::run(&eval, 3).unwrap();
+        assert_eq!((b.runs, b.bytes, b.results), (3, 6, 3));
+        assert!(b.compile.wall.min <= b.compile.wall.p99);
+        assert!(b.to_string().contains("On 6 bytes, 3 results, 3 runs"));
+        assert!(Benchmark::run(&Evaluation::new(Operation::Find, "(", ""), 3).is_err());
+    }
+}
@@ -0,0 +1,500 @@
+use std::{fmt, ops::Range, str::FromStr};
+
+use regex::bytes::{Captures, Regex};
+use serde::{Serialize, Serializer};
+
+use crate::{
+    eval::{Evaluation, Operation},
+    options::{Flags, Limits},
+    result::PatternError,
+};
+
+/// Bytes per line of a hex dump.
+const DUMP_WIDTH: usize = 16;
+
+/// How much of a long match or group is dumped.
+const DUMP_BYTES: usize = 256;
+
+/// How many matches the GUIs dump from a [`ByteSearch`] before stopping.
+/// Each takes several lines of hex, so this is far below
+/// [`MAX_FILE_RESULTS`](crate::eval::MAX_FILE_RESULTS).
+pub const MAX_BYTE_RESULTS: usize = 1_000;
+
+/// The ways of writing bytes down as text that a [`ByteSearch`] can be
+/// given them in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Encoding {
+    Hex,
+    Base64,
+}
+
+impl Encoding {
+    pub const ALL: [Encoding; 2] = [Encoding::Hex, Encoding::Base64];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Encoding::Hex => "hex",
+            Encoding::Base64 => "base64",
+        }
+    }
+
+    /// The name as the GUIs show it.
+    pub fn title(&self) -> &'static str {
+        match self {
+            Encoding::Hex => "Hex",
+            Encoding::Base64 => "Base64",
+        }
+    }
+
+    /// The bytes `s` stands for.  Whitespace is skipped so that dumps can be
+    /// pasted as they are; hex digits may be in either case, and base64 in
+    /// the standard or the URL safe alphabet, padded or not.
+    pub fn decode(&self, s: &str) -> Result<Vec<u8>, String> {
+        match self {
+            Encoding::Hex => hex(s),
+            Encoding::Base64 => base64(s),
+        }
+    }
+}
+
+impl fmt::Display for Encoding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// Parses the names given by [`Encoding::name`].
+impl FromStr for Encoding {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Encoding::ALL
+            .iter()
+            .find(|e| e.name() == s)
+            .copied()
+            .ok_or_else(|| format!("unknown encoding \"{}\"", s))
+    }
+}
+
+fn hex(s: &str) -> Result<Vec<u8>, String> {
+    let digits: Vec<(usize, char)> = s
+        .char_indices()
+        .filter(|(_, c)| !c.is_whitespace())
+        .collect();
+    let mut bytes = Vec::with_capacity(digits.len() / 2);
+    for pair in digits.chunks(2) {
+        let mut byte = 0;
+        for (i, c) in pair.iter() {
+            let d = c
+                .to_digit(16)
+                .ok_or_else(|| format!("{:?} at {} is not a hex digit", c, i))?;
+            byte = byte << 4 | d as u8;
+        }
+        if pair.len() < 2 {
+            return Err("the last byte is missing a hex digit".into());
+        }
+        bytes.push(byte);
+    }
+    Ok(bytes)
+}
+
+fn base64(s: &str) -> Result<Vec<u8>, String> {
+    let mut bytes = vec![];
+    let (mut bits, mut held) = (0u32, 0);
+    let mut padded = false;
+    for (i, c) in s.char_indices().filter(|(_, c)| !c.is_whitespace()) {
+        let v = match c {
+            '=' => {
+                padded = true;
+                continue;
+            }
+            _ if padded => return Err(format!("{:?} at {} comes after the padding", c, i)),
+            'A'..='Z' => c as u32 - 'A' as u32,
+            'a'..='z' => c as u32 - 'a' as u32 + 26,
+            '0'..='9' => c as u32 - '0' as u32 + 52,
+            '+' | '-' => 62,
+            '/' | '_' => 63,
+            _ => return Err(format!("{:?} at {} is not base64", c, i)),
+        };
+        bits = bits << 6 | v;
+        held += 6;
+        if held >= 8 {
+            held -= 8;
+            bytes.push((bits >> held) as u8);
+            bits &= (1 << held) - 1;
+        }
+    }
+    // one character alone at the end is too few bits for a byte
+    if held == 6 {
+        return Err("the base64 ends part way through a byte".into());
+    }
+    Ok(bytes)
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// Bytes in JSON as a hex string rather than an array of numbers.
+fn hex_string<S: Serializer>(bytes: &[u8], s: S) -> Result<S::Ok, S::Error> {
+    s.serialize_str(&to_hex(bytes))
+}
+
+fn hex_option<S: Serializer>(bytes: &Option<Vec<u8>>, s: S) -> Result<S::Ok, S::Error> {
+    bytes.as_deref().map(to_hex).serialize(s)
+}
+
+/// One capture group of a [`ByteMatch`].  `range` and `bytes` are `None`
+/// when the group did not participate in the match.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ByteGroup {
+    pub index: usize,
+    pub name: Option<String>,
+    pub range: Option<Range<usize>>,
+    #[serde(serialize_with = "hex_option")]
+    pub bytes: Option<Vec<u8>>,
+}
+
+impl ByteGroup {
+    /// How the group is labelled in text output, as [`Group::label`] does.
+    ///
+    /// [`Group::label`]: crate::result::Group::label
+    pub fn label(&self) -> String {
+        match &self.name {
+            Some(n) => format!("group[{}]<{}>", self.index, n),
+            None => format!("group[{}]", self.index),
+        }
+    }
+}
+
+/// A single match, at byte offsets into the haystack.  `groups` holds every
+/// capture group, group 0 included, when the operation asked for captures
+/// and is empty otherwise.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ByteMatch {
+    pub range: Range<usize>,
+    #[serde(serialize_with = "hex_string")]
+    pub bytes: Vec<u8>,
+    pub groups: Vec<ByteGroup>,
+}
+
+fn captured(re: &Regex, caps: &Captures) -> ByteMatch {
+    let whole = caps.get(0).unwrap();
+    let groups = re
+        .capture_names()
+        .enumerate()
+        .map(|(i, name)| {
+            let c = caps.get(i);
+            ByteGroup {
+                index: i,
+                name: name.map(|n| n.to_string()),
+                range: c.map(|c| c.range()),
+                bytes: c.map(|c| c.as_bytes().to_vec()),
+            }
+        })
+        .collect();
+    ByteMatch { range: whole.range(), bytes: whole.as_bytes().to_vec(), groups }
+}
+
+/// An operation run with `regex::bytes` on bytes that need not be UTF-8,
+/// such as a protocol dump or a binary file.  `Match` finds the first match
+/// with its groups, `Find` every match, and `FindCaptures` every match with
+/// its groups; no other operation can search bytes.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ByteSearch {
+    pub operation: Operation,
+    pub pattern: String,
+    pub haystack: Vec<u8>,
+    /// Where the bytes came from, e.g. `hex input` or a file, for the
+    /// results to say.
+    pub source: String,
+    /// Stop after this many matches.
+    pub max_results: Option<usize>,
+    pub flags: Flags,
+    pub limits: Limits,
+}
+
+impl ByteSearch {
+    /// Says why `operation` cannot search bytes when it cannot.
+    pub fn supported(operation: Operation) -> Result<(), String> {
+        match operation {
+            Operation::Match | Operation::Find | Operation::FindCaptures => Ok(()),
+            _ => Err(format!(
+                "{} cannot search bytes, only match, find and captures can",
+                operation
+            )),
+        }
+    }
+
+    pub fn new(
+        operation: Operation,
+        pattern: &str,
+        haystack: Vec<u8>,
+        source: &str,
+    ) -> Result<Self, String> {
+        ByteSearch::supported(operation)?;
+        Ok(ByteSearch {
+            operation,
+            pattern: pattern.into(),
+            haystack,
+            source: source.into(),
+            max_results: None,
+            flags: Flags::default(),
+            limits: Limits::default(),
+        })
+    }
+
+    pub fn with_flags(mut self, flags: Flags) -> Self {
+        self.flags = flags;
+        self
+    }
+
+    pub fn with_limits(mut self, limits: Limits) -> Self {
+        self.limits = limits;
+        self
+    }
+
+    pub fn with_max_results(mut self, max: usize) -> Self {
+        self.max_results = Some(max);
+        self
+    }
+
+    /// An evaluation of the same operation, pattern, flags and limits, for
+    /// showing what is wrong with the pattern the way the frontends already
+    /// do.
+    pub fn evaluation(&self) -> Evaluation {
+        Evaluation::new(self.operation, &self.pattern, "")
+            .with_flags(self.flags)
+            .with_limits(self.limits)
+    }
+
+    pub fn compile(&self) -> Result<Regex, PatternError> {
+        let mut b = self.flags.bytes_builder(&self.pattern);
+        self.limits.apply_bytes(&mut b);
+        b.build()
+            .map_err(|e| PatternError::new_bytes(&self.pattern, &self.flags, &self.limits, &e))
+    }
+
+    pub fn run(&self) -> Result<ByteMatches, PatternError> {
+        let re = self.compile()?;
+        let hay = self.haystack.as_slice();
+        let max = self.max_results.unwrap_or(usize::MAX);
+        let (matches, cut_off) = match self.operation {
+            Operation::Match => {
+                let found = re.captures(hay).map(|caps| captured(&re, &caps));
+                (found.into_iter().collect(), false)
+            }
+            Operation::Find => {
+                let mut found = re.find_iter(hay);
+                let matches = found
+                    .by_ref()
+                    .take(max)
+                    .map(|m| ByteMatch {
+                        range: m.range(),
+                        bytes: m.as_bytes().to_vec(),
+                        groups: vec![],
+                    })
+                    .collect();
+                (matches, found.next().is_some())
+            }
+            // FindCaptures, the only other operation new takes
+            _ => {
+                let mut found = re.captures_iter(hay);
+                let matches = found
+                    .by_ref()
+                    .take(max)
+                    .map(|caps| captured(&re, &caps))
+                    .collect();
+                (matches, found.next().is_some())
+            }
+        };
+        Ok(ByteMatches {
+            operation: self.operation,
+            pattern: format!("{}{}", self.flags.inline(), self.pattern),
+            source: self.source.clone(),
+            len: hay.len(),
+            max_results: self.max_results,
+            cut_off,
+            matches,
+        })
+    }
+}
+
+/// What a [`ByteSearch`] found.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ByteMatches {
+    pub operation: Operation,
+    /// The pattern with its flags inline.
+    pub pattern: String,
+    pub source: String,
+    /// Size of the haystack.
+    pub len: usize,
+    /// The cap the search ran with, to say when it was reached.
+    pub max_results: Option<usize>,
+    /// The cap left matches out.
+    pub cut_off: bool,
+    pub matches: Vec<ByteMatch>,
+}
+
+/// Writes `bytes`, found at `offset`, as `hexdump -C` does: sixteen to a
+/// line, in hex and then as ASCII, after the offset of the first of them.
+fn dump(f: &mut fmt::Formatter<'_>, indent: &str, offset: usize, bytes: &[u8]) -> fmt::Result {
+    let shown = &bytes[..bytes.len().min(DUMP_BYTES)];
+    for (i, line) in shown.chunks(DUMP_WIDTH).enumerate() {
+        let hex: Vec<String> = line.iter().map(|b| format!("{:02x}", b)).collect();
+        let ascii: String = line
+            .iter()
+            .map(|&b| {
+                if b.is_ascii_graphic() || b == b' ' {
+                    b as char
+                } else {
+                    '.'
+                }
+            })
+            .collect();
+        writeln!(
+            f,
+            "{}{:08x}  {:<w$}  |{}|",
+            indent,
+            offset + i * DUMP_WIDTH,
+            hex.join(" "),
+            ascii,
+            w = DUMP_WIDTH * 3 - 1
+        )?;
+    }
+    if shown.len() < bytes.len() {
+        writeln!(f, "{}... {} bytes in all", indent, bytes.len())?;
+    }
+    Ok(())
+}
+
+/// Lists each match at its offsets with a dump of its bytes, and below it
+/// each group that took part, dumped the same way.
+impl fmt::Display for ByteMatches {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "Bytes {} with pattern:\n\"{}\"\nIn {} ({} bytes)\n",
+            self.operation, self.pattern, self.source, self.len
+        )?;
+        if self.matches.is_empty() {
+            return write!(f, "Found nothing");
+        }
+        for (i, m) in self.matches.iter().enumerate() {
+            writeln!(
+                f,
+                "Match {} at {:#x}..{:#x} ({} bytes)",
+                i,
+                m.range.start,
+                m.range.end,
+                m.bytes.len()
+            )?;
+            dump(f, "    ", m.range.start, &m.bytes)?;
+            for g in m.groups.iter().skip(1) {
+                match (&g.range, &g.bytes) {
+                    (Some(r), Some(bytes)) => {
+                        writeln!(f, "  {} at {:#x}..{:#x}", g.label(), r.start, r.end)?;
+                        dump(f, "    ", r.start, bytes)?;
+                    }
+                    _ => writeln!(f, "  {} = None", g.label())?,
+                }
+            }
+        }
+        match self.max_results {
+            Some(max) if self.cut_off => {
+                write!(f, "\nStopped after the first {} results", max)
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hex_skips_whitespace_and_takes_either_case() {
+        assert_eq!(
+            Encoding::Hex.decode("de AD\nbe ef").unwrap(),
+            vec![0xde, 0xad, 0xbe, 0xef]
+        );
+        assert!(Encoding::Hex.decode("abc").is_err());
+        assert!(Encoding::Hex.decode("zz").is_err());
+    }
+
+    #[test]
+    fn base64_takes_both_alphabets_padded_or_not() {
+        assert_eq!(
+            Encoding::Base64.decode("3q2+7w==").unwrap(),
+            vec![0xde, 0xad, 0xbe, 0xef]
+        );
+        assert_eq!(
+            Encoding::Base64.decode("3q2-7w").unwrap(),
+            vec![0xde, 0xad, 0xbe, 0xef]
+        );
+        assert_eq!(Encoding::Base64.decode("").unwrap(), Vec::<u8>::new());
+        assert!(Encoding::Base64.decode("QQ=x").is_err());
+        assert!(Encoding::Base64.decode("QUJDR").is_err());
+    }
+
+    #[test]
+    fn only_match_find_and_captures_search_bytes() {
+        assert!(ByteSearch::new(Operation::FindCaptures, "a", vec![], "").is_ok());
+        let e = ByteSearch::new(Operation::Split, "a", vec![], "").unwrap_err();
+        assert!(e.starts_with("split cannot search bytes"));
+    }
+
+    #[test]
+    fn captures_bytes_that_are_not_utf8() {
+        let search = ByteSearch::new(
+            Operation::FindCaptures,
+            r"(?-u)\xff(?P<rest>.)|(z)",
+            vec![b'a', 0xff, b'b', 0xff, 0xfe],
+            "test",
+        )
+        .unwrap();
+        let found = search.run().unwrap();
+        assert_eq!(found.matches.len(), 2);
+        let m = &found.matches[1];
+        assert_eq!(m.range, 3..5);
+        assert_eq!(m.groups[1].label(), "group[1]<rest>");
+        assert_eq!(m.groups[1].bytes, Some(vec![0xfe]));
+        assert_eq!(m.groups[2].range, None);
+        let json = serde_json::to_value(&found).unwrap();
+        assert_eq!(json["matches"][1]["bytes"], "fffe");
+    }
+
+    #[test]
+    fn says_nothing_of_a_cap_that_left_nothing_out() {
+        for op in [Operation::Match, Operation::Find].iter() {
+            let search = ByteSearch::new(*op, "a", b"a".to_vec(), "test")
+                .unwrap()
+                .with_max_results(1);
+            let found = search.run().unwrap();
+            assert!(!found.cut_off);
+            assert!(!found.to_string().contains("Stopped after"));
+        }
+    }
+
+    #[test]
+    fn says_when_the_cap_was_reached() {
+        let search = ByteSearch::new(Operation::Find, "a", b"aaa".to_vec(), "test")
+            .unwrap()
+            .with_max_results(2);
+        let found = search.run().unwrap();
+        assert_eq!(found.matches.len(), 2);
+        assert!(found
+            .to_string()
+            .ends_with("Stopped after the first 2 results"));
+    }
+
+    #[test]
+    fn dumps_offsets_hex_and_ascii() {
+        let search = ByteSearch::new(Operation::Find, "b", b"ab\n".to_vec(), "test").unwrap();
+        let text = search.run().unwrap().to_string();
+        assert!(text.contains("Match 0 at 0x1..0x2 (1 bytes)"));
+        assert!(text.contains("    00000001  62"));
+        assert!(text.contains("|b|"));
+    }
+}
@@ -0,0 +1,445 @@
+use std::{
+    error::Error,
+    fs,
+    io::{self, Read},
+    path::Path,
+    process::exit,
+    sync::Arc,
+};
+
+use re_tester::{
+    bytes::{ByteSearch, Encoding},
+    codegen::{self, snippet, Language},
+    compare::Comparison,
+    Benchmark, Evaluation, Execution, Flags, Format, History, Limits, Operation, Outcome, Suite,
+    TestFile,
+};
+
+const USAGE: &str = "\
+Usage: re_tester <OPERATION> [OPTIONS] <PATTERN> [STRING]
+       re_tester history
+       re_tester rerun [N] [--json]
+       re_tester suite <PATH> [--json | --rust]
+       re_tester compare [OPTIONS] <PATTERN_A> <PATTERN_B> [STRING]
+
+Operations: match, find, captures, split, replace, lines, explain, inspect,
+            set
+
+The string to test comes from STRING, from --file, or else from stdin;
+explain and inspect look at the pattern alone and need no string.  `set`
+takes each line of the pattern, best read with -e, as a pattern of its own
+and lists which of them match each line of the string, as a RegexSet does.
+Every run is added to the history in ~/.re_test/state.json, shared with the
+GUIs; `history` lists it and `rerun` runs entry N, or the latest, again.
+`suite` runs the cases of a test suite saved from one of the GUIs, or with
+--rust prints them as Rust tests.  `compare` tries two patterns on each line
+of the string, or on each case of --suite PATH, and marks where they differ.
+match, find and captures also search bytes that need not be UTF-8 with
+--hex, --base64 or --binary, turning the u flag off with (?-u) or
+--no-unicode to match any byte; each match is shown at its offsets with a
+hex and ASCII dump.
+
+Options:
+  -f, --file PATH         test against the contents of PATH
+  -e, --pattern-file PATH read the pattern from PATH, - for stdin
+  -r, --replace TEXT      replacement for replace ($1, ${name}, $$)
+  -i, -m, -s, -U, -x      case insensitive, multi-line, dot matches \\n,
+                          swap greed, ignore whitespace
+      --no-unicode        turn the u flag off
+      --size-limit N      compiled program size limit in bytes
+      --dfa-size-limit N  lazy DFA cache limit in bytes
+      --nest-limit N      nesting limit
+      --no-history        do not record the run
+      --json              print the evaluation and its results as JSON
+      --code LANG         print a rust, python, go or java program doing the
+                          same instead, warning about what LANG lacks
+      --bench N           time N compiles and N runs instead, with min,
+                          median and 99th percentile wall and CPU times
+      --hex, --base64     search the bytes the string, file or stdin spells
+                          out in hex or base64
+      --binary            search the string, file or stdin as raw bytes
+  -h, --help              show this help
+
+Exits with 0 when something matched, 1 when nothing did or the pattern is
+invalid, and 2 on any other error.  For `suite`, 0 means every case passed,
+and for `compare` that the patterns agree on every input.";
+
+/// What the command line asked for, before any input is read.
+struct Args {
+    operation: Operation,
+    pattern: Option<String>,
+    pattern_file: Option<String>,
+    string: Option<String>,
+    file: Option<String>,
+    replacement: String,
+    flags: Flags,
+    limits: Limits,
+    record: bool,
+    format: Format,
+    code: Option<Language>,
+    bench: Option<usize>,
+    /// Search bytes, decoded from this or else raw when `binary` is set.
+    encoding: Option<Encoding>,
+    binary: bool,
+    /// The second pattern of `compare`.
+    pattern_b: Option<String>,
+    suite: Option<String>,
+}
+
+fn value(args: &mut impl Iterator<Item = String>, opt: &str) -> Result<String, Box<dyn Error>> {
+    args.next()
+        .ok_or_else(|| format!("{} needs a value", opt).into())
+}
+
+fn number<T: std::str::FromStr>(
+    args: &mut impl Iterator<Item = String>,
+    opt: &str,
+) -> Result<T, Box<dyn Error>> {
+    let v = value(args, opt)?;
+    v.parse()
+        .map_err(|_| format!("{} needs a number, not \"{}\"", opt, v).into())
+}
+
+/// Reads the options and positional arguments, which for `compare` hold a
+/// second pattern after the first.
+fn parse(
+    operation: Operation,
+    compare: bool,
+    mut args: impl Iterator<Item = String>,
+) -> Result<Args, Box<dyn Error>> {
+    let mut a = Args {
+        operation,
+        pattern: None,
+        pattern_file: None,
+        string: None,
+        file: None,
+        replacement: String::new(),
+        flags: Flags::default(),
+        limits: Limits::default(),
+        record: true,
+        format: Format::Plain,
+        code: None,
+        bench: None,
+        encoding: None,
+        binary: false,
+        pattern_b: None,
+        suite: None,
+    };
+    let mut positional = vec![];
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "-f" | "--file" => a.file = Some(value(&mut args, &arg)?),
+            "-e" | "--pattern-file" => a.pattern_file = Some(value(&mut args, &arg)?),
+            "-r" | "--replace" => a.replacement = value(&mut args, &arg)?,
+            "-i" => a.flags.case_insensitive = true,
+            "-m" => a.flags.multi_line = true,
+            "-s" => a.flags.dot_matches_new_line = true,
+            "-U" => a.flags.swap_greed = true,
+            "-x" => a.flags.ignore_whitespace = true,
+            "--no-unicode" => a.flags.unicode = false,
+            "--size-limit" => a.limits.size_limit = number(&mut args, &arg)?,
+            "--dfa-size-limit" => a.limits.dfa_size_limit = number(&mut args, &arg)?,
+            "--nest-limit" => a.limits.nest_limit = number(&mut args, &arg)?,
+            "--no-history" => a.record = false,
+            "--json" => a.format = Format::Json,
+            "--code" => a.code = Some(value(&mut args, &arg)?.parse()?),
+            "--bench" => a.bench = Some(number(&mut args, &arg)?),
+            "--hex" => a.encoding = Some(Encoding::Hex),
+            "--base64" => a.encoding = Some(Encoding::Base64),
+            "--binary" => a.binary = true,
+            "--suite" if compare => a.suite = Some(value(&mut args, &arg)?),
+            "--" => positional.extend(args.by_ref()),
+            s if s.starts_with('-') && s != "-" => {
+                return Err(format!("unknown option {}", s).into())
+            }
+            _ => positional.push(arg),
+        }
+    }
+    let mut positional = positional.into_iter();
+    if a.pattern_file.is_none() {
+        a.pattern = Some(positional.next().ok_or("no pattern given")?);
+    }
+    if compare {
+        a.pattern_b = Some(positional.next().ok_or("no second pattern given")?);
+    }
+    a.string = positional.next();
+    if positional.next().is_some() {
+        return Err("too many arguments".into());
+    }
+    if a.string.is_some() && a.file.is_some() {
+        return Err("give either a string or --file, not both".into());
+    }
+    if a.suite.is_some() && (a.string.is_some() || a.file.is_some()) {
+        return Err("give either a string or --suite, not both".into());
+    }
+    Ok(a)
+}
+
+fn read_stdin() -> Result<String, Box<dyn Error>> {
+    let mut s = String::new();
+    io::stdin().read_to_string(&mut s)?;
+    Ok(s)
+}
+
+fn pattern(a: &Args) -> Result<String, Box<dyn Error>> {
+    let pattern = match (&a.pattern, &a.pattern_file) {
+        (Some(p), _) => p.clone(),
+        (None, Some(f)) if f == "-" => read_stdin()?,
+        (None, Some(f)) => fs::read_to_string(f)?,
+        (None, None) => unreachable!("parse insists on a pattern"),
+    };
+    // a pattern read from a file usually ends with a newline nobody meant
+    let pattern = pattern.strip_suffix('\n').unwrap_or(&pattern);
+    let pattern = pattern.strip_suffix('\r').unwrap_or(pattern);
+    Ok(pattern.to_string())
+}
+
+fn evaluation(a: &Args) -> Result<Evaluation, Box<dyn Error>> {
+    let pattern = pattern(a)?;
+    let string = match (&a.string, &a.file) {
+        (Some(s), _) => s.clone(),
+        (None, Some(_)) => String::new(),
+        (None, None)
+            if a.suite.is_some()
+                || matches!(a.operation, Operation::Explain | Operation::Inspect) =>
+        {
+            String::new()
+        }
+        (None, None) if a.pattern_file.as_deref() == Some("-") => {
+            return Err("stdin cannot hold both the pattern and the string".into())
+        }
+        (None, None) => read_stdin()?,
+    };
+    let eval = Evaluation::new(a.operation, &pattern, &string)
+        .with_replacement(&a.replacement)
+        .with_flags(a.flags)
+        .with_limits(a.limits);
+    Ok(match &a.file {
+        Some(f) => eval.with_file(Arc::new(TestFile::open(Path::new(f))?)),
+        None => eval,
+    })
+}
+
+/// Runs `eval`, prints what the GUIs would show, and gives the exit code.
+fn run(eval: &Evaluation, format: Format, hist: Option<&History>) -> Result<i32, Box<dyn Error>> {
+    let outcome = eval.run();
+    println!("{}", format.render(eval, &outcome).trim_end());
+    if let Some(hist) = hist {
+        hist.add(Execution::from(eval))?;
+    }
+    Ok(if outcome.is_failure() { 1 } else { 0 })
+}
+
+/// Prints a program doing what `eval` does, or what is wrong with the
+/// pattern.
+fn code(eval: &Evaluation, language: Language) -> Result<i32, Box<dyn Error>> {
+    match snippet(language, eval) {
+        Ok(snippet) => print!("{}", snippet),
+        Err(e) => {
+            let outcome = Outcome::PatternError(e);
+            println!("{}", Format::Plain.render(eval, &outcome).trim_end());
+            return Ok(1);
+        }
+    }
+    Ok(0)
+}
+
+/// Times `eval` over `runs` runs and prints the statistics, or what is wrong
+/// with the pattern.  Benchmarks are not recorded in the history.
+fn bench(eval: &Evaluation, runs: usize, format: Format) -> Result<i32, Box<dyn Error>> {
+    match Benchmark::run(eval, runs) {
+        Ok(b) if format == Format::Json => println!("{}", serde_json::to_string_pretty(&b)?),
+        Ok(b) => println!("{}", b),
+        Err(e) => {
+            let outcome = Outcome::PatternError(e);
+            println!("{}", format.render(eval, &outcome).trim_end());
+            return Ok(1);
+        }
+    }
+    Ok(0)
+}
+
+/// Searches the string, file or stdin as bytes with `regex::bytes`, decoded
+/// when `--hex` or `--base64` was given.  Byte searches are not recorded in
+/// the history.
+fn bytes(a: &Args) -> Result<i32, Box<dyn Error>> {
+    ByteSearch::supported(a.operation)?;
+    let pattern = pattern(a)?;
+    let (raw, source) = match (&a.string, &a.file) {
+        (Some(s), _) => (s.clone().into_bytes(), "the string".to_string()),
+        (None, Some(f)) => (fs::read(f)?, format!("file {}", f)),
+        (None, None) if a.pattern_file.as_deref() == Some("-") => {
+            return Err("stdin cannot hold both the pattern and the bytes".into())
+        }
+        (None, None) => {
+            let mut raw = vec![];
+            io::stdin().read_to_end(&mut raw)?;
+            (raw, "stdin".to_string())
+        }
+    };
+    let (haystack, source) = match a.encoding {
+        Some(encoding) => {
+            let text = String::from_utf8(raw)
+                .map_err(|_| format!("the {} in {} is not text", encoding, source))?;
+            let decoded = encoding
+                .decode(&text)
+                .map_err(|e| format!("the {} in {} is invalid: {}", encoding, source, e))?;
+            (decoded, format!("{} from {}", encoding, source))
+        }
+        None => (raw, source),
+    };
+    let search = ByteSearch::new(a.operation, &pattern, haystack, &source)?
+        .with_flags(a.flags)
+        .with_limits(a.limits);
+    let found = match search.run() {
+        Ok(m) => m,
+        Err(e) => {
+            let outcome = Outcome::PatternError(e);
+            println!(
+                "{}",
+                a.format.render(&search.evaluation(), &outcome).trim_end()
+            );
+            return Ok(1);
+        }
+    };
+    if a.format == Format::Json {
+        println!("{}", serde_json::to_string_pretty(&found)?);
+    } else {
+        println!("{}", found.to_string().trim_end());
+    }
+    Ok(if found.matches.is_empty() { 1 } else { 0 })
+}
+
+fn load_history() -> Result<History, Box<dyn Error>> {
+    let hist = History::new();
+    // no state file just means nothing has been run yet
+    if let Err(e) = hist.load_from() {
+        match e.downcast_ref::<io::Error>() {
+            Some(io) if io.kind() == io::ErrorKind::NotFound => {}
+            _ => return Err(format!("Could not load prior state/history: {}", e).into()),
+        }
+    }
+    Ok(hist)
+}
+
+fn main_with_args(mut args: impl Iterator<Item = String>) -> Result<i32, Box<dyn Error>> {
+    let command = match args.next() {
+        None => return Err(USAGE.into()),
+        Some(c) if c == "-h" || c == "--help" => {
+            println!("{}", USAGE);
+            return Ok(0);
+        }
+        Some(c) => c,
+    };
+    match command.as_str() {
+        "history" => {
+            print!("{}", load_history()?.to_str("\n"));
+            Ok(0)
+        }
+        "rerun" => {
+            let hist = load_history()?;
+            let mut format = Format::Plain;
+            let mut entry = None;
+            for arg in args {
+                match arg.as_str() {
+                    "--json" => format = Format::Json,
+                    n => {
+                        entry = Some(
+                            n.parse()
+                                .map_err(|_| format!("not a history entry: {}", n))?,
+                        )
+                    }
+                }
+            }
+            let exe = match entry {
+                Some(n) => hist.get(n),
+                None => hist.last(),
+            };
+            let exe = exe.ok_or("no such history entry")?;
+            run(&exe.evaluation()?, format, Some(&hist))
+        }
+        "suite" => {
+            let mut json = false;
+            let mut rust = false;
+            let mut path = None;
+            for arg in args {
+                match arg.as_str() {
+                    "--json" => json = true,
+                    "--rust" => rust = true,
+                    _ if path.is_none() => path = Some(arg),
+                    _ => return Err("too many arguments".into()),
+                }
+            }
+            let path = path.ok_or("no suite given")?;
+            let suite = Suite::load(Path::new(&path))?;
+            if rust {
+                return match codegen::rust_tests(&suite) {
+                    Ok(code) => {
+                        print!("{}", code);
+                        Ok(0)
+                    }
+                    Err(e) => {
+                        let outcome = Outcome::PatternError(e);
+                        println!(
+                            "{}",
+                            Format::Plain
+                                .render(&suite.evaluation(), &outcome)
+                                .trim_end()
+                        );
+                        Ok(1)
+                    }
+                };
+            }
+            let report = suite.run();
+            if json {
+                println!("{}", serde_json::to_string_pretty(&report)?);
+            } else {
+                println!("{}", report);
+            }
+            Ok(if report.all_passed() { 0 } else { 1 })
+        }
+        "compare" => {
+            let a = parse(Operation::Match, true, args)?;
+            let eval_a = evaluation(&a)?;
+            let mut eval_b = eval_a.clone();
+            eval_b.pattern = a.pattern_b.clone().unwrap_or_default();
+            let suite = match &a.suite {
+                Some(path) => Some(Suite::load(Path::new(path))?),
+                None => None,
+            };
+            let cases = suite.as_ref().map(|s| s.cases.as_slice());
+            let c = Comparison::run(&eval_a, &eval_b, cases);
+            if a.format == Format::Json {
+                println!("{}", serde_json::to_string_pretty(&c)?);
+            } else {
+                println!("{}", c);
+            }
+            Ok(if c.all_same() { 0 } else { 1 })
+        }
+        op => {
+            let a = parse(op.parse()?, false, args)?;
+            if a.encoding.is_some() || a.binary {
+                bytes(&a)
+            } else if let Some(language) = a.code {
+                code(&evaluation(&a)?, language)
+            } else if let Some(runs) = a.bench {
+                bench(&evaluation(&a)?, runs, a.format)
+            } else if a.record {
+                let hist = load_history()?;
+                run(&evaluation(&a)?, a.format, Some(&hist))
+            } else {
+                run(&evaluation(&a)?, a.format, None)
+            }
+        }
+    }
+}
+
+fn main() {
+    match main_with_args(std::env::args().skip(1)) {
+        Ok(code) => exit(code),
+        Err(e) => {
+            eprintln!("re_tester: {}", e);
+            exit(2);
+        }
+    }
+}
@@ -0,0 +1,1146 @@
+use std::{
+    fmt::{self, Write},
+    str::FromStr,
+};
+
+use regex_syntax::ast::{
+    self, AssertionKind, Ast, ClassSetBinaryOp, ClassSetBinaryOpKind, ClassSetItem,
+    ClassUnicodeKind, ClassUnicodeOpKind, Flag, FlagsItemKind, GroupKind, HexLiteralKind,
+    LiteralKind, SpecialLiteralKind,
+};
+
+use crate::{
+    eval::{Evaluation, Operation},
+    options::Limits,
+    result::PatternError,
+    suite::{Expect, Suite},
+};
+
+/// `s` as a Rust raw string literal, with as many `#`s as it needs.
+pub fn rust_raw(s: &str) -> String {
+    let mut hashes = String::new();
+    while s.contains(&format!("\"{}", hashes)) {
+        hashes.push('#');
+    }
+    format!("r{0}\"{1}\"{0}", hashes, s)
+}
+
+/// `name` made into part of a function name: lower case, with runs of
+/// anything else than letters and digits turned into one `_`.
+fn identifier(name: &str) -> String {
+    let mut id = String::new();
+    for c in name.chars().flat_map(char::to_lowercase) {
+        if c.is_ascii_alphanumeric() {
+            id.push(c);
+        } else if !id.is_empty() && !id.ends_with('_') {
+            id.push('_');
+        }
+    }
+    id.trim_end_matches('_').to_string()
+}
+
+/// A Rust module holding the suite's pattern as a lazily compiled `Regex`,
+/// and a `#[test]` per case asserting what the case expects.  Flags are
+/// written inline into the pattern, and limits only appear when they differ
+/// from the defaults.
+pub fn rust_tests(suite: &Suite) -> Result<String, PatternError> {
+    // tests for a pattern that does not compile would only ever panic
+    suite.compile()?;
+    let pattern = rust_raw(&format!("{}{}", suite.flags.inline(), suite.pattern));
+    let mut s = String::new();
+    if suite.limits == Limits::default() {
+        s += "use regex::Regex;\n\n";
+        s += "lazy_static::lazy_static! {\n";
+        writeln!(
+            s,
+            "    static ref RE: Regex = Regex::new({}).unwrap();",
+            pattern
+        )
+        .unwrap();
+    } else {
+        let l = &suite.limits;
+        s += "use regex::{Regex, RegexBuilder};\n\n";
+        s += "lazy_static::lazy_static! {\n";
+        writeln!(
+            s,
+            "    static ref RE: Regex = RegexBuilder::new({})",
+            pattern
+        )
+        .unwrap();
+        writeln!(s, "        .size_limit({})", l.size_limit).unwrap();
+        writeln!(s, "        .dfa_size_limit({})", l.dfa_size_limit).unwrap();
+        writeln!(s, "        .nest_limit({})", l.nest_limit).unwrap();
+        s += "        .build()\n        .unwrap();\n";
+    }
+    s += "}\n\n#[cfg(test)]\nmod tests {\n    use super::*;\n";
+    for (i, case) in suite.cases.iter().enumerate() {
+        let name = match identifier(&case.name).as_str() {
+            "" => format!("case_{}", i),
+            id => format!("case_{}_{}", i, id),
+        };
+        write!(s, "\n    #[test]\n    fn {}() {{\n", name).unwrap();
+        match &case.expect {
+            Expect::Match => writeln!(s, "        assert!(RE.is_match({:?}));", case.input),
+            Expect::NoMatch => writeln!(s, "        assert!(!RE.is_match({:?}));", case.input),
+            Expect::Captures(groups) => {
+                writeln!(
+                    s,
+                    "        let caps = RE.captures({:?}).unwrap();",
+                    case.input
+                )
+                .unwrap();
+                for (key, text) in groups.iter() {
+                    let group = match key.parse::<usize>() {
+                        Ok(i) => format!("get({})", i),
+                        Err(_) => format!("name({:?})", key),
+                    };
+                    writeln!(
+                        s,
+                        "        assert_eq!(caps.{}.map(|m| m.as_str()), {:?});",
+                        group,
+                        text.as_deref()
+                    )
+                    .unwrap();
+                }
+                Ok(())
+            }
+        }
+        .unwrap();
+        s += "    }\n";
+    }
+    s += "}\n";
+    Ok(s)
+}
+
+/// The languages [`snippet`] writes code for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Language {
+    Rust,
+    Python,
+    Go,
+    Java,
+}
+
+impl Language {
+    pub const ALL: [Language; 4] = [
+        Language::Rust,
+        Language::Python,
+        Language::Go,
+        Language::Java,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Language::Rust => "rust",
+            Language::Python => "python",
+            Language::Go => "go",
+            Language::Java => "java",
+        }
+    }
+
+    /// The name as the GUIs show it.
+    pub fn title(&self) -> &'static str {
+        match self {
+            Language::Rust => "Rust",
+            Language::Python => "Python",
+            Language::Go => "Go",
+            Language::Java => "Java",
+        }
+    }
+
+    fn comment(&self) -> &'static str {
+        match self {
+            Language::Python => "#",
+            _ => "//",
+        }
+    }
+}
+
+impl fmt::Display for Language {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// Parses the names given by [`Language::name`].
+impl FromStr for Language {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Language::ALL
+            .iter()
+            .find(|l| l.name() == s)
+            .copied()
+            .ok_or_else(|| format!("unknown language \"{}\"", s))
+    }
+}
+
+/// A program doing what an evaluation does, in some language.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Snippet {
+    pub language: Language,
+    pub code: String,
+    /// Parts of the pattern the language has no equivalent for, or reads
+    /// differently; the code is written as if it had them.
+    pub warnings: Vec<String>,
+}
+
+/// The code with the warnings as comments above it.
+impl fmt::Display for Snippet {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for w in self.warnings.iter() {
+            writeln!(f, "{} warning: {}", self.language.comment(), w)?;
+        }
+        if !self.warnings.is_empty() {
+            writeln!(f)?;
+        }
+        f.write_str(&self.code)
+    }
+}
+
+/// What the pattern uses that some dialects lack.  Only what is written
+/// in the pattern is recorded; the flags of the evaluation are checked on
+/// their own.
+#[derive(Debug, Default)]
+struct Features {
+    /// Each `\p` class as written, with its kind.
+    unicode_classes: Vec<(String, ClassUnicodeKind)>,
+    perl_classes: bool,
+    word_boundary: bool,
+    ascii_classes: bool,
+    nested_classes: bool,
+    class_intersection: bool,
+    /// `--` or `~~` in a class.
+    class_difference: bool,
+    /// `\x{...}` and the like.
+    hex_brace: bool,
+    /// `\u0061`
+    hex_short: bool,
+    /// `\U00000061`
+    hex_long: bool,
+    vertical_tab: bool,
+    end_text: bool,
+    swap_greed: bool,
+    ignore_whitespace: bool,
+    unicode_off: bool,
+    /// Flags set somewhere past the start, as in `a(?i)b`.
+    late_flags: bool,
+    captures: bool,
+    /// Where each `(?P<name>` starts, and the name.
+    named_groups: Vec<(usize, String)>,
+}
+
+struct Collector<'a> {
+    pattern: &'a str,
+    /// Where `pattern` starts in the whole pattern, when it is one line of a
+    /// set.
+    offset: usize,
+    features: Features,
+}
+
+impl Collector<'_> {
+    fn flags(&mut self, flags: &ast::Flags) {
+        let f = &mut self.features;
+        let mut negated = false;
+        for item in flags.items.iter() {
+            match item.kind {
+                FlagsItemKind::Negation => negated = true,
+                FlagsItemKind::Flag(Flag::SwapGreed) => f.swap_greed = true,
+                FlagsItemKind::Flag(Flag::IgnoreWhitespace) if !negated => {
+                    f.ignore_whitespace = true
+                }
+                FlagsItemKind::Flag(Flag::Unicode) if negated => f.unicode_off = true,
+                _ => {}
+            }
+        }
+    }
+
+    fn literal(&mut self, lit: &ast::Literal) {
+        let f = &mut self.features;
+        match lit.kind {
+            LiteralKind::HexBrace(_) => f.hex_brace = true,
+            LiteralKind::HexFixed(HexLiteralKind::UnicodeShort) => f.hex_short = true,
+            LiteralKind::HexFixed(HexLiteralKind::UnicodeLong) => f.hex_long = true,
+            LiteralKind::Special(SpecialLiteralKind::VerticalTab) => f.vertical_tab = true,
+            _ => {}
+        }
+    }
+
+    fn unicode(&mut self, class: &ast::ClassUnicode) {
+        let written = &self.pattern[class.span.start.offset..class.span.end.offset];
+        self.features
+            .unicode_classes
+            .push((written.to_string(), class.kind.clone()));
+    }
+}
+
+impl ast::Visitor for Collector<'_> {
+    type Output = Features;
+    type Err = ();
+
+    fn finish(self) -> Result<Features, ()> {
+        Ok(self.features)
+    }
+
+    fn visit_pre(&mut self, ast: &Ast) -> Result<(), ()> {
+        match ast {
+            Ast::Flags(set) => {
+                self.features.late_flags |= set.span.start.offset > 0;
+                self.flags(&set.flags);
+            }
+            Ast::Group(g) => match &g.kind {
+                GroupKind::NonCapturing(flags) => self.flags(flags),
+                GroupKind::CaptureName(name) => {
+                    self.features.captures = true;
+                    self.features
+                        .named_groups
+                        .push((self.offset + g.span.start.offset, name.name.clone()));
+                }
+                GroupKind::CaptureIndex(_) => self.features.captures = true,
+            },
+            Ast::Literal(lit) => self.literal(lit),
+            Ast::Assertion(a) => match a.kind {
+                AssertionKind::EndText => self.features.end_text = true,
+                AssertionKind::WordBoundary | AssertionKind::NotWordBoundary => {
+                    self.features.word_boundary = true
+                }
+                _ => {}
+            },
+            Ast::Class(ast::Class::Unicode(c)) => self.unicode(c),
+            Ast::Class(ast::Class::Perl(_)) => self.features.perl_classes = true,
+            _ => {}
+        }
+        Ok(())
+    }
+
+    fn visit_class_set_item_pre(&mut self, item: &ClassSetItem) -> Result<(), ()> {
+        match item {
+            ClassSetItem::Literal(lit) => self.literal(lit),
+            ClassSetItem::Range(r) => {
+                self.literal(&r.start);
+                self.literal(&r.end);
+            }
+            ClassSetItem::Ascii(_) => self.features.ascii_classes = true,
+            ClassSetItem::Unicode(c) => self.unicode(c),
+            ClassSetItem::Perl(_) => self.features.perl_classes = true,
+            ClassSetItem::Bracketed(_) => self.features.nested_classes = true,
+            _ => {}
+        }
+        Ok(())
+    }
+
+    fn visit_class_set_binary_op_pre(&mut self, op: &ClassSetBinaryOp) -> Result<(), ()> {
+        match op.kind {
+            ClassSetBinaryOpKind::Intersection => self.features.class_intersection = true,
+            _ => self.features.class_difference = true,
+        }
+        Ok(())
+    }
+}
+
+/// A piece of a replacement template, read the way `Captures::expand`
+/// reads it.
+enum Part {
+    Literal(String),
+    Group(String),
+}
+
+fn template(replacement: &str) -> Vec<Part> {
+    let mut parts = vec![];
+    let mut literal = String::new();
+    let mut rest = replacement;
+    while let Some(i) = rest.find('$') {
+        literal += &rest[..i];
+        rest = &rest[i + 1..];
+        let name = if rest.starts_with('$') {
+            None
+        } else if let Some(braced) = rest.strip_prefix('{') {
+            braced.find('}').map(|end| (&braced[..end], end + 2))
+        } else {
+            let end = rest
+                .find(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))
+                .unwrap_or(rest.len());
+            Some((&rest[..end], end)).filter(|_| end > 0)
+        };
+        match name {
+            Some((name, len)) => {
+                if !literal.is_empty() {
+                    parts.push(Part::Literal(std::mem::take(&mut literal)));
+                }
+                parts.push(Part::Group(name.to_string()));
+                rest = &rest[len..];
+            }
+            None => {
+                literal.push('$');
+                rest = rest.strip_prefix('$').unwrap_or(rest);
+            }
+        }
+    }
+    literal += rest;
+    if !literal.is_empty() {
+        parts.push(Part::Literal(literal));
+    }
+    parts
+}
+
+/// Whether `s` can go in a raw literal that cannot hold `quote`.
+fn raw_safe(s: &str, quote: char) -> bool {
+    !s.contains(quote) && !s.chars().any(char::is_control)
+}
+
+/// `s` as a literal of a C like language, escaping whatever `escape` does
+/// not handle as `\x` hex.
+fn escaped(s: &str, escape: fn(char) -> Option<String>) -> String {
+    let mut lit = String::from("\"");
+    for c in s.chars() {
+        match c {
+            '\\' => lit += "\\\\",
+            '"' => lit += "\\\"",
+            '\n' => lit += "\\n",
+            '\r' => lit += "\\r",
+            '\t' => lit += "\\t",
+            c if c.is_control() => match escape(c) {
+                Some(e) => lit += &e,
+                None => write!(lit, "\\x{:02x}", c as u32).unwrap(),
+            },
+            c => lit.push(c),
+        }
+    }
+    lit.push('"');
+    lit
+}
+
+/// Whether `s` needs no escapes in a `"` literal.
+fn plain(s: &str) -> bool {
+    !s.contains(|c: char| c == '\\' || c == '"' || c.is_control())
+}
+
+fn python_str(s: &str) -> String {
+    if plain(s) {
+        return format!("\"{}\"", s);
+    }
+    // a raw string cannot end in an unpaired backslash
+    let unpaired = (s.len() - s.trim_end_matches('\\').len()) % 2 == 1;
+    for quote in ['"', '\''].iter() {
+        if raw_safe(s, *quote) && !unpaired {
+            return format!("r{0}{1}{0}", quote, s);
+        }
+    }
+    escaped(s, |_| None)
+}
+
+fn go_str(s: &str) -> String {
+    if plain(s) {
+        format!("\"{}\"", s)
+    } else if raw_safe(s, '`') {
+        format!("`{}`", s)
+    } else {
+        escaped(s, |_| None)
+    }
+}
+
+fn java_str(s: &str) -> String {
+    // Java has no \x; \u is read before the lexer sees the literal, so an
+    // escaped newline that way would end it
+    escaped(s, |c| {
+        if (c as u32) < 0o400 {
+            Some(format!("\\{:03o}", c as u32))
+        } else {
+            None
+        }
+    })
+}
+
+/// Code doing what `eval` does: compiling the pattern with its flags,
+/// reading the string or file, and running the operation on it.  Explain
+/// and inspect have no counterpart in code, so they get a match.  Only Rust
+/// has sets; the other languages try a list of patterns in turn.
+pub fn snippet(language: Language, eval: &Evaluation) -> Result<Snippet, PatternError> {
+    eval.prepare()?;
+    // the lines of a set are parsed one by one, as they are compiled
+    let parts = match eval.operation {
+        Operation::Set => eval.patterns(),
+        _ => vec![eval.pattern.as_str()],
+    };
+    let mut features = Features::default();
+    for part in parts {
+        let ast = ast::parse::ParserBuilder::new()
+            .ignore_whitespace(eval.flags.ignore_whitespace)
+            .nest_limit(eval.limits.nest_limit)
+            .build()
+            .parse(part)
+            .expect("the pattern compiled");
+        let offset = part.as_ptr() as usize - eval.pattern.as_ptr() as usize;
+        features = ast::visit(&ast, Collector { pattern: part, offset, features })
+            .expect("collecting cannot fail");
+    }
+    let mut warnings = vec![];
+    let code = match language {
+        Language::Rust => rust(eval),
+        Language::Python => python(eval, &features, &mut warnings),
+        Language::Go => go(eval, &features, &mut warnings),
+        Language::Java => java(eval, &features, &mut warnings),
+    };
+    Ok(Snippet { language, code, warnings })
+}
+
+fn rust(eval: &Evaluation) -> String {
+    let mut s = String::from("use regex::");
+    let (ty, pattern) = match eval.operation {
+        Operation::Set => {
+            let mut list = String::from("&[\n");
+            for p in eval.patterns() {
+                let p = format!("{}{}", eval.flags.inline(), p);
+                writeln!(list, "        {},", rust_raw(&p)).unwrap();
+            }
+            ("RegexSet", list + "    ]")
+        }
+        _ => ("Regex", rust_raw(&eval.effective_pattern())),
+    };
+    if eval.limits == Limits::default() {
+        write!(s, "{};\n\nfn main() {{\n", ty).unwrap();
+        writeln!(s, "    let re = {}::new({}).unwrap();", ty, pattern).unwrap();
+    } else {
+        let l = &eval.limits;
+        write!(s, "{}Builder;\n\nfn main() {{\n", ty).unwrap();
+        writeln!(s, "    let re = {}Builder::new({})", ty, pattern).unwrap();
+        writeln!(s, "        .size_limit({})", l.size_limit).unwrap();
+        writeln!(s, "        .dfa_size_limit({})", l.dfa_size_limit).unwrap();
+        writeln!(s, "        .nest_limit({})", l.nest_limit).unwrap();
+        s += "        .build()\n        .unwrap();\n";
+    }
+    match &eval.file {
+        Some(f) => {
+            let path = f.path().display().to_string();
+            writeln!(
+                s,
+                "    let text = std::fs::read_to_string({}).unwrap();",
+                rust_raw(&path)
+            )
+            .unwrap();
+            s += "    let text = text.as_str();\n";
+        }
+        None => writeln!(s, "    let text = {:?};", eval.string).unwrap(),
+    }
+    s += match eval.operation {
+        Operation::Match | Operation::Explain | Operation::Inspect => {
+            "    match re.captures(text) {\n        Some(caps) => println!(\"{:?}\", caps),\n        \
+             None => println!(\"no match\"),\n    }\n"
+        }
+        Operation::Find => {
+            "    for m in re.find_iter(text) {\n        \
+             println!(\"{} {} {}\", m.start(), m.end(), m.as_str());\n    }\n"
+        }
+        Operation::FindCaptures => {
+            "    for caps in re.captures_iter(text) {\n        println!(\"{:?}\", caps);\n    }\n"
+        }
+        Operation::Split => "    println!(\"{:?}\", re.split(text).collect::<Vec<_>>());\n",
+        Operation::Replace => "",
+        Operation::Lines => {
+            "    for (i, line) in text.lines().enumerate() {\n        if re.is_match(line) {\n            \
+             println!(\"{} {}\", i + 1, line);\n        }\n    }\n"
+        }
+        Operation::Set => {
+            "    for (i, line) in text.lines().enumerate() {\n        \
+             let matched: Vec<usize> = re.matches(line).into_iter().collect();\n        \
+             println!(\"{} {:?}\", i + 1, matched);\n    }\n"
+        }
+    };
+    if eval.operation == Operation::Replace {
+        writeln!(
+            s,
+            "    println!(\"{{}}\", re.replace_all(text, {}));",
+            rust_raw(&eval.replacement)
+        )
+        .unwrap();
+    }
+    s += "}\n";
+    s
+}
+
+fn python(eval: &Evaluation, f: &Features, warnings: &mut Vec<String>) -> String {
+    let flags = &eval.flags;
+    if !f.unicode_classes.is_empty() {
+        let classes: Vec<&str> = f.unicode_classes.iter().map(|(c, _)| c.as_str()).collect();
+        warnings.push(format!(
+            "Python's re has no Unicode classes like {}; the third party regex module does",
+            classes.join(" ")
+        ));
+    }
+    if f.ascii_classes {
+        warnings.push("Python has no [[:alpha:]] style classes".into());
+    }
+    if f.nested_classes || f.class_intersection || f.class_difference {
+        warnings.push("Python has no nested classes or class operations (&&, --, ~~)".into());
+    }
+    if f.hex_brace {
+        warnings.push("Python writes \\x{...} escapes as \\uXXXX or \\UXXXXXXXX".into());
+    }
+    if f.end_text {
+        warnings.push("Python writes \\z as \\Z".into());
+    }
+    if flags.swap_greed || f.swap_greed {
+        warnings.push("Python has no U flag; swap each repetition's greed by hand".into());
+    }
+    if f.unicode_off {
+        warnings.push(
+            "Python cannot turn Unicode off inside a pattern; (?a) at the start is nearest".into(),
+        );
+    }
+    if f.late_flags {
+        warnings.push(
+            "Python only allows flags like (?i) at the start; use a group like (?i:...)".into(),
+        );
+    }
+    if eval.operation == Operation::Split && f.captures {
+        warnings.push("Python's split also returns the text of each capture group".into());
+    }
+
+    let mut names = vec![];
+    for (set, name) in [
+        (flags.case_insensitive, "re.IGNORECASE"),
+        (flags.multi_line, "re.MULTILINE"),
+        (flags.dot_matches_new_line, "re.DOTALL"),
+        (flags.ignore_whitespace, "re.VERBOSE"),
+        (!flags.unicode, "re.ASCII"),
+    ]
+    .iter()
+    {
+        if *set {
+            names.push(*name);
+        }
+    }
+    let names = match names.as_slice() {
+        [] => String::new(),
+        names => format!(", {}", names.join(" | ")),
+    };
+    let mut s = String::from("import re\n\n");
+    match eval.operation {
+        Operation::Set => {
+            s += "patterns = [\n";
+            for p in eval.patterns() {
+                writeln!(s, "    re.compile({}{}),", python_str(p), names).unwrap();
+            }
+            s += "]\n";
+        }
+        _ => writeln!(
+            s,
+            "pattern = re.compile({}{})",
+            python_str(&eval.pattern),
+            names
+        )
+        .unwrap(),
+    }
+    match &eval.file {
+        Some(file) => {
+            let path = python_str(&file.path().display().to_string());
+            writeln!(
+                s,
+                "with open({}, encoding=\"utf-8\") as f:\n    text = f.read()",
+                path
+            )
+            .unwrap();
+        }
+        None => writeln!(s, "text = {}", python_str(&eval.string)).unwrap(),
+    }
+    s += match eval.operation {
+        // match would only try the start of the text
+        Operation::Match | Operation::Explain | Operation::Inspect => {
+            "m = pattern.search(text)\nif m:\n    print(m.group(0), m.groups())\nelse:\n    \
+             print(\"no match\")\n"
+        }
+        Operation::Find => "for m in pattern.finditer(text):\n    print(m.start(), m.end(), m.group(0))\n",
+        Operation::FindCaptures => {
+            "for m in pattern.finditer(text):\n    print(m.group(0), m.groups(), m.groupdict())\n"
+        }
+        Operation::Split => "print(pattern.split(text))\n",
+        Operation::Replace => "",
+        Operation::Lines => {
+            "for number, line in enumerate(text.splitlines(), 1):\n    if pattern.search(line):\n        \
+             print(number, line)\n"
+        }
+        Operation::Set => {
+            "for number, line in enumerate(text.splitlines(), 1):\n    \
+             print(number, [i for i, p in enumerate(patterns) if p.search(line)])\n"
+        }
+    };
+    if eval.operation == Operation::Replace {
+        let mut repl = String::new();
+        for part in template(&eval.replacement) {
+            match part {
+                Part::Literal(l) => repl += &l.replace('\\', "\\\\"),
+                Part::Group(g) => write!(repl, "\\g<{}>", g).unwrap(),
+            }
+        }
+        writeln!(s, "print(pattern.sub({}, text))", python_str(&repl)).unwrap();
+    }
+    s
+}
+
+fn go(eval: &Evaluation, f: &Features, warnings: &mut Vec<String>) -> String {
+    let flags = &eval.flags;
+    for (class, kind) in f.unicode_classes.iter() {
+        match kind {
+            ClassUnicodeKind::Named(name) if name.len() > 2 => warnings.push(format!(
+                "Go only knows general categories and scripts; check that {} is one",
+                class
+            )),
+            ClassUnicodeKind::NamedValue { .. } => {
+                warnings.push(format!("Go has no property classes like {}", class))
+            }
+            _ => {}
+        }
+    }
+    if flags.unicode && (f.perl_classes || f.word_boundary) {
+        warnings.push("Go's \\d, \\s, \\w and \\b only know ASCII".into());
+    }
+    if f.nested_classes || f.class_intersection || f.class_difference {
+        warnings.push("Go has no nested classes or class operations (&&, --, ~~)".into());
+    }
+    if f.hex_short || f.hex_long {
+        warnings.push("Go writes \\u and \\U escapes as \\x{...}".into());
+    }
+    if flags.ignore_whitespace || f.ignore_whitespace {
+        warnings.push("Go has no x flag; whitespace and # comments must come out".into());
+    }
+    if f.unicode_off {
+        warnings.push("Go has no u flag to turn off".into());
+    }
+
+    // Go takes the same inline flags, apart from x and u
+    let mut inline = String::new();
+    for (set, c) in [
+        (flags.case_insensitive, 'i'),
+        (flags.multi_line, 'm'),
+        (flags.dot_matches_new_line, 's'),
+        (flags.swap_greed, 'U'),
+    ]
+    .iter()
+    {
+        if *set {
+            inline.push(*c);
+        }
+    }
+    let inline = match inline.as_str() {
+        "" => String::new(),
+        on => format!("(?{})", on),
+    };
+    let mut imports = vec!["fmt", "regexp"];
+    if eval.file.is_some() {
+        imports.push("os");
+    }
+    if let Operation::Lines | Operation::Set = eval.operation {
+        imports.extend(["bufio", "strings"].iter());
+    }
+    imports.sort_unstable();
+    let mut s = String::from("package main\n\nimport (\n");
+    for i in imports.iter() {
+        writeln!(s, "\t\"{}\"", i).unwrap();
+    }
+    s += ")\n\nfunc main() {\n";
+    match eval.operation {
+        Operation::Set => {
+            s += "\tres := []*regexp.Regexp{\n";
+            for p in eval.patterns() {
+                let p = format!("{}{}", inline, p);
+                writeln!(s, "\t\tregexp.MustCompile({}),", go_str(&p)).unwrap();
+            }
+            s += "\t}\n";
+        }
+        _ => {
+            let pattern = format!("{}{}", inline, eval.pattern);
+            writeln!(s, "\tre := regexp.MustCompile({})", go_str(&pattern)).unwrap();
+        }
+    }
+    match &eval.file {
+        Some(file) => {
+            let path = go_str(&file.path().display().to_string());
+            writeln!(s, "\tdata, err := os.ReadFile({})", path).unwrap();
+            s += "\tif err != nil {\n\t\tpanic(err)\n\t}\n\ttext := string(data)\n";
+        }
+        None => writeln!(s, "\ttext := {}", go_str(&eval.string)).unwrap(),
+    }
+    if let Operation::Lines | Operation::Set = eval.operation {
+        // split as str::lines does, taking \r\n as a line break and leaving
+        // no empty line after the last one; the buffer fits the longest line
+        s += "\tlines := bufio.NewScanner(strings.NewReader(text))\n\
+              \tlines.Buffer(nil, len(text)+1)\n";
+    }
+    s += match eval.operation {
+        Operation::Match | Operation::Explain | Operation::Inspect => {
+            "\tm := re.FindStringSubmatch(text)\n\tif m != nil {\n\t\tfmt.Printf(\"%q\\n\", m)\n\t} else \
+             {\n\t\tfmt.Println(\"no match\")\n\t}\n"
+        }
+        Operation::Find => {
+            "\tfor _, loc := range re.FindAllStringIndex(text, -1) {\n\t\t\
+             fmt.Println(loc[0], loc[1], text[loc[0]:loc[1]])\n\t}\n"
+        }
+        Operation::FindCaptures => {
+            "\tfor _, m := range re.FindAllStringSubmatch(text, -1) {\n\t\tfmt.Printf(\"%q\\n\", m)\n\t}\n"
+        }
+        Operation::Split => "\tfmt.Printf(\"%q\\n\", re.Split(text, -1))\n",
+        Operation::Replace => "",
+        Operation::Lines => {
+            "\tfor i := 1; lines.Scan(); i++ {\n\t\tline := lines.Text()\n\t\t\
+             if re.MatchString(line) {\n\t\t\tfmt.Println(i, line)\n\t\t}\n\t}\n"
+        }
+        Operation::Set => {
+            "\tfor i := 1; lines.Scan(); i++ {\n\t\tline := lines.Text()\n\t\tvar matched []int\n\t\t\
+             for j, re := range res {\n\t\t\tif re.MatchString(line) {\n\t\t\t\t\
+             matched = append(matched, j)\n\t\t\t}\n\t\t}\n\t\tfmt.Println(i, matched)\n\t}\n"
+        }
+    };
+    if eval.operation == Operation::Replace {
+        // Go expands $1, ${name} and $$ just as the regex crate does
+        writeln!(
+            s,
+            "\tfmt.Println(re.ReplaceAllString(text, {}))",
+            go_str(&eval.replacement)
+        )
+        .unwrap();
+    }
+    s += "}\n";
+    s
+}
+
+fn java(eval: &Evaluation, f: &Features, warnings: &mut Vec<String>) -> String {
+    let flags = &eval.flags;
+    for (class, kind) in f.unicode_classes.iter() {
+        match kind {
+            ClassUnicodeKind::Named(name)
+                if name.len() > 2 && !name.starts_with("Is") && !name.starts_with("In") =>
+            {
+                warnings.push(format!(
+                    "Java writes scripts and properties with an Is prefix, e.g. \\p{{IsGreek}}, so {} \
+                     likely needs one",
+                    class
+                ))
+            }
+            ClassUnicodeKind::NamedValue { op, .. } if *op != ClassUnicodeOpKind::Equal => {
+                warnings.push(format!("Java has no {}; only = works in \\p{{...}}", class))
+            }
+            _ => {}
+        }
+    }
+    if f.ascii_classes {
+        warnings.push("Java writes [[:alpha:]] style classes as \\p{Alpha}".into());
+    }
+    if f.class_difference {
+        warnings.push("Java classes only have &&, not -- or ~~".into());
+    }
+    if f.hex_long {
+        warnings.push("Java has no \\U escape; use \\x{...}".into());
+    }
+    if f.vertical_tab {
+        warnings.push("Java reads \\v as any vertical whitespace, not just a vertical tab".into());
+    }
+    if flags.swap_greed || f.swap_greed {
+        warnings.push("Java has no U flag; swap each repetition's greed by hand".into());
+    }
+    if f.unicode_off {
+        warnings.push("Java's u flag only turns Unicode case folding off".into());
+    }
+    for (_, name) in f.named_groups.iter() {
+        if !name.chars().all(|c| c.is_ascii_alphanumeric()) {
+            warnings.push(format!(
+                "Java group names are only letters and digits, unlike {}",
+                name
+            ));
+        }
+    }
+    // Java writes (?P<name>...) as (?<name>...)
+    let mut pattern = eval.pattern.clone();
+    for (start, _) in f.named_groups.iter().rev() {
+        pattern.replace_range(start + 2..start + 3, "");
+    }
+
+    let mut names = vec![];
+    for (set, name) in [
+        (flags.case_insensitive, "Pattern.CASE_INSENSITIVE"),
+        (flags.multi_line, "Pattern.MULTILINE"),
+        (flags.dot_matches_new_line, "Pattern.DOTALL"),
+        (flags.ignore_whitespace, "Pattern.COMMENTS"),
+        // without it \w, \b and case folding only know ASCII
+        (flags.unicode, "Pattern.UNICODE_CHARACTER_CLASS"),
+    ]
+    .iter()
+    {
+        if *set {
+            names.push(*name);
+        }
+    }
+    let mut s = String::new();
+    match eval.operation {
+        Operation::Split => s += "import java.util.Arrays;\n",
+        Operation::Match
+        | Operation::Explain
+        | Operation::Inspect
+        | Operation::Find
+        | Operation::FindCaptures => s += "import java.util.regex.Matcher;\n",
+        Operation::Set => s += "import java.util.ArrayList;\nimport java.util.List;\n",
+        _ => {}
+    }
+    s += "import java.util.regex.Pattern;\n";
+    if eval.file.is_some() {
+        s = format!("import java.io.IOException;\nimport java.nio.file.Files;\nimport java.nio.file.Paths;\n{}", s);
+    }
+    s += "\npublic class Main {\n";
+    match eval.file {
+        Some(_) => s += "    public static void main(String[] args) throws IOException {\n",
+        None => s += "    public static void main(String[] args) {\n",
+    }
+    match (eval.operation, names.as_slice()) {
+        (Operation::Set, names) => {
+            s += "        Pattern[] patterns = {\n";
+            for p in pattern.lines() {
+                match names {
+                    [] => writeln!(s, "                Pattern.compile({}),", java_str(p)),
+                    names => writeln!(
+                        s,
+                        "                Pattern.compile({}, {}),",
+                        java_str(p),
+                        names.join(" | ")
+                    ),
+                }
+                .unwrap();
+            }
+            s += "        };\n";
+        }
+        (_, []) => writeln!(
+            s,
+            "        Pattern pattern = Pattern.compile({});",
+            java_str(&pattern)
+        )
+        .unwrap(),
+        (_, names) => writeln!(
+            s,
+            "        Pattern pattern = Pattern.compile({},\n                {});",
+            java_str(&pattern),
+            names.join(" | ")
+        )
+        .unwrap(),
+    }
+    match &eval.file {
+        Some(file) => writeln!(
+            s,
+            "        String text = new String(Files.readAllBytes(Paths.get({})), \"UTF-8\");",
+            java_str(&file.path().display().to_string())
+        )
+        .unwrap(),
+        None => writeln!(s, "        String text = {};", java_str(&eval.string)).unwrap(),
+    }
+    if let Operation::Lines | Operation::Set = eval.operation {
+        // split as str::lines does, taking \r\n as a line break and leaving
+        // no empty line after the last one
+        s += "        String[] lines = text.split(\"\\r?\\n\", -1);\n        \
+              int count = lines[lines.length - 1].isEmpty() ? lines.length - 1 : lines.length;\n";
+    }
+    s += match eval.operation {
+        Operation::Match | Operation::Explain | Operation::Inspect => {
+            "        Matcher m = pattern.matcher(text);\n        if (m.find()) {\n            \
+             for (int i = 0; i <= m.groupCount(); i++) {\n                \
+             System.out.println(i + \": \" + m.group(i));\n            }\n        } else {\n            \
+             System.out.println(\"no match\");\n        }\n"
+        }
+        Operation::Find => {
+            "        Matcher m = pattern.matcher(text);\n        while (m.find()) {\n            \
+             System.out.println(m.start() + \" \" + m.end() + \" \" + m.group());\n        }\n"
+        }
+        Operation::FindCaptures => {
+            "        Matcher m = pattern.matcher(text);\n        while (m.find()) {\n            \
+             for (int i = 0; i <= m.groupCount(); i++) {\n                \
+             System.out.println(i + \": \" + m.group(i));\n            }\n        }\n"
+        }
+        Operation::Split => "        System.out.println(Arrays.toString(pattern.split(text, -1)));\n",
+        Operation::Replace => "",
+        Operation::Lines => {
+            "        for (int i = 0; i < count; i++) {\n            \
+             if (pattern.matcher(lines[i]).find()) {\n                \
+             System.out.println((i + 1) + \" \" + lines[i]);\n            }\n        }\n"
+        }
+        Operation::Set => {
+            "        for (int i = 0; i < count; i++) {\n            \
+             List<Integer> matched = new ArrayList<>();\n            \
+             for (int j = 0; j < patterns.length; j++) {\n                \
+             if (patterns[j].matcher(lines[i]).find()) {\n                    \
+             matched.add(j);\n                }\n            }\n            \
+             System.out.println((i + 1) + \" \" + matched);\n        }\n"
+        }
+    };
+    if eval.operation == Operation::Replace {
+        let mut repl = String::new();
+        for part in template(&eval.replacement) {
+            match part {
+                Part::Literal(l) => repl += &l.replace('\\', "\\\\").replace('$', "\\$"),
+                // Java only takes ${name} for named groups
+                Part::Group(g) if g.bytes().all(|b| b.is_ascii_digit()) => {
+                    write!(repl, "${}", g).unwrap()
+                }
+                Part::Group(g) => write!(repl, "${{{}}}", g).unwrap(),
+            }
+        }
+        writeln!(
+            s,
+            "        System.out.println(pattern.matcher(text).replaceAll({}));",
+            java_str(&repl)
+        )
+        .unwrap();
+    }
+    s += "    }\n}\n";
+    s
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn code(language: Language, op: Operation, pattern: &str) -> String {
+        let eval = Evaluation::new(op, pattern, "ab\r\nb\n").with_replacement("$1-");
+        snippet(language, &eval).unwrap().code
+    }
+
+    #[test]
+    fn python_strings_are_raw_when_they_can_be() {
+        assert_eq!(python_str("abc"), r#""abc""#);
+        assert_eq!(python_str(r"\d+"), r#"r"\d+""#);
+        assert_eq!(python_str(r#"\d"x"#), r#"r'\d"x'"#);
+        assert_eq!(python_str(r#"'\d"x"#), r#""'\\d\"x""#);
+        // a raw string cannot end in one backslash, but can in two
+        assert_eq!(python_str(r"a\"), r#""a\\""#);
+        assert_eq!(python_str(r"a\\"), r#"r"a\\""#);
+        assert_eq!(python_str("a\nb\u{7}"), r#""a\nb\x07""#);
+    }
+
+    #[test]
+    fn go_strings_use_backquotes_for_backslashes() {
+        assert_eq!(go_str("abc"), r#""abc""#);
+        assert_eq!(go_str(r#"\d"x"#), r#"`\d"x`"#);
+        assert_eq!(go_str(r"`\d`"), r#""`\\d`""#);
+        assert_eq!(go_str("a\tb"), r#""a\tb""#);
+    }
+
+    #[test]
+    fn java_strings_escape_controls_in_octal() {
+        assert_eq!(java_str(r#"\d"é"#), r#""\\d\"é""#);
+        assert_eq!(java_str("a\nb"), r#""a\nb""#);
+        assert_eq!(java_str("a\u{7}b\u{85}"), r#""a\007b\205""#);
+    }
+
+    #[test]
+    fn java_names_groups_without_the_p() {
+        let c = code(Language::Java, Operation::Find, r"(?P<year>\d+)-(?P<m>\d+)");
+        assert!(
+            c.contains(r#"Pattern.compile("(?<year>\\d+)-(?<m>\\d+)""#),
+            "{}",
+            c
+        );
+        let c = code(Language::Java, Operation::Set, "(?P<a>x)\n(?P<b>y)");
+        assert!(c.contains(r#"Pattern.compile("(?<a>x)""#), "{}", c);
+        assert!(c.contains(r#"Pattern.compile("(?<b>y)""#), "{}", c);
+    }
+
+    #[test]
+    fn go_reads_lines_like_str_lines() {
+        let c = code(Language::Go, Operation::Lines, "b");
+        assert!(
+            c.contains("bufio.NewScanner(strings.NewReader(text))"),
+            "{}",
+            c
+        );
+        assert!(!c.contains("strings.Split"), "{}", c);
+    }
+
+    #[test]
+    fn java_reads_lines_like_str_lines() {
+        for op in [Operation::Lines, Operation::Set].iter() {
+            let c = code(Language::Java, *op, "b");
+            assert!(
+                c.contains(r#"String[] lines = text.split("\r?\n", -1);"#),
+                "{}",
+                c
+            );
+            assert!(c.contains("lines[lines.length - 1].isEmpty() ?"), "{}", c);
+            assert!(c.contains("for (int i = 0; i < count; i++)"), "{}", c);
+        }
+    }
+
+    #[test]
+    fn each_language_writes_each_operation() {
+        use Operation::*;
+        let expected: [(Language, [&str; 9]); 4] = [
+            (
+                Language::Rust,
+                [
+                    "re.captures(text)",
+                    "re.find_iter(text)",
+                    "re.captures_iter(text)",
+                    "re.split(text)",
+                    r#"re.replace_all(text, r"$1-")"#,
+                    "text.lines().enumerate()",
+                    "re.captures(text)",
+                    "re.captures(text)",
+                    "re.matches(line)",
+                ],
+            ),
+            (
+                Language::Python,
+                [
+                    "pattern.search(text)",
+                    "m.start(), m.end()",
+                    "m.groupdict()",
+                    "pattern.split(text)",
+                    r#"pattern.sub(r"\g<1>-", text)"#,
+                    "enumerate(text.splitlines(), 1)",
+                    "pattern.search(text)",
+                    "pattern.search(text)",
+                    "enumerate(patterns) if p.search(line)",
+                ],
+            ),
+            (
+                Language::Go,
+                [
+                    "re.FindStringSubmatch(text)",
+                    "re.FindAllStringIndex(text, -1)",
+                    "re.FindAllStringSubmatch(text, -1)",
+                    "re.Split(text, -1)",
+                    r#"re.ReplaceAllString(text, "$1-")"#,
+                    "if re.MatchString(line)",
+                    "re.FindStringSubmatch(text)",
+                    "re.FindStringSubmatch(text)",
+                    "for j, re := range res",
+                ],
+            ),
+            (
+                Language::Java,
+                [
+                    "if (m.find())",
+                    "m.start() + \" \" + m.end()",
+                    "while (m.find()) {\n            for",
+                    "pattern.split(text, -1)",
+                    r#"replaceAll("$1-")"#,
+                    "pattern.matcher(lines[i]).find()",
+                    "if (m.find())",
+                    "if (m.find())",
+                    "patterns[j].matcher(lines[i]).find()",
+                ],
+            ),
+        ];
+        let ops = [
+            Match,
+            Find,
+            FindCaptures,
+            Split,
+            Replace,
+            Lines,
+            Explain,
+            Inspect,
+            Set,
+        ];
+        for (language, wanted) in expected.iter() {
+            for (op, wanted) in ops.iter().zip(wanted.iter()) {
+                let c = code(*language, *op, "(a)b?\nb");
+                assert!(c.contains(wanted), "{} {:?}:\n{}", language, op, c);
+            }
+        }
+    }
+}
@@ -0,0 +1,233 @@
+use std::{fmt, ops::Range};
+
+use regex::Regex;
+use serde::Serialize;
+
+use crate::{
+    eval::Evaluation,
+    result::{Highlight, PatternError, Span, Spans},
+    suite::Case,
+};
+
+/// How much of an input or result a comparison row quotes.
+const CELL_CHARS: usize = 30;
+
+/// Which of the two patterns of a [`Comparison`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum Side {
+    A,
+    B,
+}
+
+/// What a group of a [`Compared`] match captured.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Captured {
+    /// Byte offsets into the input.
+    pub range: Range<usize>,
+    pub text: String,
+}
+
+/// One input tried with both patterns: each group of the first match, group 0
+/// first, or `None` when the pattern does not match.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Compared {
+    pub input: String,
+    /// Where the input is in the haystack, when it is a line of it.
+    pub span: Option<Span>,
+    pub a: Option<Vec<Option<Captured>>>,
+    pub b: Option<Vec<Option<Captured>>>,
+}
+
+impl Compared {
+    /// True when both patterns match the same text at the same place with the
+    /// same groups, or neither matches.
+    pub fn same(&self) -> bool {
+        self.a == self.b
+    }
+}
+
+/// Two patterns tried on the same inputs, to check that a rewritten pattern
+/// still does what the old one did.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Comparison {
+    /// The patterns with their flags inline.
+    pub a: String,
+    pub b: String,
+    /// The first pattern that does not compile; no inputs are tried then.
+    pub error: Option<(Side, PatternError)>,
+    pub inputs: Vec<Compared>,
+}
+
+fn captures(re: &Regex, input: &str) -> Option<Vec<Option<Captured>>> {
+    re.captures(input).map(|caps| {
+        caps.iter()
+            .map(|m| m.map(|m| Captured { range: m.range(), text: m.as_str().to_string() }))
+            .collect()
+    })
+}
+
+impl Comparison {
+    /// Tries `a` and `b` on each line of the haystack of `a`, or on the
+    /// input of each case when there are `cases`.  Only the patterns, flags
+    /// and limits of the evaluations are used, and `max_results` of `a`
+    /// caps the number of lines.
+    pub fn run(a: &Evaluation, b: &Evaluation, cases: Option<&[Case]>) -> Self {
+        let mut comparison = Comparison {
+            a: a.effective_pattern(),
+            b: b.effective_pattern(),
+            error: None,
+            inputs: vec![],
+        };
+        let (re_a, re_b) = match (a.compile(), b.compile()) {
+            (Ok(re_a), Ok(re_b)) => (re_a, re_b),
+            (Err(e), _) => {
+                comparison.error = Some((Side::A, e));
+                return comparison;
+            }
+            (_, Err(e)) => {
+                comparison.error = Some((Side::B, e));
+                return comparison;
+            }
+        };
+        let compared = |input: &str, span| Compared {
+            input: input.to_string(),
+            span,
+            a: captures(&re_a, input),
+            b: captures(&re_b, input),
+        };
+        comparison.inputs = match cases {
+            Some(cases) => cases.iter().map(|c| compared(&c.input, None)).collect(),
+            None => {
+                let hay = a.haystack();
+                let mut spans = Spans::new(hay);
+                hay.lines()
+                    .take(a.max_results.unwrap_or(usize::MAX))
+                    .map(|line| {
+                        let start = line.as_ptr() as usize - hay.as_ptr() as usize;
+                        compared(line, Some(spans.span(start..start + line.len())))
+                    })
+                    .collect()
+            }
+        };
+        comparison
+    }
+
+    /// How many inputs the patterns disagree on.
+    pub fn differences(&self) -> usize {
+        self.inputs.iter().filter(|c| !c.same()).count()
+    }
+
+    pub fn all_same(&self) -> bool {
+        self.error.is_none() && self.differences() == 0
+    }
+
+    /// The lines the patterns disagree on, for colouring the input.
+    pub fn highlights(&self) -> Vec<Highlight> {
+        self.inputs
+            .iter()
+            .enumerate()
+            .filter(|(_, c)| !c.same())
+            .filter_map(|(row, c)| Some(Highlight { span: c.span?, group: 0, row }))
+            .collect()
+    }
+}
+
+/// `s` quoted, cut short when it is long.
+fn cell(s: &str) -> String {
+    let mut quoted = format!("{:?}", s);
+    if quoted.chars().count() > CELL_CHARS {
+        quoted = quoted.chars().take(CELL_CHARS - 3).collect();
+        quoted.push_str("...");
+    }
+    quoted
+}
+
+/// The match and its groups with their byte ranges, e.g.
+/// `"ab" (0..2) 1="a" (0..1) 2=None`.
+fn described(caps: &Option<Vec<Option<Captured>>>) -> String {
+    let caps = match caps {
+        None => return "no match".to_string(),
+        Some(caps) => caps,
+    };
+    let mut s = String::new();
+    for (i, g) in caps.iter().enumerate() {
+        if i > 0 {
+            s.push_str(&format!(" {}=", i));
+        }
+        match g {
+            Some(g) => s.push_str(&format!("{} ({:?})", cell(&g.text), g.range)),
+            None => s.push_str("None"),
+        }
+    }
+    s
+}
+
+/// Lists each input with what A and then B made of it side by side, marking
+/// with `*` the inputs they disagree on.
+impl fmt::Display for Comparison {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "Comparing pattern A:\n\"{}\"\nWith pattern B:\n\"{}\"\n",
+            self.a, self.b
+        )?;
+        if let Some((side, e)) = &self.error {
+            return write!(f, "Error with pattern {:?}: {}", side, e.message);
+        }
+        let mut rows = vec![("input".to_string(), "A".to_string(), "B".to_string(), true)];
+        rows.extend(
+            self.inputs
+                .iter()
+                .map(|c| (cell(&c.input), described(&c.a), described(&c.b), c.same())),
+        );
+        let input_width = rows.iter().map(|r| r.0.chars().count()).max().unwrap_or(0);
+        let a_width = rows.iter().map(|r| r.1.chars().count()).max().unwrap_or(0);
+        for (input, a, b, same) in rows.iter() {
+            let mark = if *same { ' ' } else { '*' };
+            writeln!(
+                f,
+                "{} {:<iw$}  {:<aw$}  {}",
+                mark,
+                input,
+                a,
+                b,
+                iw = input_width,
+                aw = a_width
+            )?;
+        }
+        write!(
+            f,
+            "\n{} of {} inputs differ",
+            self.differences(),
+            self.inputs.len()
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::eval::Operation;
+
+    fn compare(a: &str, b: &str, haystack: &str) -> Comparison {
+        Comparison::run(
+            &Evaluation::new(Operation::FindCaptures, a, haystack),
+            &Evaluation::new(Operation::FindCaptures, b, haystack),
+            None,
+        )
+    }
+
+    #[test]
+    fn same_text_at_another_place_differs() {
+        let c = compare("a", "a$", "ab a");
+        assert_eq!(c.differences(), 1);
+        assert!(c.to_string().contains("\"a\" (0..1)"));
+        assert!(c.to_string().contains("\"a\" (3..4)"));
+    }
+
+    #[test]
+    fn different_groups_differ() {
+        assert_eq!(compare("(a)(b)", "(a)b", "ab").differences(), 1);
+        assert_eq!(compare("(a)b", "(a)b", "ab\nb").differences(), 0);
+    }
+}
@@ -0,0 +1,669 @@
+use std::{fmt::Display, str::FromStr, sync::Arc};
+
+use regex::{Captures, Regex, RegexBuilder, RegexSet};
+use serde::{Serialize, Serializer};
+
+use crate::explain::explain;
+use crate::inspect::inspect;
+use crate::options::{Flags, Limits};
+use crate::result::{
+    Group, Lines, Match, MatchedLine, Outcome, PatternError, Piece, Replaced, SetLine, SetMatches,
+    Spans, Substitution,
+};
+use crate::testfile::TestFile;
+
+/// The operations the frontends offer as buttons.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Operation {
+    Match,
+    Find,
+    /// Find every match along with all of its capture groups.
+    FindCaptures,
+    Split,
+    Replace,
+    /// Apply the pattern to each line on its own, as grep does.
+    Lines,
+    /// Describe the pattern in plain English; the string is not used.
+    Explain,
+    /// Show the HIR `regex` builds from the pattern and the literals it
+    /// searches for; the string is not used.
+    Inspect,
+    /// Take each line of the pattern as a pattern of its own and report
+    /// which of them match each line, as a `RegexSet` does.
+    Set,
+}
+
+impl Operation {
+    /// Name recorded in the history for this operation.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Operation::Match => "match",
+            Operation::Find => "find",
+            Operation::FindCaptures => "captures",
+            Operation::Split => "split",
+            Operation::Replace => "replace",
+            Operation::Lines => "lines",
+            Operation::Explain => "explain",
+            Operation::Inspect => "inspect",
+            Operation::Set => "set",
+        }
+    }
+}
+
+impl Display for Operation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// Parses the names given by [`Operation::name`].
+impl FromStr for Operation {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let ops = [
+            Operation::Match,
+            Operation::Find,
+            Operation::FindCaptures,
+            Operation::Split,
+            Operation::Replace,
+            Operation::Lines,
+            Operation::Explain,
+            Operation::Inspect,
+            Operation::Set,
+        ];
+        ops.iter()
+            .find(|op| op.name() == s)
+            .copied()
+            .ok_or_else(|| format!("unknown operation \"{}\"", s))
+    }
+}
+
+/// What [`Evaluation::prepare`] compiles the pattern into: a `RegexSet` of
+/// its lines for `Set`, and a `Regex` for everything else.
+#[derive(Debug, Clone)]
+pub enum Compiled {
+    Regex(Regex),
+    Set(RegexSet),
+}
+
+/// How many results the frontends collect from a [`TestFile`] before
+/// stopping.
+pub const MAX_FILE_RESULTS: usize = 100_000;
+
+/// One operation applied with one pattern to one string.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Evaluation {
+    pub operation: Operation,
+    pub pattern: String,
+    pub string: String,
+    /// Searched instead of `string` when set.
+    #[serde(skip_serializing_if = "Option::is_none", serialize_with = "shared")]
+    pub file: Option<Arc<TestFile>>,
+    /// Stop after this many matches, pieces or replacements.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_results: Option<usize>,
+    /// Substitution template for `Replace`; `$1`, `${name}` and `$$` are
+    /// expanded as `Regex::replace_all` does.
+    pub replacement: String,
+    pub flags: Flags,
+    pub limits: Limits,
+}
+
+/// How far [`Evaluation::required_size_limit`] will raise `size_limit`
+/// looking for one that works.
+const MAX_SIZE_LIMIT_SEARCH: usize = 1 << 30;
+
+impl Evaluation {
+    pub fn new(operation: Operation, pattern: &str, string: &str) -> Self {
+        Evaluation {
+            operation,
+            pattern: pattern.into(),
+            string: string.into(),
+            file: None,
+            max_results: None,
+            replacement: String::new(),
+            flags: Flags::default(),
+            limits: Limits::default(),
+        }
+    }
+
+    pub fn with_limits(mut self, limits: Limits) -> Self {
+        self.limits = limits;
+        self
+    }
+
+    pub fn with_flags(mut self, flags: Flags) -> Self {
+        self.flags = flags;
+        self
+    }
+
+    pub fn with_file(mut self, file: Arc<TestFile>) -> Self {
+        self.file = Some(file);
+        self
+    }
+
+    pub fn with_max_results(mut self, max: usize) -> Self {
+        self.max_results = Some(max);
+        self
+    }
+
+    /// The text searched: the file when there is one, else `string`.
+    pub fn haystack(&self) -> &str {
+        match &self.file {
+            Some(f) => f.text(),
+            None => &self.string,
+        }
+    }
+
+    /// The pattern with the flags folded in as an inline group, ready to be
+    /// pasted into code that calls `Regex::new`.
+    pub fn effective_pattern(&self) -> String {
+        format!("{}{}", self.flags.inline(), self.pattern)
+    }
+
+    pub fn with_replacement(mut self, replacement: &str) -> Self {
+        self.replacement = replacement.into();
+        self
+    }
+
+    /// The patterns of a `Set`, one per line of `pattern`.
+    pub fn patterns(&self) -> Vec<&str> {
+        self.pattern.lines().collect()
+    }
+
+    fn builder(&self) -> RegexBuilder {
+        let mut b = self.flags.builder(&self.pattern);
+        self.limits.apply(&mut b);
+        b
+    }
+
+    pub fn compile(&self) -> Result<Regex, PatternError> {
+        self.builder().build().map_err(|e| {
+            let mut err = PatternError::new(&self.pattern, &self.flags, &self.limits, &e);
+            if let regex::Error::CompiledTooBig(_) = e {
+                err.required_size_limit = self.required_size_limit();
+            }
+            err
+        })
+    }
+
+    /// Compiles the lines of the pattern into one `RegexSet`.  When that
+    /// fails over a syntax error, the error is the one of the first line with
+    /// it, its span moved to where the line is in the pattern.
+    pub fn compile_set(&self) -> Result<RegexSet, PatternError> {
+        let patterns = self.patterns();
+        let mut b = self.flags.set_builder(&patterns);
+        self.limits.apply_set(&mut b);
+        b.build().map_err(|e| {
+            for line in patterns.iter() {
+                let one = Evaluation::new(Operation::Match, line, "")
+                    .with_flags(self.flags)
+                    .with_limits(self.limits);
+                if let Err(mut err @ PatternError { span: Some(_), .. }) = one.compile() {
+                    let start = line.as_ptr() as usize - self.pattern.as_ptr() as usize;
+                    let mut spans = Spans::new(&self.pattern);
+                    err.span = err.span.map(|s| spans.span(start + s.start..start + s.end));
+                    return err;
+                }
+            }
+            // no line has a syntax error, so the set as a whole is too big
+            PatternError::new(&self.pattern, &self.flags, &self.limits, &e)
+        })
+    }
+
+    /// Compiles what the operation runs: [`Evaluation::compile_set`] for
+    /// `Set`, else [`Evaluation::compile`].
+    pub fn prepare(&self) -> Result<Compiled, PatternError> {
+        match self.operation {
+            Operation::Set => self.compile_set().map(Compiled::Set),
+            _ => self.compile().map(Compiled::Regex),
+        }
+    }
+
+    /// Searches for the smallest `size_limit` the pattern compiles under, by
+    /// doubling the current limit until it works and then bisecting.
+    pub fn required_size_limit(&self) -> Option<usize> {
+        let compiles = |size| self.builder().size_limit(size).build().is_ok();
+        let mut lo = self.limits.size_limit;
+        let mut hi = lo.max(1);
+        while !compiles(hi) {
+            if hi >= MAX_SIZE_LIMIT_SEARCH {
+                return None;
+            }
+            lo = hi;
+            hi = (hi * 2).min(MAX_SIZE_LIMIT_SEARCH);
+        }
+        while hi - lo > 1 {
+            let mid = lo + (hi - lo) / 2;
+            if compiles(mid) {
+                hi = mid;
+            } else {
+                lo = mid;
+            }
+        }
+        Some(hi)
+    }
+
+    pub fn run(&self) -> Outcome {
+        self.run_until(&|| false).expect("never cancelled")
+    }
+
+    /// Like [`Evaluation::run`], but gives up and returns `None` as soon as
+    /// `cancelled` returns true.  It is polled between matches, so a newer
+    /// evaluation need not wait for a stale one to walk a huge input.
+    pub fn run_until(&self, cancelled: &dyn Fn() -> bool) -> Option<Outcome> {
+        match self.prepare() {
+            Err(e) => Some(Outcome::PatternError(e)),
+            Ok(compiled) => self.run_compiled(&compiled, cancelled),
+        }
+    }
+
+    /// Like [`Evaluation::run_until`] with the pattern already compiled by
+    /// [`Evaluation::prepare`], so that searching can be timed on its own.
+    pub fn run_compiled(
+        &self,
+        compiled: &Compiled,
+        cancelled: &dyn Fn() -> bool,
+    ) -> Option<Outcome> {
+        let re = match compiled {
+            Compiled::Regex(re) => re,
+            Compiled::Set(set) => return self.run_set(set, cancelled),
+        };
+        let hay = self.haystack();
+        let max = self.max_results.unwrap_or(usize::MAX);
+        let mut spans = Spans::new(hay);
+        let outcome = match self.operation {
+            Operation::Match => match re.captures(hay) {
+                None => Outcome::NoMatch,
+                Some(caps) => Outcome::Matched(captured(re, &caps, 0, &mut spans)),
+            },
+            Operation::Find => {
+                let mut found = vec![];
+                let mut matches = re.find_iter(hay);
+                for m in matches.by_ref().take(max) {
+                    if cancelled() {
+                        return None;
+                    }
+                    found.push(Match {
+                        span: spans.span(m.range()),
+                        text: m.as_str().to_string(),
+                        groups: vec![],
+                    });
+                }
+                Outcome::Found { matches: found, cut_off: matches.next().is_some() }
+            }
+            Operation::FindCaptures => {
+                let mut found = vec![];
+                let mut matches = re.captures_iter(hay);
+                for caps in matches.by_ref().take(max) {
+                    if cancelled() {
+                        return None;
+                    }
+                    found.push(captured(re, &caps, 0, &mut spans));
+                }
+                Outcome::Found { matches: found, cut_off: matches.next().is_some() }
+            }
+            Operation::Split => {
+                let mut pieces = vec![];
+                let mut start = 0;
+                for m in re.find_iter(hay) {
+                    if cancelled() {
+                        return None;
+                    }
+                    if pieces.len() == max {
+                        break;
+                    }
+                    pieces.push(piece(hay, start..m.start(), &mut spans));
+                    start = m.end();
+                }
+                // with the cap reached there is at least the last piece left
+                let cut_off = pieces.len() == max;
+                if !cut_off {
+                    pieces.push(piece(hay, start..hay.len(), &mut spans));
+                }
+                Outcome::Pieces { pieces, cut_off }
+            }
+            Operation::Replace => {
+                let mut substitutions = vec![];
+                let mut matches = re.captures_iter(hay);
+                for caps in matches.by_ref().take(max) {
+                    if cancelled() {
+                        return None;
+                    }
+                    let m = caps.get(0).unwrap();
+                    let mut with = String::new();
+                    caps.expand(&self.replacement, &mut with);
+                    substitutions.push(Substitution {
+                        span: spans.span(m.range()),
+                        text: m.as_str().to_string(),
+                        replacement: with,
+                    });
+                }
+                // as many as were collected; replacen reads 0 as all, so a
+                // cap of 0 must not reach it
+                let text = match substitutions.len() {
+                    0 => hay.to_string(),
+                    n => re.replacen(hay, n, self.replacement.as_str()).into_owned(),
+                };
+                let cut_off = matches.next().is_some();
+                Outcome::Replaced(Replaced { text, substitutions, cut_off })
+            }
+            Operation::Lines => {
+                let mut lines = Lines { matched: 0, unmatched: 0, lines: vec![] };
+                for (i, line) in hay.lines().enumerate() {
+                    if cancelled() {
+                        return None;
+                    }
+                    let caps = match re.captures(line) {
+                        Some(caps) => caps,
+                        None => {
+                            lines.unmatched += 1;
+                            continue;
+                        }
+                    };
+                    lines.matched += 1;
+                    // keep counting past the cap so the totals stay right
+                    if lines.lines.len() < max {
+                        let start = line.as_ptr() as usize - hay.as_ptr() as usize;
+                        lines.lines.push(MatchedLine {
+                            number: i + 1,
+                            span: spans.span(start..start + line.len()),
+                            text: line.to_string(),
+                            first: captured(re, &caps, start, &mut spans),
+                        });
+                    }
+                }
+                Outcome::Lines(lines)
+            }
+            Operation::Explain => explain(&self.pattern, &self.flags, &self.limits)
+                .map_or_else(Outcome::PatternError, Outcome::Explained),
+            Operation::Inspect => inspect(&self.pattern, &self.flags, &self.limits)
+                .map_or_else(Outcome::PatternError, Outcome::Inspected),
+            Operation::Set => unreachable!("prepare compiles a set for Set"),
+        };
+        Some(outcome)
+    }
+
+    /// Tries `set` on each line of the haystack, counting the lines each of
+    /// its patterns matches.
+    fn run_set(&self, set: &RegexSet, cancelled: &dyn Fn() -> bool) -> Option<Outcome> {
+        let hay = self.haystack();
+        let max = self.max_results.unwrap_or(usize::MAX);
+        let mut spans = Spans::new(hay);
+        let mut result =
+            SetMatches { counts: vec![0; set.len()], matched: 0, unmatched: 0, lines: vec![] };
+        for (i, line) in hay.lines().enumerate() {
+            if cancelled() {
+                return None;
+            }
+            let patterns: Vec<usize> = set.matches(line).into_iter().collect();
+            for p in patterns.iter() {
+                result.counts[*p] += 1;
+            }
+            if patterns.is_empty() {
+                result.unmatched += 1;
+            } else {
+                result.matched += 1;
+            }
+            // keep counting past the cap so the totals stay right
+            if result.lines.len() < max {
+                let start = line.as_ptr() as usize - hay.as_ptr() as usize;
+                result.lines.push(SetLine {
+                    number: i + 1,
+                    span: spans.span(start..start + line.len()),
+                    text: line.to_string(),
+                    patterns,
+                });
+            }
+        }
+        Some(Outcome::Set(result))
+    }
+}
+
+fn shared<S: Serializer>(file: &Option<Arc<TestFile>>, s: S) -> Result<S::Ok, S::Error> {
+    file.as_deref().serialize(s)
+}
+
+fn piece(s: &str, r: std::ops::Range<usize>, spans: &mut Spans) -> Piece {
+    Piece { text: s[r.clone()].to_string(), span: spans.span(r) }
+}
+
+/// Builds a [`Match`] carrying every group of `caps`, named where the pattern
+/// names them.  `offset` is where the text `caps` came from starts in the
+/// haystack.
+fn captured(re: &Regex, caps: &Captures, offset: usize, spans: &mut Spans) -> Match {
+    let whole = caps.get(0).unwrap();
+    let mut groups = Vec::with_capacity(caps.len());
+    for (i, name) in re.capture_names().enumerate() {
+        let c = caps.get(i);
+        groups.push(Group {
+            index: i,
+            name: name.map(|n| n.to_string()),
+            span: c.map(|c| spans.span(offset + c.start()..offset + c.end())),
+            text: c.map(|c| c.as_str().to_string()),
+        });
+    }
+    Match { span: groups[0].span.unwrap(), text: whole.as_str().to_string(), groups }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn run(op: Operation, pattern: &str, string: &str) -> Outcome {
+        Evaluation::new(op, pattern, string).run()
+    }
+
+    #[test]
+    fn find_lists_matches_without_groups() {
+        let found = match run(Operation::Find, r"a(\d)", "a1 b a2") {
+            Outcome::Found { matches, .. } => matches,
+            other => panic!("{:?}", other),
+        };
+        let got: Vec<_> = found
+            .iter()
+            .map(|m| (m.text.as_str(), m.span.range()))
+            .collect();
+        assert_eq!(got, [("a1", 0..2), ("a2", 5..7)]);
+        assert!(found.iter().all(|m| m.groups.is_empty()));
+    }
+
+    #[test]
+    fn split_keeps_the_piece_after_the_last_match() {
+        let pieces = match run(Operation::Split, ",", "a,b,") {
+            Outcome::Pieces { pieces, .. } => pieces,
+            other => panic!("{:?}", other),
+        };
+        let got: Vec<_> = pieces
+            .iter()
+            .map(|p| (p.text.as_str(), p.span.range()))
+            .collect();
+        assert_eq!(got, [("a", 0..1), ("b", 2..3), ("", 4..4)]);
+    }
+
+    #[test]
+    fn replace_expands_the_template() {
+        let eval =
+            Evaluation::new(Operation::Replace, r"(?P<n>\d)", "a1b2").with_replacement("<${n}$$>");
+        let r = match eval.run() {
+            Outcome::Replaced(r) => r,
+            other => panic!("{:?}", other),
+        };
+        assert_eq!(r.text, "a<1$>b<2$>");
+        assert_eq!(r.substitutions[1].replacement, "<2$>");
+        assert_eq!(r.substitutions[1].span.range(), 3..4);
+    }
+
+    #[test]
+    fn find_captures_lists_every_group() {
+        let found = match run(Operation::FindCaptures, r"(\w)(\d)?", "a1 b") {
+            Outcome::Found { matches, .. } => matches,
+            other => panic!("{:?}", other),
+        };
+        assert_eq!(found.len(), 2);
+        assert_eq!(found[0].groups[2].text.as_deref(), Some("1"));
+        assert_eq!(found[1].groups[1].span.map(|s| s.range()), Some(3..4));
+        assert_eq!(found[1].groups[2].text, None);
+    }
+
+    #[test]
+    fn match_captures_the_first_match() {
+        let m = match run(Operation::Match, r"(?P<l>[a-z])(\d)?", "1 a b2") {
+            Outcome::Matched(m) => m,
+            other => panic!("{:?}", other),
+        };
+        assert_eq!((m.text.as_str(), m.span.range()), ("a", 2..3));
+        assert_eq!(m.named("l").and_then(|g| g.text.as_deref()), Some("a"));
+        assert_eq!(m.groups[2].span, None);
+        assert_eq!(run(Operation::Match, "x", "abc"), Outcome::NoMatch);
+    }
+
+    #[test]
+    fn required_size_limit_is_the_smallest_that_compiles() {
+        let limits = Limits { size_limit: 100, ..Limits::default() };
+        let eval = Evaluation::new(Operation::Match, "a{300}", "").with_limits(limits);
+        let e = eval.compile().unwrap_err();
+        let needed = e.required_size_limit.unwrap();
+        assert_eq!(eval.required_size_limit(), Some(needed));
+        let with = |size_limit| Limits { size_limit, ..limits };
+        assert!(eval.clone().with_limits(with(needed)).compile().is_ok());
+        assert!(eval.with_limits(with(needed - 1)).compile().is_err());
+    }
+
+    #[test]
+    fn pattern_errors_are_outcomes() {
+        match run(Operation::Find, "a(b", "") {
+            Outcome::PatternError(e) => assert_eq!(e.kind, "unclosed group"),
+            other => panic!("{:?}", other),
+        }
+    }
+
+    #[test]
+    fn replace_stops_at_max_results() {
+        let replaced = |max| {
+            let eval = Evaluation::new(Operation::Replace, r"\d", "a1b2c3")
+                .with_replacement("#")
+                .with_max_results(max);
+            match eval.run() {
+                Outcome::Replaced(r) => (r.text, r.substitutions.len()),
+                other => panic!("{:?}", other),
+            }
+        };
+        assert_eq!(replaced(0), ("a1b2c3".to_string(), 0));
+        assert_eq!(replaced(2), ("a#b#c3".to_string(), 2));
+        assert_eq!(replaced(5), ("a#b#c#".to_string(), 3));
+    }
+
+    #[test]
+    fn find_and_split_stop_at_max_results() {
+        let eval = Evaluation::new(Operation::Find, ",", "a,b,c").with_max_results(1);
+        assert_eq!(eval.run().rows(), 1);
+        let eval = Evaluation::new(Operation::Split, ",", "a,b,c").with_max_results(2);
+        assert_eq!(eval.run().rows(), 2);
+    }
+
+    #[test]
+    fn lines_are_split_as_str_lines_does() {
+        let lines = match run(Operation::Lines, r"\d$", "a1\r\nb\nc2\n") {
+            Outcome::Lines(l) => l,
+            other => panic!("{:?}", other),
+        };
+        assert_eq!((lines.matched, lines.unmatched), (2, 1));
+        let got: Vec<_> = lines
+            .lines
+            .iter()
+            .map(|l| (l.number, l.text.as_str(), l.span.range()))
+            .collect();
+        assert_eq!(got, [(1, "a1", 0..2), (3, "c2", 6..8)]);
+        // the first match is placed in the haystack, not in its line
+        assert_eq!(lines.lines[1].first.span.range(), 7..8);
+    }
+
+    #[test]
+    fn lines_keep_counting_past_max_results() {
+        let eval = Evaluation::new(Operation::Lines, "a", "a\na\nb\na").with_max_results(1);
+        let lines = match eval.run() {
+            Outcome::Lines(l) => l,
+            other => panic!("{:?}", other),
+        };
+        assert_eq!(
+            (lines.matched, lines.unmatched, lines.lines.len()),
+            (3, 1, 1)
+        );
+    }
+
+    #[test]
+    fn set_tries_each_pattern_on_each_line() {
+        let set = match run(Operation::Set, "a\nb", "a\r\nab\nc") {
+            Outcome::Set(s) => s,
+            other => panic!("{:?}", other),
+        };
+        assert_eq!(set.counts, [2, 1]);
+        assert_eq!((set.matched, set.unmatched), (2, 1));
+        let got: Vec<_> = set
+            .lines
+            .iter()
+            .map(|l| (l.number, l.text.as_str(), l.patterns.clone()))
+            .collect();
+        assert_eq!(
+            got,
+            [(1, "a", vec![0]), (2, "ab", vec![0, 1]), (3, "c", vec![])]
+        );
+        assert_eq!(set.lines[1].span.range(), 3..5);
+    }
+
+    #[test]
+    fn set_errors_point_into_the_whole_pattern() {
+        let e = Evaluation::new(Operation::Set, "ab\né(x\nc", "")
+            .compile_set()
+            .unwrap_err();
+        let span = e.span.unwrap();
+        assert_eq!(e.kind, "unclosed group");
+        assert_eq!(span.range(), 5..6);
+        assert_eq!(span.char_range(), 4..5);
+        assert_eq!((span.line, span.column), (2, 2));
+    }
+
+    #[test]
+    fn inspect_looks_at_the_pattern_alone() {
+        match run(Operation::Inspect, "^abc", "") {
+            Outcome::Inspected(i) => assert!(i.anchored_start),
+            other => panic!("{:?}", other),
+        }
+        match run(Operation::Inspect, "a(b", "") {
+            Outcome::PatternError(e) => assert_eq!(e.kind, "unclosed group"),
+            other => panic!("{:?}", other),
+        }
+    }
+
+    #[test]
+    fn explain_looks_at_the_pattern_alone() {
+        assert!(run(Operation::Explain, "a+", "").rows() > 0);
+        match run(Operation::Explain, "a(b", "") {
+            Outcome::PatternError(e) => assert_eq!(e.kind, "unclosed group"),
+            other => panic!("{:?}", other),
+        }
+    }
+
+    #[test]
+    fn only_results_left_out_count_as_cut_off() {
+        let cut_off = |op, max| {
+            Evaluation::new(op, ",", "a,b,c")
+                .with_replacement("")
+                .with_max_results(max)
+                .run()
+                .cut_off()
+        };
+        for op in [Operation::Find, Operation::FindCaptures, Operation::Replace].iter() {
+            assert!(cut_off(*op, 1), "{:?}", op);
+            assert!(!cut_off(*op, 2), "{:?}", op);
+        }
+        assert!(cut_off(Operation::Split, 2));
+        assert!(!cut_off(Operation::Split, 3));
+        assert!(cut_off(Operation::Lines, 0));
+        assert!(!cut_off(Operation::Lines, 1));
+        assert!(!cut_off(Operation::Match, 0));
+    }
+}
@@ -0,0 +1,506 @@
+use regex_syntax::ast::{
+    self, Assertion, AssertionKind, Ast, Class, ClassAsciiKind, ClassBracketed, ClassPerl,
+    ClassPerlKind, ClassSet, ClassSetBinaryOpKind, ClassSetItem, ClassUnicode, ClassUnicodeKind,
+    ClassUnicodeOpKind, Flag, FlagsItemKind, GroupKind, RepetitionKind, RepetitionRange,
+};
+
+use crate::{
+    options::{Flags, Limits},
+    result::{Node, PatternError, Spans},
+};
+
+/// Describes `pattern` in plain English, one [`Node`] per part of it, in the
+/// order the parts appear.  `flags` are the ones in force at the start;
+/// inline flags change them from where they are set, as they do for the
+/// matcher.
+pub fn explain(pattern: &str, flags: &Flags, limits: &Limits) -> Result<Vec<Node>, PatternError> {
+    let ast = ast::parse::ParserBuilder::new()
+        .ignore_whitespace(flags.ignore_whitespace)
+        .nest_limit(limits.nest_limit)
+        .build()
+        .parse(pattern)
+        .map_err(|e| {
+            let err = regex::Error::Syntax(e.to_string());
+            PatternError::new(pattern, flags, limits, &err)
+        })?;
+    let mut e = Explainer { pattern, spans: Spans::new(pattern), nodes: vec![] };
+    let mut flags = *flags;
+    e.sequence(&ast, 0, &mut flags);
+    Ok(e.nodes)
+}
+
+struct Explainer<'a> {
+    pattern: &'a str,
+    spans: Spans<'a>,
+    nodes: Vec<Node>,
+}
+
+impl<'a> Explainer<'a> {
+    fn push(&mut self, depth: usize, span: &ast::Span, text: String) {
+        let range = span.start.offset..span.end.offset;
+        self.nodes.push(Node {
+            depth,
+            span: self.spans.span(range.clone()),
+            source: self.pattern[range].to_string(),
+            text,
+        });
+    }
+
+    /// Explains `ast` at `depth`, or each part of it in turn when it is a
+    /// concatenation.  Runs of literals are put together as one text.
+    fn sequence(&mut self, ast: &Ast, depth: usize, flags: &mut Flags) {
+        let asts = match ast {
+            Ast::Concat(c) => &c.asts,
+            _ => return self.node(ast, depth, flags),
+        };
+        let mut i = 0;
+        while i < asts.len() {
+            let run = asts[i..]
+                .iter()
+                .take_while(|a| matches!(a, Ast::Literal(_)))
+                .count();
+            if run < 2 {
+                self.node(&asts[i], depth, flags);
+                i += 1;
+                continue;
+            }
+            let text: String = asts[i..i + run]
+                .iter()
+                .map(|a| match a {
+                    Ast::Literal(l) => l.c,
+                    _ => unreachable!(),
+                })
+                .collect();
+            let span = ast::Span::new(asts[i].span().start, asts[i + run - 1].span().end);
+            self.push(
+                depth,
+                &span,
+                format!("the text {:?}{}", text, any_case(flags, &text)),
+            );
+            i += run;
+        }
+    }
+
+    fn node(&mut self, ast: &Ast, depth: usize, flags: &mut Flags) {
+        match ast {
+            Ast::Empty(span) => self.push(depth, span, "nothing (the empty string)".to_string()),
+            Ast::Flags(set) => self.push(
+                depth,
+                &set.span,
+                format!("from here on, {}", set_flags(&set.flags, flags)),
+            ),
+            Ast::Literal(l) => {
+                let text = format!("{}{}", character(l.c), any_case(flags, &l.c.to_string()));
+                self.push(depth, &l.span, text)
+            }
+            Ast::Dot(span) => {
+                let text = if flags.dot_matches_new_line {
+                    "any character"
+                } else {
+                    "any character except a newline"
+                };
+                self.push(depth, span, text.to_string())
+            }
+            Ast::Assertion(a) => self.push(depth, &a.span, assertion(a, flags)),
+            Ast::Class(c) => {
+                let text = match c {
+                    Class::Unicode(u) => unicode(u),
+                    Class::Perl(p) => perl(p, flags),
+                    Class::Bracketed(b) if flags.case_insensitive => {
+                        format!("{}, in any case", bracketed(b, flags))
+                    }
+                    Class::Bracketed(b) => bracketed(b, flags),
+                };
+                self.push(depth, c.span(), text)
+            }
+            Ast::Repetition(r) => {
+                let mut how = match &r.op.kind {
+                    RepetitionKind::ZeroOrOne => "optionally".to_string(),
+                    RepetitionKind::ZeroOrMore => "zero or more times".to_string(),
+                    RepetitionKind::OneOrMore => "one or more times".to_string(),
+                    RepetitionKind::Range(RepetitionRange::Exactly(1)) => "once".to_string(),
+                    RepetitionKind::Range(RepetitionRange::Exactly(n)) => {
+                        format!("exactly {} times", n)
+                    }
+                    RepetitionKind::Range(RepetitionRange::AtLeast(n)) => {
+                        format!("{} or more times", n)
+                    }
+                    RepetitionKind::Range(RepetitionRange::Bounded(m, n)) => {
+                        format!("between {} and {} times", m, n)
+                    }
+                };
+                // the U flag swaps which of `*` and `*?` is lazy
+                if r.greedy == flags.swap_greed {
+                    how += ", as few as possible";
+                }
+                let start = self.nodes.len();
+                self.node(&r.ast, depth + 1, flags);
+                if self.nodes.len() == start + 1 {
+                    // a single line reads better with the count after it
+                    let inner = self.nodes.pop().unwrap();
+                    self.push(depth, &r.span, format!("{}, {}", inner.text, how));
+                } else {
+                    self.push(depth, &r.span, format!("{}:", how));
+                    let outer = self.nodes.pop().unwrap();
+                    self.nodes.insert(start, outer);
+                }
+            }
+            Ast::Group(g) => {
+                let mut inner = *flags;
+                let text = match &g.kind {
+                    GroupKind::CaptureIndex(i) => format!("group {}, capturing:", i),
+                    GroupKind::CaptureName(n) => {
+                        format!("group {} \"{}\", capturing:", n.index, n.name)
+                    }
+                    GroupKind::NonCapturing(f) if f.items.is_empty() => {
+                        "group, not capturing:".to_string()
+                    }
+                    GroupKind::NonCapturing(f) => {
+                        format!("group, not capturing, with {}:", set_flags(f, &mut inner))
+                    }
+                };
+                self.push(depth, &g.span, text);
+                self.sequence(&g.ast, depth + 1, &mut inner);
+            }
+            Ast::Alternation(a) => {
+                self.push(depth, &a.span, "one of these alternatives:".to_string());
+                for alt in a.asts.iter() {
+                    match alt {
+                        Ast::Concat(c) if c.asts.len() > 1 => {
+                            let start = self.nodes.len();
+                            self.sequence(alt, depth + 2, flags);
+                            if self.nodes.len() > start + 1 {
+                                self.push(depth + 1, &c.span, "in order:".to_string());
+                                let outer = self.nodes.pop().unwrap();
+                                self.nodes.insert(start, outer);
+                            } else {
+                                self.nodes[start].depth = depth + 1;
+                            }
+                        }
+                        _ => self.sequence(alt, depth + 1, flags),
+                    }
+                }
+            }
+            Ast::Concat(c) => {
+                self.push(depth, &c.span, "in order:".to_string());
+                self.sequence(ast, depth + 1, flags);
+            }
+        }
+    }
+}
+
+/// Applies `set` to `flags` and says what it did, e.g. `i (ignore case) on`.
+fn set_flags(set: &ast::Flags, flags: &mut Flags) -> String {
+    let mut on = true;
+    let mut said = vec![];
+    for item in set.items.iter() {
+        let flag = match item.kind {
+            FlagsItemKind::Negation => {
+                on = false;
+                continue;
+            }
+            FlagsItemKind::Flag(flag) => flag,
+        };
+        let (field, what) = match flag {
+            Flag::CaseInsensitive => (&mut flags.case_insensitive, "i (ignore case)"),
+            Flag::MultiLine => (&mut flags.multi_line, "m (^ and $ match at lines)"),
+            Flag::DotMatchesNewLine => (&mut flags.dot_matches_new_line, "s (. matches \\n)"),
+            Flag::SwapGreed => (&mut flags.swap_greed, "U (lazy by default)"),
+            Flag::Unicode => (&mut flags.unicode, "u (Unicode)"),
+            Flag::IgnoreWhitespace => (&mut flags.ignore_whitespace, "x (ignore whitespace)"),
+        };
+        *field = on;
+        said.push(format!("{} {}", what, if on { "on" } else { "off" }));
+    }
+    said.join(", ")
+}
+
+/// Notes that case is ignored when it matters for `text`.
+fn any_case(flags: &Flags, text: &str) -> &'static str {
+    let cased = text.chars().any(|c| c.to_lowercase().ne(c.to_uppercase()));
+    if flags.case_insensitive && cased {
+        ", in any case"
+    } else {
+        ""
+    }
+}
+
+fn character(c: char) -> String {
+    match c {
+        '\n' => "a newline".to_string(),
+        '\r' => "a carriage return".to_string(),
+        '\t' => "a tab".to_string(),
+        ' ' => "a space".to_string(),
+        c => format!("the character {:?}", c),
+    }
+}
+
+fn assertion(a: &Assertion, flags: &Flags) -> String {
+    let at = match a.kind {
+        AssertionKind::StartLine if flags.multi_line => "at the start of a line",
+        AssertionKind::EndLine if flags.multi_line => "at the end of a line",
+        AssertionKind::StartLine | AssertionKind::StartText => "at the start of the text",
+        AssertionKind::EndLine | AssertionKind::EndText => "at the end of the text",
+        AssertionKind::WordBoundary => "at a word boundary",
+        AssertionKind::NotWordBoundary => "not at a word boundary",
+    };
+    at.to_string()
+}
+
+/// `a digit` for `\d`, or `any character but a digit` for `\D`.
+fn negatable(negated: bool, what: &str) -> String {
+    if negated {
+        format!("any character but {}", what)
+    } else {
+        what.to_string()
+    }
+}
+
+fn perl(p: &ClassPerl, flags: &Flags) -> String {
+    let what = match (p.kind.clone(), flags.unicode) {
+        (ClassPerlKind::Digit, true) => "a digit",
+        (ClassPerlKind::Digit, false) => "an ASCII digit",
+        (ClassPerlKind::Space, true) => "a whitespace character",
+        (ClassPerlKind::Space, false) => "an ASCII whitespace character",
+        (ClassPerlKind::Word, true) => "a word character (letter, digit or _)",
+        (ClassPerlKind::Word, false) => "an ASCII word character (letter, digit or _)",
+    };
+    negatable(p.negated, what)
+}
+
+fn unicode(u: &ClassUnicode) -> String {
+    let mut negated = u.negated;
+    let what = match &u.kind {
+        ClassUnicodeKind::OneLetter(c) => {
+            let category = match c {
+                'L' => "letter",
+                'M' => "mark",
+                'N' => "number",
+                'P' => "punctuation character",
+                'S' => "symbol",
+                'Z' => "separator",
+                'C' => "control or other character",
+                _ => "character",
+            };
+            format!("a Unicode {} (\\p{{{}}})", category, c)
+        }
+        ClassUnicodeKind::Named(name) => format!("a character with Unicode property {}", name),
+        ClassUnicodeKind::NamedValue { op, name, value } => {
+            if let ClassUnicodeOpKind::NotEqual = op {
+                negated = !negated;
+            }
+            format!("a character whose Unicode {} is {}", name, value)
+        }
+    };
+    negatable(negated, &what)
+}
+
+fn bracketed(b: &ClassBracketed, flags: &Flags) -> String {
+    match (&b.kind, b.negated) {
+        (ClassSet::Item(_), false) => format!("one of [{}]", set(&b.kind, flags)),
+        (ClassSet::Item(_), true) => format!("any character except [{}]", set(&b.kind, flags)),
+        (ClassSet::BinaryOp(_), negated) => negatable(negated, &set(&b.kind, flags)),
+    }
+}
+
+fn set(s: &ClassSet, flags: &Flags) -> String {
+    match s {
+        ClassSet::Item(item) => set_item(item, flags),
+        ClassSet::BinaryOp(op) => {
+            let (lhs, rhs) = (set(&op.lhs, flags), set(&op.rhs, flags));
+            match op.kind {
+                ClassSetBinaryOpKind::Intersection => {
+                    format!("a character in both [{}] and [{}]", lhs, rhs)
+                }
+                ClassSetBinaryOpKind::Difference => {
+                    format!("a character in [{}] but not in [{}]", lhs, rhs)
+                }
+                ClassSetBinaryOpKind::SymmetricDifference => {
+                    format!("a character in just one of [{}] and [{}]", lhs, rhs)
+                }
+            }
+        }
+    }
+}
+
+fn set_item(item: &ClassSetItem, flags: &Flags) -> String {
+    match item {
+        ClassSetItem::Empty(_) => "nothing".to_string(),
+        ClassSetItem::Literal(l) => format!("{:?}", l.c),
+        ClassSetItem::Range(r) => format!("{:?} to {:?}", r.start.c, r.end.c),
+        ClassSetItem::Ascii(a) => {
+            let what = match a.kind {
+                ClassAsciiKind::Alnum => "ASCII letters and digits",
+                ClassAsciiKind::Alpha => "ASCII letters",
+                ClassAsciiKind::Ascii => "any ASCII character",
+                ClassAsciiKind::Blank => "space and tab",
+                ClassAsciiKind::Cntrl => "ASCII control characters",
+                ClassAsciiKind::Digit => "ASCII digits",
+                ClassAsciiKind::Graph => "visible ASCII characters",
+                ClassAsciiKind::Lower => "ASCII lowercase letters",
+                ClassAsciiKind::Print => "printable ASCII characters",
+                ClassAsciiKind::Punct => "ASCII punctuation",
+                ClassAsciiKind::Space => "ASCII whitespace",
+                ClassAsciiKind::Upper => "ASCII uppercase letters",
+                ClassAsciiKind::Word => "ASCII word characters",
+                ClassAsciiKind::Xdigit => "hex digits",
+            };
+            if a.negated {
+                format!("anything but {}", what)
+            } else {
+                what.to_string()
+            }
+        }
+        ClassSetItem::Unicode(u) => unicode(u),
+        ClassSetItem::Perl(p) => perl(p, flags),
+        ClassSetItem::Bracketed(b) => bracketed(b, flags),
+        ClassSetItem::Union(u) => u
+            .items
+            .iter()
+            .map(|item| set_item(item, flags))
+            .collect::<Vec<_>>()
+            .join(", "),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The explanation, a line per node indented two spaces per level.
+    fn explained(pattern: &str) -> Vec<String> {
+        explain(pattern, &Flags::default(), &Limits::default())
+            .unwrap()
+            .iter()
+            .map(|n| format!("{}{}", "  ".repeat(n.depth), n.text))
+            .collect()
+    }
+
+    #[test]
+    fn groups_say_what_they_capture() {
+        assert_eq!(
+            explained(r"(?P<year>\d{4})-(\d)(?:ab)"),
+            vec![
+                "group 1 \"year\", capturing:",
+                "  a digit, exactly 4 times",
+                "the character '-'",
+                "group 2, capturing:",
+                "  a digit",
+                "group, not capturing:",
+                "  the text \"ab\"",
+            ]
+        );
+        assert_eq!(
+            explained("(?i:x)y"),
+            vec![
+                "group, not capturing, with i (ignore case) on:",
+                "  the character 'x', in any case",
+                "the character 'y'",
+            ]
+        );
+    }
+
+    #[test]
+    fn classes_list_what_they_match() {
+        assert_eq!(
+            explained("[a-z0-9_]"),
+            vec!["one of ['a' to 'z', '0' to '9', '_']"]
+        );
+        assert_eq!(
+            explained(r"[^\s]"),
+            vec!["any character except [a whitespace character]"]
+        );
+        assert_eq!(
+            explained("[[:alpha:]&&[a-f]]"),
+            vec!["a character in both [ASCII letters] and [one of ['a' to 'f']]"]
+        );
+        assert_eq!(explained(r"\pL"), vec!["a Unicode letter (\\p{L})"]);
+        assert_eq!(
+            explained("(?s)."),
+            vec!["from here on, s (. matches \\n) on", "any character"]
+        );
+    }
+
+    #[test]
+    fn repetitions_say_how_often() {
+        for (pattern, text) in [
+            ("x?", "the character 'x', optionally"),
+            (
+                "x*?",
+                "the character 'x', zero or more times, as few as possible",
+            ),
+            ("x{2,5}", "the character 'x', between 2 and 5 times"),
+            ("x{3,}", "the character 'x', 3 or more times"),
+        ]
+        .iter()
+        {
+            assert_eq!(explained(pattern), vec![*text]);
+        }
+        assert_eq!(
+            explained("(?U)x*"),
+            vec![
+                "from here on, U (lazy by default) on",
+                "the character 'x', zero or more times, as few as possible",
+            ]
+        );
+        assert_eq!(
+            explained("(?:ab)+"),
+            vec![
+                "one or more times:",
+                "  group, not capturing:",
+                "    the text \"ab\""
+            ]
+        );
+    }
+
+    #[test]
+    fn anchors_depend_on_multi_line() {
+        assert_eq!(
+            explained("^a$"),
+            vec![
+                "at the start of the text",
+                "the character 'a'",
+                "at the end of the text"
+            ]
+        );
+        assert_eq!(
+            explained("(?m)^a$"),
+            vec![
+                "from here on, m (^ and $ match at lines) on",
+                "at the start of a line",
+                "the character 'a'",
+                "at the end of a line",
+            ]
+        );
+        assert_eq!(
+            explained(r"\bfoo\B"),
+            vec![
+                "at a word boundary",
+                "the text \"foo\"",
+                "not at a word boundary"
+            ]
+        );
+    }
+
+    #[test]
+    fn alternatives_are_listed_one_level_down() {
+        assert_eq!(
+            explained("cat|a+b|"),
+            vec![
+                "one of these alternatives:",
+                "  the text \"cat\"",
+                "  in order:",
+                "    the character 'a', one or more times",
+                "    the character 'b'",
+                "  nothing (the empty string)",
+            ]
+        );
+    }
+
+    #[test]
+    fn pattern_that_does_not_parse_is_an_error() {
+        let e = explain("a(b", &Flags::default(), &Limits::default()).unwrap_err();
+        assert_eq!(e.kind, "unclosed group");
+        assert_eq!(e.span.map(|s| s.range()), Some(1..2));
+    }
+}
@@ -2,160 +2,250 @@
 
 use std::{
     borrow::{Borrow, BorrowMut},
-    cell::RefCell,
-    fmt::Display,
-    fs::{create_dir_all, DirBuilder, File},
-    io::{BufReader, BufWriter},
+    cell::{Cell, RefCell},
+    fs,
     ops::Deref,
+    path::Path,
     rc::Rc,
+    sync::Arc,
     time::SystemTime,
 };
 
-use anyhow::Context;
-use chrono::{DateTime, Local, Utc};
-use cpu_time::{ProcessTime, ThreadTime};
 use fltk::{
     app,
-    button::Button,
+    button::{Button, CheckButton},
     dialog,
-    enums::{Align, Color, Font},
+    enums::{Align, CallbackTrigger, Color, Event, Font},
     frame::Frame,
     group::{Pack, PackType},
     image::PngImage,
-    input::Input,
-    prelude::{DisplayExt, GroupExt, InputExt, WidgetBase, WidgetExt, WindowExt},
-    text::{self, TextEditor},
+    input::{Input, IntInput, MultilineInput},
+    prelude::{ButtonExt, DisplayExt, GroupExt, InputExt, WidgetBase, WidgetExt, WindowExt},
+    text::{self, StyleTableEntry, TextEditor},
     window::Window,
 };
 use fltk_theme::{ThemeType, WidgetTheme};
-use regex::Regex;
-use serde::{Deserialize, Serialize};
-
-const DO_CPU_TIME: bool = false;
-
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct Execution {
-    time: chrono::DateTime<Local>,
-    operation: String,
-    pattern: String,
-    string: String,
-    count: u32,
+use re_tester::{
+    bench::DEFAULT_RUNS,
+    bytes::{ByteSearch, Encoding, MAX_BYTE_RESULTS},
+    codegen::{self, snippet, Language},
+    compare::Comparison,
+    eval::MAX_FILE_RESULTS,
+    live::DEBOUNCE,
+    render::PAGE_ROWS,
+    result::PatternError,
+    suite::Case,
+    Benchmark, Evaluation, Execution, Flags, Format, Highlight, History, Limits, Live, Operation,
+    Outcome, Page, Suite, TestFile,
+};
+
+/// Text colours for highlighted input: whole matches first, then capture
+/// groups cycling through the rest.
+const GROUP_COLORS: [Color; 6] = [
+    Color::Blue,
+    Color::Red,
+    Color::DarkGreen,
+    Color::Magenta,
+    Color::DarkCyan,
+    Color::DarkYellow,
+];
+
+/// The style buffer char for `group`; 'A' is left for unmatched text.
+fn group_style(group: usize) -> u8 {
+    let i = if group == 0 {
+        0
+    } else {
+        1 + (group - 1) % (GROUP_COLORS.len() - 1)
+    };
+    b'B' + i as u8
 }
 
-impl Execution {
-    pub fn new(o: &str, p: &str, s: &str) -> Self {
-        Execution {
-            time: chrono::Local::now(),
-            operation: o.into(),
-            pattern: p.into(),
-            string: s.into(),
-            count: 0,
+/// The flag toggles and the label showing their inline equivalent.
+#[derive(Debug, Clone)]
+struct FlagBoxes {
+    case_insensitive: CheckButton,
+    multi_line: CheckButton,
+    dot_matches_new_line: CheckButton,
+    swap_greed: CheckButton,
+    ignore_whitespace: CheckButton,
+    unicode: CheckButton,
+    inline: Frame,
+}
+
+impl FlagBoxes {
+    pub fn new() -> Self {
+        let check = |label: &str, tip: &str| {
+            let mut b = CheckButton::default().with_size(40, 25).with_label(label);
+            b.set_tooltip(tip);
+            b
+        };
+        let mut fb = FlagBoxes {
+            case_insensitive: check("i", "case insensitive"),
+            multi_line: check("m", "^ and $ match at line boundaries"),
+            dot_matches_new_line: check("s", ". matches \\n"),
+            swap_greed: check("U", "swap greedy and lazy repetition"),
+            ignore_whitespace: check("x", "ignore whitespace and # comments"),
+            unicode: check("u", "unicode"),
+            inline: Frame::default()
+                .with_size(120, 25)
+                .with_align(Align::Inside | Align::Left),
+        };
+        fb.set(&Flags::default());
+        fb
+    }
+
+    fn buttons(&self) -> Vec<CheckButton> {
+        vec![
+            self.case_insensitive.clone(),
+            self.multi_line.clone(),
+            self.dot_matches_new_line.clone(),
+            self.swap_greed.clone(),
+            self.ignore_whitespace.clone(),
+            self.unicode.clone(),
+        ]
+    }
+
+    pub fn flags(&self) -> Flags {
+        Flags {
+            case_insensitive: self.case_insensitive.is_checked(),
+            multi_line: self.multi_line.is_checked(),
+            dot_matches_new_line: self.dot_matches_new_line.is_checked(),
+            swap_greed: self.swap_greed.is_checked(),
+            ignore_whitespace: self.ignore_whitespace.is_checked(),
+            unicode: self.unicode.is_checked(),
         }
     }
-}
 
-impl Display for Execution {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(
-            f,
-            "{}: {} Op: \"{}\" RE: \"{}\" str: \"{}\"",
-            self.count,
-            self.time.to_rfc3339(),
-            self.operation,
-            self.pattern,
-            self.string
-        )
+    pub fn set(&mut self, f: &Flags) {
+        self.case_insensitive.set_checked(f.case_insensitive);
+        self.multi_line.set_checked(f.multi_line);
+        self.dot_matches_new_line
+            .set_checked(f.dot_matches_new_line);
+        self.swap_greed.set_checked(f.swap_greed);
+        self.ignore_whitespace.set_checked(f.ignore_whitespace);
+        self.unicode.set_checked(f.unicode);
+        self.show_inline();
+    }
+
+    pub fn show_inline(&mut self) {
+        self.inline.set_label(&self.flags().inline());
+        self.inline.redraw();
     }
 }
 
+/// Inputs for the compile limits.  Anything that does not parse falls back
+/// to the default limit.
 #[derive(Debug, Clone)]
-pub struct History {
-    hist: Rc<RefCell<Vec<Execution>>>,
+struct LimitInputs {
+    size_limit: IntInput,
+    dfa_size_limit: IntInput,
+    nest_limit: IntInput,
 }
 
-impl Default for History {
-    fn default() -> Self {
-        History::new()
+impl LimitInputs {
+    pub fn new() -> Self {
+        let input = |label: &str, w: i32| {
+            Frame::default()
+                .with_size(label.len() as i32 * 8, 25)
+                .with_label(label)
+                .with_align(Align::Inside | Align::Right);
+            IntInput::default().with_size(w, 25)
+        };
+        let mut li = LimitInputs {
+            size_limit: input("size", 90),
+            dfa_size_limit: input("dfa", 90),
+            nest_limit: input("nest", 50),
+        };
+        li.set(&Limits::default());
+        li
     }
-}
 
-impl History {
-    pub fn new() -> Self {
-        History {
-            hist: Rc::new(RefCell::new(Vec::new())),
+    pub fn limits(&self) -> Limits {
+        let d = Limits::default();
+        Limits {
+            size_limit: self.size_limit.value().parse().unwrap_or(d.size_limit),
+            dfa_size_limit: self
+                .dfa_size_limit
+                .value()
+                .parse()
+                .unwrap_or(d.dfa_size_limit),
+            nest_limit: self.nest_limit.value().parse().unwrap_or(d.nest_limit),
         }
     }
 
-    pub fn load_from() -> Result<History, Box<dyn std::error::Error>> {
-        let mut path = dirs::home_dir().context("cannot get home directory to load prior state")?;
-        path.push(".re_test");
-        create_dir_all(&path)
-            .with_context(|| format!("Unable to create directory {}", &path.to_string_lossy()))?;
-        path.push("state.json");
-        let mut f = File::open(&path)?;
-        let mut rb = BufReader::new(&f);
-        let res: Vec<Execution> = serde_json::from_reader(rb)?;
+    fn inputs(&self) -> Vec<IntInput> {
+        vec![
+            self.size_limit.clone(),
+            self.dfa_size_limit.clone(),
+            self.nest_limit.clone(),
+        ]
+    }
 
-        Ok(History {
-            hist: Rc::new(RefCell::new(res)),
-        })
+    pub fn set(&mut self, l: &Limits) {
+        self.size_limit.set_value(&l.size_limit.to_string());
+        self.dfa_size_limit.set_value(&l.dfa_size_limit.to_string());
+        self.nest_limit.set_value(&l.nest_limit.to_string());
     }
+}
 
-    pub fn save(&mut self) -> Result<(), Box<dyn std::error::Error>> {
-        let mut path = dirs::home_dir().context("cannot get home directory to load prior stuff")?;
+/// The test file button, what is loaded, and the result paging buttons.
+#[derive(Debug, Clone)]
+struct FileRow {
+    open: Button,
+    name: Frame,
+    prev: Button,
+    next: Button,
+}
 
-        // let v: Value = serde_json::from_str(data)?;
-        path.push(".re_test");
-        create_dir_all(&path)
-            .with_context(|| format!("Unable to create directory {}", &path.to_string_lossy()))?;
-        path.push("state.json");
-        let mut f = File::create(&path)?; // .with_context(||"cannot open state file: {}", &path)?;
-        let bw = BufWriter::new(&f);
-        let h = self.hist.deref().borrow();
-        serde_json::to_writer_pretty(bw, &*h);
-        Ok(())
+impl FileRow {
+    pub fn new() -> Self {
+        let mut open = Button::default().with_size(80, 25).with_label("&Open...");
+        open.set_tooltip("search a file instead of the string");
+        let name = Frame::default()
+            .with_size(360, 25)
+            .with_align(Align::Inside | Align::Left);
+        let mut prev = Button::default().with_size(30, 25).with_label("@<");
+        prev.set_tooltip("previous page of results");
+        let mut next = Button::default().with_size(30, 25).with_label("@>");
+        next.set_tooltip("next page of results");
+        FileRow { open, name, prev, next }
     }
+}
 
-    pub fn add(&mut self, mut exe: Execution) {
-        let mut found = usize::max_value();
-        for (i, e) in self.hist.deref().borrow().iter().enumerate().rev() {
-            if e.pattern == exe.pattern && e.string == exe.string {
-                found = i;
-                break;
-            }
-        }
-        exe.count = if found != usize::max_value() {
-            (self.hist.deref().borrow_mut().remove(found).count + 1u32)
-        } else {
-            1u32
-        };
-        let mut h = self.hist.deref().borrow_mut().push(exe);
-        match self.save() {
-            Err(e) => dialog::alert(200, 200, &format!("Unable to save result: {}", e)),
-            _ => {}
-        }
-    }
+/// The test suite buttons and how many cases it holds.
+#[derive(Debug, Clone)]
+struct SuiteRow {
+    load: Button,
+    save: Button,
+    add: Button,
+    run: Button,
+    rust: Button,
+    count: Frame,
+}
 
-    pub fn to_str(&self) -> String {
-        let mut s = String::with_capacity(1024);
-        let h = self.hist.deref().borrow();
-        if h.len() <= 0 {
-            s.push_str("No history as yet");
-        } else {
-            for (i, o) in h.iter().enumerate().rev() {
-                s.push_str(&format!("{}: {}\n", i, o));
-            }
-        }
-        s
+impl SuiteRow {
+    pub fn new() -> Self {
+        let mut load = Button::default().with_size(80, 25).with_label("Loa&d...");
+        load.set_tooltip("load a pattern and its test cases");
+        let mut save = Button::default().with_size(80, 25).with_label("Sa&ve...");
+        save.set_tooltip("save the pattern and its test cases");
+        let mut add = Button::default().with_size(80, 25).with_label("&Add case");
+        add.set_tooltip("add the string as a case expecting what the pattern does now");
+        let mut run = Button::default().with_size(80, 25).with_label("&Test");
+        run.set_tooltip("check every case against the pattern");
+        let mut rust = Button::default().with_size(80, 25).with_label("R&ust");
+        rust.set_tooltip("copy the cases as Rust tests");
+        let count = Frame::default()
+            .with_size(120, 25)
+            .with_align(Align::Inside | Align::Left);
+        let mut sr = SuiteRow { load, save, add, run, rust, count };
+        sr.set_count(0);
+        sr
     }
 
-    pub fn last(&self) -> Option<Execution> {
-        let h = self.hist.deref().borrow();
-        if h.len() <= 0 {
-            None
-        } else {
-            Some(h.last().unwrap().clone())
-        }
+    pub fn set_count(&mut self, n: usize) {
+        self.count.set_label(&format!("{} cases", n));
+        self.count.redraw();
     }
 }
 
@@ -163,179 +253,468 @@ impl History {
 struct ReTest {
     out: TextEditor,
     buff: text::TextBuffer,
-    inp: Input,
-    pat: Input,
-    cpu_frame: Frame,
-    cpu_time: Rc<ProcessTime>,
+    inp: text::TextBuffer,
+    style: text::TextBuffer,
+    /// What the input is coloured with, and the output line of each row.
+    highlights: Rc<RefCell<(Vec<Highlight>, Vec<usize>)>>,
+    /// Several lines long, for the patterns of a set.
+    pat: MultilineInput,
+    rep: Input,
+    /// The pattern compared with `pat`.
+    pat_b: Input,
+    flags: FlagBoxes,
+    limits: LimitInputs,
+    /// How many times `Bench` runs the last operation.
+    runs: IntInput,
     hist: History,
+    live_but: CheckButton,
+    live: Rc<Live>,
+    last_op: Rc<Cell<Operation>>,
+    file_row: FileRow,
+    file: Rc<RefCell<Option<Arc<TestFile>>>>,
+    /// The result shown, kept to page through.
+    last: Rc<RefCell<Option<(Evaluation, Outcome)>>>,
+    page: Rc<Cell<Page>>,
+    suite_row: SuiteRow,
+    /// Cases of the test suite; its pattern, flags and limits are the ones
+    /// showing.
+    cases: Rc<RefCell<Vec<Case>>>,
+    /// Hidden; text is copied to the clipboard by selecting it in here.
+    clip: MultilineInput,
 }
 
 impl ReTest {
     pub fn new(
         out: &TextEditor,
         buff: &text::TextBuffer,
-        inp: &Input,
-        pat: &Input,
-        cpu_frame: &Frame,
-        cpu_time: &ProcessTime,
+        inp: &text::TextBuffer,
+        style: &text::TextBuffer,
+        pat: &MultilineInput,
+        rep: &Input,
+        pat_b: &Input,
+        flags: &FlagBoxes,
+        limits: &LimitInputs,
+        runs: &IntInput,
         hist: History,
+        live_but: &CheckButton,
+        file_row: &FileRow,
+        suite_row: &SuiteRow,
+        clip: &MultilineInput,
+        results: app::Sender<(Evaluation, Outcome)>,
     ) -> Self {
         let r = ReTest {
             out: out.clone(),
             buff: buff.clone(),
             inp: inp.clone(),
+            style: style.clone(),
+            highlights: Rc::new(RefCell::new((vec![], vec![]))),
             pat: pat.clone(),
-            cpu_frame: cpu_frame.clone(),
-            cpu_time: Rc::new(*cpu_time),
+            rep: rep.clone(),
+            pat_b: pat_b.clone(),
+            flags: flags.clone(),
+            limits: limits.clone(),
+            runs: runs.clone(),
             hist,
+            live_but: live_but.clone(),
+            live: Rc::new(Live::new(DEBOUNCE, move |eval, outcome| {
+                results.send((eval, outcome))
+            })),
+            last_op: Rc::new(Cell::new(Operation::Match)),
+            file_row: file_row.clone(),
+            file: Rc::new(RefCell::new(None)),
+            last: Rc::new(RefCell::new(None)),
+            page: Rc::new(Cell::new(Page::first(PAGE_ROWS))),
+            suite_row: suite_row.clone(),
+            cases: Rc::new(RefCell::new(vec![])),
+            clip: clip.clone(),
         };
         r
     }
 
-    pub fn update_cpu(&mut self) {
-        if DO_CPU_TIME {
-            self.cpu_frame
-                .set_label(&format!("{:?}", &self.cpu_time.elapsed()));
-            self.cpu_frame.redraw();
-        }
+    pub fn history(&mut self) {
+        self.buff.set_text(&self.hist.to_str("\n"));
     }
 
-    pub fn history(&mut self) {
-        self.buff.set_text(&self.hist.to_str());
-        self.update_cpu();
+    fn evaluation(&self, op: Operation) -> Evaluation {
+        let eval = Evaluation::new(op, &self.pat.value(), &self.inp.text())
+            .with_replacement(&self.rep.value())
+            .with_flags(self.flags.flags())
+            .with_limits(self.limits.limits());
+        match &*self.file.deref().borrow() {
+            Some(f) => eval.with_file(f.clone()).with_max_results(MAX_FILE_RESULTS),
+            None => eval,
+        }
     }
 
-    pub fn matches(&mut self) {
-        self.buff.set_text("");
-        let mut results = String::with_capacity(128);
-        self.out.set_text_color(Color::Black);
-        match Regex::new(&self.pat.value()) {
-            Err(e) => {
-                self.out.set_text_color(Color::Red);
-                results.push_str(&format!("Error with pattern: {}", e))
+    /// Loads a file to search in place of the string, or unloads the one
+    /// loaded.
+    pub fn open_file(&mut self) {
+        if self.file.deref().borrow_mut().take().is_some() {
+            self.file_row.name.set_label("");
+            self.file_row.open.set_label("&Open...");
+        } else if let Some(path) = dialog::file_chooser("Open test file", "*", ".", false) {
+            match TestFile::open(Path::new(&path)) {
+                Ok(f) => {
+                    self.file_row.name.set_label(&f.to_string());
+                    self.file_row.open.set_label("&Close");
+                    *self.file.deref().borrow_mut() = Some(Arc::new(f));
+                }
+                Err(e) => dialog::alert(200, 200, &format!("Unable to load test file: {}", e)),
             }
-            Ok(res) => {
-                if res.is_match(&self.inp.value()) {
-                    results.push_str(&format!(
-                        "Matching: \"{}\"\nAgainst: \"{}\"\n\n",
-                        &self.pat.value(),
-                        &self.inp.value()
-                    ));
-                    let s = self.inp.value().clone();
-                    let caps = res.captures(&s);
-                    if let Some(caps) = caps {
-                        for (i, c) in caps.iter().enumerate() {
-                            if let Some(cc) = c {
-                                results.push_str(&format!("group[{}] = \"{}\"\n", i, cc.as_str()));
-                            } else {
-                                results.push_str(&format!("group[{}] = None\n", i));
-                            }
-                        }
-                    } else {
-                        results.push_str("There are no group captures");
-                    }
-                } else {
-                    self.out.set_text_color(Color::Red);
+        }
+        self.file_row.name.redraw();
+        self.live_update();
+    }
 
-                    results.push_str(&format!(
-                        "String:\n\"{}\"\nDoes not match Pattern:\n\"{}\"",
-                        &self.inp.value(),
-                        &self.pat.value()
-                    ));
-                }
+    /// Shows the current page of the last result.
+    fn render(&mut self) {
+        if let Some((eval, outcome)) = &*self.last.deref().borrow() {
+            let (text, rows) = Format::Plain.render_page(eval, outcome, self.page.get());
+            self.buff.set_text(&text);
+            self.highlights.deref().borrow_mut().1 = rows;
+        }
+    }
+
+    pub fn next_page(&mut self) {
+        if let Some((_, outcome)) = &*self.last.deref().borrow() {
+            self.page.set(self.page.get().next(outcome.rows()));
+        }
+        self.render();
+    }
+
+    pub fn prev_page(&mut self) {
+        self.page.set(self.page.get().prev());
+        self.render();
+    }
+
+    pub fn show(&mut self, eval: &Evaluation, outcome: &Outcome) {
+        if outcome.is_failure() {
+            self.out.set_text_color(Color::Red);
+        } else {
+            self.out.set_text_color(Color::Black);
+        }
+        self.page.set(Page::first(PAGE_ROWS));
+        *self.last.deref().borrow_mut() = Some((eval.clone(), outcome.clone()));
+        // a live result can arrive after the input changed again
+        if eval.file.is_none() && eval.string == self.inp.text() {
+            self.paint(outcome.highlights());
+        }
+        self.render();
+    }
+
+    /// Colours the input, which `highlights` must have come from.
+    fn paint(&mut self, highlights: Vec<Highlight>) {
+        let mut styles = vec![b'A'; self.inp.length() as usize];
+        for h in highlights.iter() {
+            let style = group_style(h.group);
+            styles[h.span.range()].iter_mut().for_each(|c| *c = style);
+        }
+        self.style.set_text(&String::from_utf8(styles).unwrap());
+        self.highlights.deref().borrow_mut().0 = highlights;
+    }
+
+    /// Drops the colouring once the input is edited, as the offsets no
+    /// longer line up.
+    pub fn clear_highlights(&mut self) {
+        self.style.set_text(&"A".repeat(self.inp.length() as usize));
+        *self.highlights.deref().borrow_mut() = (vec![], vec![]);
+    }
+
+    /// Scrolls the output to, and selects, the result row of the highlight at
+    /// byte `pos` of the input.
+    pub fn jump_to_result(&mut self, pos: usize) {
+        let line = {
+            let h = self.highlights.deref().borrow();
+            let start = self.page.get().start;
+            let row = Highlight::at(&h.0, pos).and_then(|hl| hl.row.checked_sub(start));
+            match row.and_then(|r| h.1.get(r)) {
+                Some(line) => *line as i32,
+                None => return,
             }
+        };
+        let text = self.buff.text();
+        let start: usize = text
+            .split_inclusive('\n')
+            .take(line as usize)
+            .map(str::len)
+            .sum();
+        let end = text[start..].find('\n').map_or(text.len(), |n| start + n);
+        self.buff.select(start as i32, end as i32);
+        self.out.scroll(line + 1, 0);
+    }
+
+    /// Re-runs the last operation in the background when live mode is on.
+    pub fn live_update(&mut self) {
+        if self.live_but.is_checked() {
+            self.live.submit(self.evaluation(self.last_op.get()));
+        }
+    }
+
+    fn evaluate(&mut self, op: Operation) {
+        self.last_op.set(op);
+        let eval = self.evaluation(op);
+        let outcome = eval.run();
+        self.show(&eval, &outcome);
+        if let Outcome::PatternError(PatternError { span: Some(span), .. }) = &outcome {
+            // FLTK input positions are byte offsets
+            self.pat.set_position(span.start as i32).ok();
+            self.pat.set_mark(span.end as i32).ok();
+            self.pat.take_focus().ok();
+        }
+        if let Err(e) = self.hist.add(Execution::from(&eval)) {
+            dialog::alert(200, 200, &format!("Unable to save result: {}", e));
         }
-        self.buff.set_text(&results);
-        self.hist.add(Execution::new(
-            "match",
-            &self.pat.value(),
-            &self.inp.value(),
-        ));
-        self.update_cpu();
+    }
+
+    pub fn matches(&mut self) {
+        self.evaluate(Operation::Match);
     }
 
     pub fn find(&mut self) {
-        self.out.set_text_color(Color::Black);
-        self.buff.set_text("");
+        self.evaluate(Operation::Find);
+    }
 
-        let mut results = String::with_capacity(128);
-        let pattern = self.pat.value().clone();
-        let string = self.inp.value().clone();
+    pub fn find_captures(&mut self) {
+        self.evaluate(Operation::FindCaptures);
+    }
 
-        match Regex::new(&pattern) {
-            Err(e) => {
-                self.out.set_text_color(Color::Red);
-                results.push_str(&format!("Error with pattern: {}", e))
+    pub fn split(&mut self) {
+        self.evaluate(Operation::Split);
+    }
+
+    pub fn replace(&mut self) {
+        self.evaluate(Operation::Replace);
+    }
+
+    pub fn lines(&mut self) {
+        self.evaluate(Operation::Lines);
+    }
+
+    pub fn explain(&mut self) {
+        self.evaluate(Operation::Explain);
+    }
+
+    pub fn inspect(&mut self) {
+        self.evaluate(Operation::Inspect);
+    }
+
+    pub fn set(&mut self) {
+        self.evaluate(Operation::Set);
+    }
+
+    fn suite(&self) -> Suite {
+        let mut suite = Suite::new(&self.pat.value(), self.flags.flags(), self.limits.limits());
+        suite.cases = self.cases.deref().borrow().clone();
+        suite
+    }
+
+    /// Loads a test suite, putting its pattern, flags and limits in place,
+    /// and runs it.
+    pub fn load_suite(&mut self) {
+        let path = match dialog::file_chooser("Load test suite", "*.json", ".", false) {
+            Some(path) => path,
+            None => return,
+        };
+        match Suite::load(Path::new(&path)) {
+            Ok(suite) => {
+                self.pat.set_value(&suite.pattern);
+                self.flags.set(&suite.flags);
+                self.limits.set(&suite.limits);
+                self.suite_row.set_count(suite.cases.len());
+                *self.cases.deref().borrow_mut() = suite.cases;
+                self.run_suite();
             }
-            Ok(res) => {
-                results.push_str(&format!(
-                    "Find pattern:\n\"{}\"\nIn:\n\"{}\"\n\n",
-                    pattern, string
-                ));
-                let mut finds = 0;
-                for (i, m) in res.find_iter(&string).enumerate() {
-                    finds += 1;
-
-                    results.push_str(&format!(
-                        "Iteration {} found \"{}\" at ({:?})\n",
-                        i,
-                        m.as_str(),
-                        m.range()
-                    ));
-                }
-                if finds <= 0 {
-                    self.out.set_text_color(Color::Red);
-                    results.push_str("Found nothing");
-                }
+            Err(e) => dialog::alert(200, 200, &format!("Unable to load test suite: {}", e)),
+        }
+    }
+
+    pub fn save_suite(&mut self) {
+        let mut chooser =
+            dialog::NativeFileChooser::new(dialog::NativeFileChooserType::BrowseSaveFile);
+        chooser.set_title("Save test suite");
+        chooser.set_filter("*.json");
+        chooser.set_option(dialog::NativeFileChooserOptions::SaveAsConfirm);
+        chooser.show();
+        let path = chooser.filename();
+        if path.as_os_str().is_empty() {
+            return;
+        }
+        if let Err(e) = self.suite().save(&path) {
+            dialog::alert(200, 200, &format!("Unable to save test suite: {}", e));
+        }
+    }
+
+    /// Adds the string as a case expecting what the pattern does with it now.
+    pub fn add_case(&mut self) {
+        let name = match dialog::input_default("Name of the case:", "") {
+            Some(name) => name,
+            None => return,
+        };
+        let mut suite = self.suite();
+        match suite.record(&name, &self.inp.text()) {
+            Ok(()) => {
+                self.suite_row.set_count(suite.cases.len());
+                *self.cases.deref().borrow_mut() = suite.cases;
             }
+            Err(e) => dialog::alert(200, 200, &format!("Unable to add case: {}", e.message)),
         }
-        self.buff.set_text(&results);
-        self.hist
-            .add(Execution::new("find", &self.pat.value(), &self.inp.value()));
-        self.update_cpu();
     }
 
-    pub fn split(&mut self) {
-        self.buff.set_text("");
-        self.out.set_text_color(Color::Black);
+    /// Shows pass or fail for each case of the suite.
+    pub fn run_suite(&mut self) {
+        let report = self.suite().run();
+        if report.all_passed() {
+            self.out.set_text_color(Color::Black);
+        } else {
+            self.out.set_text_color(Color::Red);
+        }
+        // the report has no pages, rows or JSON of its own
+        *self.last.deref().borrow_mut() = None;
+        self.highlights.deref().borrow_mut().1.clear();
+        self.buff.set_text(&report.to_string());
+    }
+
+    /// Copies Rust tests of the suite to the clipboard; with no cases yet the
+    /// string is the one tested.
+    pub fn copy_rust_tests(&mut self) {
+        let mut suite = self.suite();
+        if suite.cases.is_empty() {
+            suite.record("", &self.inp.text()).ok();
+        }
+        match codegen::rust_tests(&suite) {
+            Ok(code) => self.copy(&code),
+            Err(e) => dialog::alert(200, 200, &format!("Unable to make tests: {}", e.message)),
+        }
+    }
 
-        let mut results = String::with_capacity(128);
-        let pattern = self.pat.value().clone();
-        let string = self.inp.value().clone();
+    /// Shows a program doing what the last operation did, in `language`, and
+    /// copies it to the clipboard.
+    pub fn code(&mut self, language: Language) {
+        match snippet(language, &self.evaluation(self.last_op.get())) {
+            Ok(snippet) => {
+                let code = snippet.to_string();
+                self.copy(&code);
+                self.out.set_text_color(Color::Black);
+                *self.last.deref().borrow_mut() = None;
+                self.highlights.deref().borrow_mut().1.clear();
+                self.buff.set_text(&code);
+            }
+            Err(e) => dialog::alert(200, 200, &format!("Unable to write code: {}", e.message)),
+        }
+    }
 
-        match Regex::new(&pattern) {
+    /// Runs the last operation with `regex::bytes` on `haystack`, showing up
+    /// to `MAX_BYTE_RESULTS` matches at their offsets with hex dumps.
+    fn search_bytes(&mut self, haystack: Vec<u8>, source: &str) {
+        let search = match ByteSearch::new(self.last_op.get(), &self.pat.value(), haystack, source)
+        {
+            Ok(search) => search
+                .with_flags(self.flags.flags())
+                .with_limits(self.limits.limits())
+                .with_max_results(MAX_BYTE_RESULTS),
             Err(e) => {
-                self.out.set_text_color(Color::Red);
-                results.push_str(&format!("Error with pattern: {}", e))
+                dialog::alert(200, 200, &format!("Unable to search bytes: {}", e));
+                return;
             }
-            Ok(res) => {
-                results.push_str(&format!(
-                    "Splitting with pattern:\n\"{}\"\nString:\n\"{}\"\n\n",
-                    pattern, string
-                ));
-                let mut finds = 0;
-                for (i, m) in res.split(&string).enumerate() {
-                    finds += 1;
-                    results.push_str(&format!("Index {} is \"{}\"\n", i, m));
-                }
-                if finds <= 0 {
+        };
+        match search.run() {
+            Ok(found) => {
+                if found.matches.is_empty() {
                     self.out.set_text_color(Color::Red);
-                    results.push_str("Found nothing");
+                } else {
+                    self.out.set_text_color(Color::Black);
                 }
+                *self.last.deref().borrow_mut() = None;
+                self.highlights.deref().borrow_mut().1.clear();
+                self.buff.set_text(&found.to_string());
             }
+            Err(e) => self.show(&search.evaluation(), &Outcome::PatternError(e)),
+        }
+    }
+
+    /// Searches the bytes the string spells out in `encoding`.
+    pub fn decode_bytes(&mut self, encoding: Encoding) {
+        match encoding.decode(&self.inp.text()) {
+            Ok(bytes) => self.search_bytes(bytes, &format!("{} from the string", encoding)),
+            Err(e) => dialog::alert(200, 200, &format!("Unable to read {}: {}", encoding, e)),
         }
-        self.buff.set_text(&results);
-        self.hist.add(Execution::new(
-            "split",
-            &self.pat.value(),
-            &self.inp.value(),
-        ));
-        self.update_cpu();
+    }
+
+    /// Searches the bytes of a file, which need not be text.
+    pub fn open_bytes(&mut self) {
+        if let Some(path) = dialog::file_chooser("Open binary file", "*", ".", false) {
+            match fs::read(&path) {
+                Ok(bytes) => self.search_bytes(bytes, &format!("file {}", path)),
+                Err(e) => dialog::alert(200, 200, &format!("Unable to load binary file: {}", e)),
+            }
+        }
+    }
+
+    /// Tries the pattern and pattern B on each line of the string, or on the
+    /// cases of the suite when it has any, colouring the lines they disagree
+    /// on.
+    pub fn compare(&mut self) {
+        let a = self.evaluation(Operation::Match);
+        let mut b = a.clone();
+        b.pattern = self.pat_b.value();
+        let comparison = {
+            let cases = self.cases.deref().borrow();
+            let cases = if cases.is_empty() {
+                None
+            } else {
+                Some(cases.as_slice())
+            };
+            Comparison::run(&a, &b, cases)
+        };
+        if comparison.all_same() {
+            self.out.set_text_color(Color::Black);
+        } else {
+            self.out.set_text_color(Color::Red);
+        }
+        *self.last.deref().borrow_mut() = None;
+        self.highlights.deref().borrow_mut().1.clear();
+        if a.file.is_none() && a.string == self.inp.text() {
+            self.paint(comparison.highlights());
+        }
+        self.buff.set_text(&comparison.to_string());
+    }
+
+    /// Times the last operation on the current input and shows the
+    /// statistics in place of the result.
+    pub fn bench(&mut self) {
+        let eval = self.evaluation(self.last_op.get());
+        let runs = self.runs.value().parse().unwrap_or(DEFAULT_RUNS);
+        match Benchmark::run(&eval, runs) {
+            Ok(b) => {
+                self.out.set_text_color(Color::Black);
+                *self.last.deref().borrow_mut() = None;
+                self.highlights.deref().borrow_mut().1.clear();
+                self.buff.set_text(&b.to_string());
+            }
+            Err(e) => self.show(&eval, &Outcome::PatternError(e)),
+        }
+    }
+
+    /// Copies the result shown, as JSON, to the clipboard.
+    pub fn copy_json(&mut self) {
+        let json = match &*self.last.deref().borrow() {
+            Some((eval, outcome)) => Format::Json.render(eval, outcome),
+            None => return,
+        };
+        self.copy(&json);
+    }
+
+    /// Puts `text` on the clipboard, which fltk 1.1 only fills from a
+    /// widget's selection.
+    fn copy(&mut self, text: &str) {
+        self.clip.set_value(text);
+        self.clip.set_position(0).ok();
+        self.clip.set_mark(self.clip.value().len() as i32).ok();
+        self.clip.copy().ok();
     }
 }
 
 fn main() {
-    let start_cpu = cpu_time::ProcessTime::now();
     let app = app::App::default();
     let widget_theme = WidgetTheme::new(ThemeType::Metro);
     widget_theme.apply();
@@ -343,7 +722,7 @@ fn main() {
     let font = Font::by_name("Courier");
 
     let mut wind = Window::default()
-        .with_size(600, 400)
+        .with_size(600, 675)
         .center_screen()
         .with_label("Regular Expression Tester");
 
@@ -353,20 +732,20 @@ fn main() {
         Ok(i) => Some(i),
     };
     wind.set_icon(im);
-    wind.size_range(600, 400, 0, 0);
+    wind.size_range(600, 675, 0, 0);
 
-    let mut main_group = Pack::new(0, 0, 600, 400, "");
+    let mut main_group = Pack::new(0, 0, 600, 675, "");
 
     let f1 = Frame::default().with_size(0, 5);
 
-    let mut pattern_pack = Pack::default().with_size(600, 25);
+    let mut pattern_pack = Pack::default().with_size(600, 50);
 
     let mut pat_lab = Frame::default()
-        .with_size(60, 25)
+        .with_size(60, 50)
         .with_label("Pattern: ")
-        .with_align(Align::Inside | Align::Left);
+        .with_align(Align::Inside | Align::Left | Align::Top);
     //pat_lab.set_label_type(fltk::enums::LabelType::Engraved);
-    let mut pat = Input::new(0, 0, 500, 25, "").left_of(&pat_lab, 4);
+    let mut pat = MultilineInput::new(0, 0, 500, 50, "").left_of(&pat_lab, 4);
     pat.set_text_font(font.clone());
 
     pattern_pack.resizable(&pat);
@@ -375,14 +754,35 @@ fn main() {
 
     let f1 = Frame::default().with_size(0, 5);
 
-    let mut string_pack = Pack::default().with_size(600, 25).with_pos(0, 25);
+    let mut string_pack = Pack::default().with_size(600, 80).with_pos(0, 25);
 
     let mut str_lab = Frame::default()
-        .with_size(60, 25)
+        .with_size(60, 80)
         .with_label("String: ")
-        .with_align(Align::Inside | Align::Left);
-    let mut str = Input::new(0, 0, 500, 25, "").left_of(&str_lab, 4);
+        .with_align(Align::Inside | Align::Left | Align::Top);
+    let mut str_buff = text::TextBuffer::default();
+    let mut str = TextEditor::new(0, 0, 500, 80, "").left_of(&str_lab, 4);
+    str.set_buffer(str_buff.clone());
+    str.set_scrollbar_size(12);
     str.set_text_font(font.clone());
+    let style_buff = text::TextBuffer::default();
+    let mut styles = vec![StyleTableEntry { color: Color::Black, font: font.clone(), size: 14 }];
+    styles.extend(
+        GROUP_COLORS
+            .iter()
+            .enumerate()
+            .map(|(i, c)| StyleTableEntry {
+                color: *c,
+                font: if i == 0 {
+                    Font::CourierBold
+                } else {
+                    font.clone()
+                },
+                size: 14,
+            }),
+    );
+    str.set_highlight_data(style_buff.clone(), styles);
+    str.set_tooltip("click a highlighted match to find it in the results");
 
     string_pack.resizable(&str);
     string_pack.end();
@@ -390,6 +790,141 @@ fn main() {
 
     let f1 = Frame::default().with_size(0, 5);
 
+    let mut file_pack = Pack::default().with_size(600, 25).with_pos(0, 25);
+
+    let mut file_lab = Frame::default()
+        .with_size(60, 25)
+        .with_label("File: ")
+        .with_align(Align::Inside | Align::Left);
+    let file_row = FileRow::new();
+
+    file_pack.resizable(&file_row.name);
+    file_pack.end();
+    file_pack.set_type(PackType::Horizontal);
+    file_pack.set_spacing(4);
+
+    let f1 = Frame::default().with_size(0, 5);
+
+    let mut suite_pack = Pack::default().with_size(600, 25).with_pos(0, 25);
+
+    let mut suite_lab = Frame::default()
+        .with_size(60, 25)
+        .with_label("Suite: ")
+        .with_align(Align::Inside | Align::Left);
+    let suite_row = SuiteRow::new();
+
+    suite_pack.end();
+    suite_pack.set_type(PackType::Horizontal);
+    suite_pack.set_spacing(4);
+
+    let f1 = Frame::default().with_size(0, 5);
+
+    let mut code_pack = Pack::default().with_size(600, 25).with_pos(0, 25);
+
+    let mut code_lab = Frame::default()
+        .with_size(60, 25)
+        .with_label("Code: ")
+        .with_align(Align::Inside | Align::Left);
+    let code_buts: Vec<(Button, Language)> = Language::ALL
+        .iter()
+        .map(|l| {
+            let mut b = Button::default().with_size(80, 25).with_label(l.title());
+            b.set_tooltip("copy a program doing what the last operation did");
+            (b, *l)
+        })
+        .collect();
+
+    code_pack.end();
+    code_pack.set_type(PackType::Horizontal);
+    code_pack.set_spacing(4);
+
+    let f1 = Frame::default().with_size(0, 5);
+
+    let mut bytes_pack = Pack::default().with_size(600, 25).with_pos(0, 25);
+
+    let mut bytes_lab = Frame::default()
+        .with_size(60, 25)
+        .with_label("Bytes: ")
+        .with_align(Align::Inside | Align::Left);
+    let bytes_buts: Vec<(Button, Encoding)> = Encoding::ALL
+        .iter()
+        .map(|e| {
+            let mut b = Button::default().with_size(80, 25).with_label(e.title());
+            b.set_tooltip("run the last operation on the bytes the string spells out");
+            (b, *e)
+        })
+        .collect();
+    let mut bytes_file_but = Button::default().with_size(80, 25).with_label("File...");
+    bytes_file_but.set_tooltip("run the last operation on the bytes of a binary file");
+
+    bytes_pack.end();
+    bytes_pack.set_type(PackType::Horizontal);
+    bytes_pack.set_spacing(4);
+
+    let f1 = Frame::default().with_size(0, 5);
+
+    let mut replace_pack = Pack::default().with_size(600, 25).with_pos(0, 25);
+
+    let mut rep_lab = Frame::default()
+        .with_size(60, 25)
+        .with_label("Replace: ")
+        .with_align(Align::Inside | Align::Left);
+    let mut rep = Input::new(0, 0, 500, 25, "").left_of(&rep_lab, 4);
+    rep.set_text_font(font.clone());
+
+    replace_pack.resizable(&rep);
+    replace_pack.end();
+    replace_pack.set_type(PackType::Horizontal);
+
+    let f1 = Frame::default().with_size(0, 5);
+
+    let mut compare_pack = Pack::default().with_size(600, 25).with_pos(0, 25);
+
+    let mut pat_b_lab = Frame::default()
+        .with_size(60, 25)
+        .with_label("Pattern B: ")
+        .with_align(Align::Inside | Align::Left);
+    let mut pat_b = Input::new(0, 0, 420, 25, "").left_of(&pat_b_lab, 4);
+    pat_b.set_text_font(font.clone());
+    pat_b.set_tooltip("a pattern to compare with the one above");
+    let mut compare_but = Button::default().with_size(80, 25).with_label("Com&pare");
+    compare_but.set_tooltip("mark the lines, or cases, the two patterns disagree on");
+
+    compare_pack.resizable(&pat_b);
+    compare_pack.end();
+    compare_pack.set_type(PackType::Horizontal);
+    compare_pack.set_spacing(4);
+
+    let f1 = Frame::default().with_size(0, 5);
+
+    let mut flags_pack = Pack::default().with_size(600, 25).with_pos(0, 25);
+
+    let mut flags_lab = Frame::default()
+        .with_size(60, 25)
+        .with_label("Flags: ")
+        .with_align(Align::Inside | Align::Left);
+    let mut flags = FlagBoxes::new();
+
+    flags_pack.end();
+    flags_pack.set_type(PackType::Horizontal);
+    flags_pack.set_spacing(4);
+
+    let f1 = Frame::default().with_size(0, 5);
+
+    let mut limits_pack = Pack::default().with_size(600, 25).with_pos(0, 25);
+
+    let mut limits_lab = Frame::default()
+        .with_size(60, 25)
+        .with_label("Limits: ")
+        .with_align(Align::Inside | Align::Left);
+    let mut limits = LimitInputs::new();
+
+    limits_pack.end();
+    limits_pack.set_type(PackType::Horizontal);
+    limits_pack.set_spacing(4);
+
+    let f1 = Frame::default().with_size(0, 5);
+
     let mut button_pack = Pack::default()
         .with_size(600, 25)
         .with_pos(0, 25)
@@ -405,26 +940,62 @@ fn main() {
     matches_but.set_visible_focus();
 
     let mut find_but = Button::default().with_size(60, 25).with_label("&Find");
+    let mut captures_but = Button::default().with_size(60, 25).with_label("&Captures");
     let mut split_but = Button::default().with_size(60, 25).with_label("&Split");
-    let mut hist_but = Button::default().with_size(60, 25).with_label("&History");
-    let mut cpu_frame = Frame::default().with_size(90, 25).with_label("");
+    let mut replace_but = Button::default().with_size(60, 25).with_label("&Replace");
+    let mut lines_but = Button::default().with_size(60, 25).with_label("Li&nes");
+    lines_but.set_tooltip("match each line on its own, like grep");
+    let mut explain_but = Button::default().with_size(60, 25).with_label("E&xplain");
+    explain_but.set_tooltip("describe the pattern in plain English");
 
     button_pack.end();
     button_pack.set_type(PackType::Horizontal);
-    button_pack.set_spacing(20);
+    button_pack.set_spacing(10);
     button_pack.set_align(Align::Center);
 
     let f1 = Frame::default().with_size(0, 5);
 
+    // the rest of the buttons do not fit on one row with the operations
+    let mut tool_pack = Pack::default()
+        .with_size(600, 25)
+        .with_pos(0, 25)
+        .with_align(Align::Center);
+
+    let f1 = Frame::default().with_size(5, 0);
+
+    let mut inspect_but = Button::default().with_size(60, 25).with_label("&Inspect");
+    inspect_but.set_tooltip("show the HIR, anchoring and literal prefixes");
+    let mut set_but = Button::default().with_size(60, 25).with_label("S&et");
+    set_but.set_tooltip("try each line of the pattern on each line, as a RegexSet does");
+    let mut hist_but = Button::default().with_size(60, 25).with_label("&History");
+    let mut json_but = Button::default().with_size(60, 25).with_label("&JSON");
+    json_but.set_tooltip("copy the result as JSON");
+    let mut live_but = CheckButton::default().with_size(60, 25).with_label("&Live");
+    live_but.set_tooltip("re-run the last operation as you type");
+    let mut bench_but = Button::default().with_size(60, 25).with_label("&Bench");
+    bench_but.set_tooltip("time the last operation over this many runs");
+    let mut runs_inp = IntInput::default().with_size(50, 25);
+    runs_inp.set_value(&DEFAULT_RUNS.to_string());
+
+    tool_pack.end();
+    tool_pack.set_type(PackType::Horizontal);
+    tool_pack.set_spacing(10);
+    tool_pack.set_align(Align::Center);
+
+    let f1 = Frame::default().with_size(0, 5);
+
     let mut buff = text::TextBuffer::default();
     buff.set_tab_distance(4);
 
-    let mut op = TextEditor::default().with_size(600, 300);
+    let mut op = TextEditor::default().with_size(600, 215);
     op.set_buffer(buff.clone());
     op.set_scrollbar_size(16);
     op.set_text_font(font);
     op.set_align(Align::Inside | Align::Left | Align::Top);
 
+    let mut clip = MultilineInput::default();
+    clip.hide();
+
     main_group.end();
     main_group.set_type(PackType::Vertical);
     main_group.resizable(&op);
@@ -435,40 +1006,138 @@ fn main() {
     wind.end();
     wind.show();
 
-    let hist = match History::load_from() {
-        Err(e) => {
-            dialog::alert(
-                200,
-                200,
-                &format!("Could not load prior state/history: \n\t{}", e),
-            );
-            History::new()
-        }
-        Ok(h) => h,
-    };
+    let hist = History::new();
+    if let Err(e) = hist.load_from() {
+        dialog::alert(
+            200,
+            200,
+            &format!("Could not load prior state/history: \n\t{}", e),
+        );
+    }
 
     if let Some(last) = hist.last() {
-        str.set_value(&last.string);
+        str_buff.set_text(&last.string);
         pat.set_value(&last.pattern);
+        rep.set_value(&last.replacement);
+        flags.set(&last.flags);
+        limits.set(&last.limits);
     }
 
-    let mut r_ = ReTest::new(&op, &buff, &str, &pat, &cpu_frame, &start_cpu, hist);
+    let (live_s, live_r) = app::channel::<(Evaluation, Outcome)>();
+    let mut r_ = ReTest::new(
+        &op,
+        &buff,
+        &str_buff,
+        &style_buff,
+        &pat,
+        &rep,
+        &pat_b,
+        &flags,
+        &limits,
+        &runs_inp,
+        hist,
+        &live_but,
+        &file_row,
+        &suite_row,
+        &clip,
+        live_s,
+    );
+    r_.clear_highlights();
 
     let mut r = r_.clone();
     matches_but.set_callback(move |b| r.matches());
     let mut r = r_.clone();
     find_but.set_callback(move |b| r.find());
     let mut r = r_.clone();
+    captures_but.set_callback(move |b| r.find_captures());
+    let mut r = r_.clone();
     split_but.set_callback(move |b| r.split());
     let mut r = r_.clone();
+    replace_but.set_callback(move |b| r.replace());
+    let mut r = r_.clone();
+    lines_but.set_callback(move |b| r.lines());
+    let mut r = r_.clone();
+    explain_but.set_callback(move |b| r.explain());
+    let mut r = r_.clone();
+    inspect_but.set_callback(move |b| r.inspect());
+    let mut r = r_.clone();
+    set_but.set_callback(move |b| r.set());
+    let mut r = r_.clone();
     hist_but.set_callback(move |b| r.history());
-
-    if DO_CPU_TIME {
-        wind.handle(move |x, y| {
-            r_.update_cpu();
-            false
+    let mut r = r_.clone();
+    bench_but.set_callback(move |b| r.bench());
+    let mut r = r_.clone();
+    compare_but.set_callback(move |b| r.compare());
+    let mut r = r_.clone();
+    json_but.set_callback(move |b| r.copy_json());
+    let mut r = r_.clone();
+    file_row.open.clone().set_callback(move |b| r.open_file());
+    let mut r = r_.clone();
+    file_row.prev.clone().set_callback(move |b| r.prev_page());
+    let mut r = r_.clone();
+    file_row.next.clone().set_callback(move |b| r.next_page());
+    let mut r = r_.clone();
+    suite_row.load.clone().set_callback(move |b| r.load_suite());
+    let mut r = r_.clone();
+    suite_row.save.clone().set_callback(move |b| r.save_suite());
+    let mut r = r_.clone();
+    suite_row.add.clone().set_callback(move |b| r.add_case());
+    let mut r = r_.clone();
+    suite_row.run.clone().set_callback(move |b| r.run_suite());
+    let mut r = r_.clone();
+    suite_row
+        .rust
+        .clone()
+        .set_callback(move |b| r.copy_rust_tests());
+    for (mut b, language) in code_buts {
+        let mut r = r_.clone();
+        b.set_callback(move |b| r.code(language));
+    }
+    for (mut b, encoding) in bytes_buts {
+        let mut r = r_.clone();
+        b.set_callback(move |b| r.decode_bytes(encoding));
+    }
+    let mut r = r_.clone();
+    bytes_file_but.set_callback(move |b| r.open_bytes());
+    for mut b in flags.buttons() {
+        let mut r = r_.clone();
+        b.set_callback(move |b| {
+            r.flags.show_inline();
+            r.live_update();
         });
     }
+    let mut r = r_.clone();
+    str_buff.add_modify_callback(move |_, inserted, deleted, _, _| {
+        // selection changes land here too, and must not wipe the colours
+        if inserted > 0 || deleted > 0 {
+            r.clear_highlights();
+            r.live_update();
+        }
+    });
+    let mut r = r_.clone();
+    str.handle(move |ed, ev| {
+        if ev == Event::Released {
+            r.jump_to_result(ed.insert_position() as usize);
+        }
+        false
+    });
+    let mut r = r_.clone();
+    pat.set_trigger(CallbackTrigger::Changed);
+    pat.set_callback(move |i| r.live_update());
+    let mut r = r_.clone();
+    rep.set_trigger(CallbackTrigger::Changed);
+    rep.set_callback(move |i| r.live_update());
+    for mut i in limits.inputs() {
+        let mut r = r_.clone();
+        i.set_trigger(CallbackTrigger::Changed);
+        i.set_callback(move |i| r.live_update());
+    }
+    let mut r = r_.clone();
+    live_but.set_callback(move |b| r.live_update());
 
-    app.run().unwrap();
+    while app.wait() {
+        if let Some((eval, outcome)) = live_r.recv() {
+            r_.show(&eval, &outcome);
+        }
+    }
 }
@@ -1,18 +1,40 @@
-use std::{cell::RefCell, fmt::Display, fs::{File, create_dir_all}, io::{BufReader, BufWriter}, rc::Rc,ops::Deref};
+use std::{
+    cell::RefCell,
+    fmt::Display,
+    fs::{create_dir_all, File},
+    io::{BufReader, BufWriter},
+    ops::Deref,
+    path::PathBuf,
+    rc::Rc,
+    sync::Arc,
+};
 
 use anyhow::Context;
-use nwg::{MessageWindow, simple_message};
+use chrono::Local;
 use serde::{Deserialize, Serialize};
-use chrono::{DateTime, Local, Utc};
 
+use crate::{
+    eval::Evaluation,
+    options::{Flags, Limits},
+    testfile::TestFile,
+};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Execution {
-    time: chrono::DateTime<Local>,
-    operation: String,
-    pattern: String,
-    string: String,
-    count: u32,
+    pub time: chrono::DateTime<Local>,
+    pub operation: String,
+    pub pattern: String,
+    pub string: String,
+    /// The test file searched instead of `string`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub file: Option<PathBuf>,
+    #[serde(default)]
+    pub replacement: String,
+    #[serde(default)]
+    pub flags: Flags,
+    #[serde(default)]
+    pub limits: Limits,
+    pub count: u32,
 }
 
 impl Execution {
@@ -22,25 +44,66 @@ impl Execution {
             operation: o.into(),
             pattern: p.into(),
             string: s.into(),
+            file: None,
+            replacement: String::new(),
+            flags: Flags::default(),
+            limits: Limits::default(),
             count: 0,
         }
     }
 }
 
+impl Execution {
+    /// The evaluation to run this again, reloading the test file if there
+    /// was one.
+    pub fn evaluation(&self) -> Result<Evaluation, Box<dyn std::error::Error>> {
+        let eval = Evaluation::new(self.operation.parse()?, &self.pattern, &self.string)
+            .with_replacement(&self.replacement)
+            .with_flags(self.flags)
+            .with_limits(self.limits);
+        Ok(match &self.file {
+            Some(p) => eval.with_file(Arc::new(TestFile::open(p)?)),
+            None => eval,
+        })
+    }
+}
+
+impl From<&Evaluation> for Execution {
+    fn from(eval: &Evaluation) -> Self {
+        let mut exe = Execution::new(eval.operation.name(), &eval.pattern, &eval.string);
+        exe.file = eval.file.as_ref().map(|f| f.path().to_path_buf());
+        exe.replacement = eval.replacement.clone();
+        exe.flags = eval.flags;
+        exe.limits = eval.limits;
+        exe
+    }
+}
+
 impl Display for Execution {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(
             f,
-            "{}: {} Op: \"{}\" RE: \"{}\" str: \"{}\"",
+            "{}: {} Op: \"{}\" RE: \"{}{}\" ",
             self.count,
             self.time.to_rfc3339(),
             self.operation,
-            self.pattern,
-            self.string
-        )
+            self.flags.inline(),
+            one_line(&self.pattern),
+        )?;
+        match &self.file {
+            Some(p) => write!(f, "file: \"{}\"", p.display()),
+            None => write!(f, "str: \"{}\"", one_line(&self.string)),
+        }
     }
 }
 
+/// Escapes line breaks so each execution lists on a single line.
+fn one_line(s: &str) -> String {
+    s.replace('\r', "\\r").replace('\n', "\\n")
+}
+
+/// Executions in the order they were last run, persisted to
+/// `~/.re_test/state.json` so the frontends share one history.
 #[derive(Debug, Clone)]
 pub struct History {
     hist: Rc<RefCell<Vec<Execution>>>,
@@ -52,87 +115,185 @@ impl Default for History {
     }
 }
 
+fn state_path() -> Result<PathBuf, Box<dyn std::error::Error>> {
+    let mut path = dirs::home_dir().context("cannot get home directory for prior state")?;
+    path.push(".re_test");
+    create_dir_all(&path)
+        .with_context(|| format!("Unable to create directory {}", &path.to_string_lossy()))?;
+    path.push("state.json");
+    Ok(path)
+}
 
 impl History {
     pub fn new() -> Self {
-        println!("creating history");
-        History {
-            hist: Rc::new(RefCell::new(Vec::new())),
-        }
+        History { hist: Rc::new(RefCell::new(Vec::new())) }
     }
 
     pub fn load_from(&self) -> Result<(), Box<dyn std::error::Error>> {
-        let mut path = dirs::home_dir().context("cannot get home directory to load prior state")?;
-        path.push(".re_test");
-        create_dir_all(&path)
-            .with_context(|| format!("Unable to create directory {}", &path.to_string_lossy()))?;
-        path.push("state.json");
-        let mut f = File::open(&path)?;
-        let mut rb = BufReader::new(&f);
+        let f = File::open(state_path()?)?;
+        let rb = BufReader::new(&f);
         let res: Vec<Execution> = serde_json::from_reader(rb)?;
 
-        for x in res.into_iter() {
-            self.hist.borrow_mut().push(x);
-        }
-        println!("loading history");
+        self.hist.borrow_mut().extend(res);
         Ok(())
     }
 
     pub fn save(&self) -> Result<(), Box<dyn std::error::Error>> {
-        let mut path = dirs::home_dir().context("cannot get home directory to load prior stuff")?;
-
-        // let v: Value = serde_json::from_str(data)?;
-        path.push(".re_test");
-        create_dir_all(&path)
-            .with_context(|| format!("Unable to create directory {}", &path.to_string_lossy()))?;
-        path.push("state.json");
-        let mut f = File::create(&path)?; // .with_context(||"cannot open state file: {}", &path)?;
+        let f = File::create(state_path()?)?;
         let bw = BufWriter::new(&f);
         let h = self.hist.deref().borrow();
-        serde_json::to_writer_pretty(bw, &*h);
+        serde_json::to_writer_pretty(bw, &*h)?;
         Ok(())
     }
 
-    pub fn add(&self, mut exe: Execution) {
-        let mut found = usize::max_value();
-        for (i, e) in self.hist.deref().borrow().iter().enumerate().rev() {
-            if e.pattern == exe.pattern && e.string == exe.string {
-                found = i;
-                break;
-            }
-        }
-        exe.count = if found != usize::max_value() {
-            (self.hist.deref().borrow_mut().remove(found).count + 1u32)
-        } else {
-            1u32
+    /// Records `exe`, replacing an earlier run of the same operation, pattern,
+    /// replacement, flags and limits against the same string or file and
+    /// bumping its count, then saves the history.
+    pub fn add(&self, exe: Execution) -> Result<(), Box<dyn std::error::Error>> {
+        self.record(exe);
+        self.save()
+    }
+
+    /// [`History::add`] without the saving.
+    fn record(&self, mut exe: Execution) {
+        let found = self.hist.borrow().iter().rposition(|e| {
+            e.operation == exe.operation
+                && e.pattern == exe.pattern
+                && e.string == exe.string
+                && e.file == exe.file
+                && e.replacement == exe.replacement
+                && e.flags == exe.flags
+                && e.limits == exe.limits
+        });
+        exe.count = match found {
+            Some(i) => self.hist.borrow_mut().remove(i).count + 1,
+            None => 1,
         };
-        let mut h = self.hist.deref().borrow_mut().push(exe);
-        match self.save() {
-            Err(e) => { simple_message("Error", &format!("Unable to save history: {}", e)); }
-            _ => {}
-        }
+        self.hist.borrow_mut().push(exe);
     }
 
-    pub fn to_str(&self) -> String {
+    pub fn to_str(&self, eol: &str) -> String {
         let mut s = String::with_capacity(1024);
         let h = self.hist.deref().borrow();
-        if h.len() <= 0 {
+        if h.is_empty() {
             s.push_str("No history as yet");
         } else {
-            println!("hist size: {}", h.len());
             for (i, o) in h.iter().enumerate().rev() {
-                s.push_str(&format!("{}: {}\r\n", i, o));
+                s.push_str(&format!("{}: {}{}", i, o, eol));
             }
         }
         s
     }
 
     pub fn last(&self) -> Option<Execution> {
-        let h = self.hist.deref().borrow();
-        if h.len() <= 0 {
-            None
-        } else {
-            Some(h.last().unwrap().clone())
+        self.hist.borrow().last().cloned()
+    }
+
+    pub fn len(&self) -> usize {
+        self.hist.borrow().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.hist.borrow().is_empty()
+    }
+
+    /// The execution listed as `i` by [`History::to_str`].
+    pub fn get(&self, i: usize) -> Option<Execution> {
+        self.hist.borrow().get(i).cloned()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn counts(h: &History) -> Vec<(String, u32)> {
+        h.hist
+            .borrow()
+            .iter()
+            .map(|e| (e.pattern.clone(), e.count))
+            .collect()
+    }
+
+    #[test]
+    fn the_same_run_again_moves_to_the_end_and_counts_up() {
+        let h = History::new();
+        h.record(Execution::new("find", "a", "abc"));
+        h.record(Execution::new("find", "b", "abc"));
+        h.record(Execution::new("find", "a", "abc"));
+        assert_eq!(counts(&h), vec![("b".to_string(), 1), ("a".to_string(), 2)]);
+        assert_eq!(h.last().unwrap().pattern, "a");
+    }
+
+    #[test]
+    fn runs_differing_in_anything_but_time_are_kept_apart() {
+        let base = Execution::new("find", "a", "abc");
+        let mut changed = vec![];
+        let mut e = base.clone();
+        e.operation = "split".into();
+        changed.push(e);
+        let mut e = base.clone();
+        e.string = "abd".into();
+        changed.push(e);
+        let mut e = base.clone();
+        e.file = Some(PathBuf::from("abc.txt"));
+        changed.push(e);
+        let mut e = base.clone();
+        e.replacement = "x".into();
+        changed.push(e);
+        let mut e = base.clone();
+        e.flags.case_insensitive = true;
+        changed.push(e);
+        let mut e = base.clone();
+        e.limits.nest_limit = 10;
+        changed.push(e);
+
+        let h = History::new();
+        h.record(base);
+        for e in changed {
+            h.record(e);
         }
+        assert_eq!(h.hist.borrow().len(), 7);
+        assert!(h.hist.borrow().iter().all(|e| e.count == 1));
+    }
+
+    #[test]
+    fn entries_are_listed_newest_first() {
+        let h = History::new();
+        assert_eq!(h.to_str("\n"), "No history as yet");
+        h.record(Execution::new("find", "a", "x\ny"));
+        h.record(Execution::new("match", "b", "z"));
+        let listed = h.to_str("\n");
+        let lines: Vec<&str> = listed.lines().collect();
+        assert!(lines[0].starts_with("1: 1: "), "{}", listed);
+        assert!(
+            lines[0].ends_with(r#"Op: "match" RE: "b" str: "z""#),
+            "{}",
+            listed
+        );
+        assert!(lines[1].ends_with(r#"str: "x\ny""#), "{}", listed);
+        assert_eq!(h.get(0).unwrap().pattern, "a");
+    }
+
+    #[test]
+    fn flags_show_inline_with_the_pattern() {
+        let mut e = Execution::new("find", "a", "x");
+        e.flags.multi_line = true;
+        assert!(e.to_string().contains(r#"RE: "(?m)a""#), "{}", e);
+    }
+
+    #[test]
+    fn an_execution_runs_again_as_it_was() {
+        let mut e = Execution::new("replace", "a", "abc");
+        e.replacement = "x".into();
+        e.flags.case_insensitive = true;
+        let eval = e.evaluation().unwrap();
+        assert_eq!(eval.effective_pattern(), "(?i)a");
+        let back = Execution::from(&eval);
+        assert_eq!(
+            (back.operation, back.replacement, back.flags),
+            (e.operation, e.replacement, e.flags)
+        );
+        assert!(Execution::new("nope", "a", "").evaluation().is_err());
     }
 }
@@ -0,0 +1,293 @@
+use regex_automata::{dfa::dense, nfa::thompson::NFA};
+use regex_syntax::hir::{
+    self, literal::Literals, Anchor, Class, GroupKind, Hir, HirKind, RepetitionKind,
+    RepetitionRange, WordBoundary,
+};
+
+use crate::{
+    options::{Flags, Limits},
+    result::{Automata, Extracted, Inspection, PatternError},
+};
+
+/// Classes with more ranges than this are summed up rather than listed in the
+/// tree.
+const CLASS_RANGES_SHOWN: usize = 8;
+
+/// What `regex` makes of `pattern` before compiling it: the HIR it is
+/// translated to, how it is anchored, and the literals searches start by
+/// looking for.  The literals are extracted as `regex` 1.5 does it, so a
+/// pattern with no prefixes here is run without a prefilter.  Alongside are
+/// the sizes of the automata `regex-automata` builds.
+pub fn inspect(pattern: &str, flags: &Flags, limits: &Limits) -> Result<Inspection, PatternError> {
+    let mut parser = flags.parser();
+    parser.nest_limit(limits.nest_limit);
+    let hir = parser.build().parse(pattern).map_err(|e| {
+        let err = regex::Error::Syntax(e.to_string());
+        PatternError::new(pattern, flags, limits, &err)
+    })?;
+
+    // a pattern anchored in some branches only gets no prefilter at all
+    let prefixes = if !hir.is_anchored_start() && hir.is_any_anchored_start() {
+        vec![]
+    } else {
+        extracted(&hir, Literals::union_prefixes)
+    };
+    let suffixes = if !hir.is_anchored_end() && hir.is_any_anchored_end() {
+        vec![]
+    } else {
+        extracted(&hir, Literals::union_suffixes)
+    };
+    let mut tree = vec![];
+    walk(&hir, 0, &mut tree);
+    Ok(Inspection {
+        hir: hir.to_string(),
+        tree,
+        anchored_start: hir.is_anchored_start(),
+        anchored_end: hir.is_anchored_end(),
+        line_anchored_start: hir.is_line_anchored_start(),
+        line_anchored_end: hir.is_line_anchored_end(),
+        matches_empty: hir.is_match_empty(),
+        literal: hir.is_literal(),
+        alternation_literal: hir.is_alternation_literal(),
+        prefixes,
+        suffixes,
+        automata: automata(pattern, flags, limits),
+    })
+}
+
+/// The NFA and DFA `regex-automata` builds for `pattern`.  The size limit
+/// bounds the NFA and, as nothing bounds a full DFA otherwise, the DFA size
+/// limit bounds the DFA.
+fn automata(pattern: &str, flags: &Flags, limits: &Limits) -> Result<Automata, String> {
+    let nfa = NFA::compiler()
+        .syntax(flags.syntax().nest_limit(limits.nest_limit))
+        .configure(NFA::config().nfa_size_limit(Some(limits.size_limit)))
+        .build(pattern)
+        .map_err(|e| e.to_string())?;
+    let dfa_memory = dense::Builder::new()
+        .configure(
+            dense::DFA::config()
+                .dfa_size_limit(Some(limits.dfa_size_limit))
+                .determinize_size_limit(Some(limits.dfa_size_limit)),
+        )
+        .build_from_nfa(&nfa)
+        .map(|dfa| dfa.memory_usage())
+        .map_err(|e| e.to_string());
+    Ok(Automata {
+        nfa_states: nfa.states().len(),
+        nfa_memory: nfa.memory_usage(),
+        nfa_anchored: nfa.is_always_start_anchored(),
+        dfa_memory,
+    })
+}
+
+/// The literals `union` finds in `hir`, or none when there are too many to
+/// be worth searching for.
+fn extracted(hir: &Hir, union: fn(&mut Literals, &Hir) -> bool) -> Vec<Extracted> {
+    let mut lits = Literals::empty();
+    if !union(&mut lits, hir) {
+        return vec![];
+    }
+    lits.literals()
+        .iter()
+        .map(|l| Extracted { text: escaped(l), exact: !l.is_cut() })
+        .collect()
+}
+
+/// `bytes` as text, with any that are not UTF-8 written as `\xFF`.
+fn escaped(bytes: &[u8]) -> String {
+    match std::str::from_utf8(bytes) {
+        Ok(s) => s.to_string(),
+        Err(_) => bytes
+            .iter()
+            .flat_map(|b| std::ascii::escape_default(*b))
+            .map(char::from)
+            .collect(),
+    }
+}
+
+fn walk(h: &Hir, depth: usize, tree: &mut Vec<String>) {
+    let indent = "  ".repeat(depth);
+    let line = match h.kind() {
+        HirKind::Empty => "empty".to_string(),
+        HirKind::Literal(hir::Literal::Unicode(c)) => format!("literal {:?}", c),
+        HirKind::Literal(hir::Literal::Byte(b)) => format!("byte {:#04x}", b),
+        HirKind::Class(c) => {
+            let (kind, ranges) = match c {
+                Class::Unicode(u) => ("class", u.ranges().len()),
+                Class::Bytes(b) => ("byte class", b.ranges().len()),
+            };
+            if ranges <= CLASS_RANGES_SHOWN {
+                format!("{} {}", kind, h)
+            } else {
+                format!("{} of {} ranges", kind, ranges)
+            }
+        }
+        HirKind::Anchor(a) => match a {
+            Anchor::StartLine => "start of line",
+            Anchor::EndLine => "end of line",
+            Anchor::StartText => "start of text",
+            Anchor::EndText => "end of text",
+        }
+        .to_string(),
+        HirKind::WordBoundary(w) => match w {
+            WordBoundary::Unicode => "word boundary",
+            WordBoundary::UnicodeNegate => "not a word boundary",
+            WordBoundary::Ascii => "ASCII word boundary",
+            WordBoundary::AsciiNegate => "not an ASCII word boundary",
+        }
+        .to_string(),
+        HirKind::Repetition(r) => {
+            let op = match &r.kind {
+                RepetitionKind::ZeroOrOne => "?".to_string(),
+                RepetitionKind::ZeroOrMore => "*".to_string(),
+                RepetitionKind::OneOrMore => "+".to_string(),
+                RepetitionKind::Range(RepetitionRange::Exactly(n)) => format!("{{{}}}", n),
+                RepetitionKind::Range(RepetitionRange::AtLeast(n)) => format!("{{{},}}", n),
+                RepetitionKind::Range(RepetitionRange::Bounded(m, n)) => {
+                    format!("{{{},{}}}", m, n)
+                }
+            };
+            let greed = if r.greedy { "greedy" } else { "lazy" };
+            tree.push(format!("{}repeat {} {}", indent, op, greed));
+            return walk(&r.hir, depth + 1, tree);
+        }
+        HirKind::Group(g) => {
+            let line = match &g.kind {
+                GroupKind::CaptureIndex(i) => format!("capture {}", i),
+                GroupKind::CaptureName { name, index } => format!("capture {} {:?}", index, name),
+                GroupKind::NonCapturing => "group".to_string(),
+            };
+            tree.push(format!("{}{}", indent, line));
+            return walk(&g.hir, depth + 1, tree);
+        }
+        HirKind::Concat(hirs) => {
+            if h.is_literal() {
+                let bytes: Vec<u8> = hirs
+                    .iter()
+                    .flat_map(|h| match h.kind() {
+                        HirKind::Literal(hir::Literal::Unicode(c)) => c.to_string().into_bytes(),
+                        HirKind::Literal(hir::Literal::Byte(b)) => vec![*b],
+                        _ => unreachable!("literals hold only literals"),
+                    })
+                    .collect();
+                tree.push(format!("{}literal {:?}", indent, escaped(&bytes)));
+                return;
+            }
+            tree.push(format!("{}concat", indent));
+            let mut i = 0;
+            while i < hirs.len() {
+                // runs of characters read better as one literal
+                let text: String = hirs[i..]
+                    .iter()
+                    .map_while(|h| match h.kind() {
+                        HirKind::Literal(hir::Literal::Unicode(c)) => Some(*c),
+                        _ => None,
+                    })
+                    .collect();
+                match text.chars().count() {
+                    0 | 1 => {
+                        walk(&hirs[i], depth + 1, tree);
+                        i += 1;
+                    }
+                    n => {
+                        tree.push(format!("{}  literal {:?}", indent, text));
+                        i += n;
+                    }
+                }
+            }
+            return;
+        }
+        HirKind::Alternation(hirs) => {
+            tree.push(format!("{}alternation", indent));
+            for h in hirs.iter() {
+                walk(h, depth + 1, tree);
+            }
+            return;
+        }
+    };
+    tree.push(format!("{}{}", indent, line));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn inspected(pattern: &str) -> Inspection {
+        inspect(pattern, &Flags::default(), &Limits::default()).unwrap()
+    }
+
+    fn texts(lits: &[Extracted]) -> Vec<(&str, bool)> {
+        lits.iter().map(|l| (l.text.as_str(), l.exact)).collect()
+    }
+
+    #[test]
+    fn anchoring_is_to_the_text_or_to_lines() {
+        let i = inspected("^abc$");
+        assert!(i.anchored_start && i.anchored_end);
+        assert!(i.line_anchored_start && i.line_anchored_end);
+        let i = inspected("(?m)^abc");
+        assert!(!i.anchored_start && i.line_anchored_start);
+        assert!(!i.anchored_end && !i.line_anchored_end);
+    }
+
+    #[test]
+    fn prefixes_and_suffixes_are_extracted() {
+        let i = inspected("foo(bar|baz)");
+        assert_eq!(texts(&i.prefixes), vec![("foobar", true), ("foobaz", true)]);
+        let i = inspected(r"abc\d+xyz");
+        assert_eq!(texts(&i.prefixes), vec![("abc", false)]);
+        assert_eq!(texts(&i.suffixes), vec![("xyz", false)]);
+    }
+
+    #[test]
+    fn partly_anchored_patterns_get_no_prefixes() {
+        let i = inspected("^ab|cd");
+        assert!(!i.anchored_start);
+        assert!(i.prefixes.is_empty());
+    }
+
+    #[test]
+    fn the_tree_has_a_line_per_node() {
+        let i = inspected("(?P<x>ab)+?|c");
+        assert_eq!(
+            i.tree,
+            vec![
+                "alternation",
+                "  repeat + lazy",
+                "    capture 1 \"x\"",
+                "      literal \"ab\"",
+                "  literal 'c'",
+            ]
+        );
+        let i = inspected(r"^\w");
+        assert_eq!(i.tree[0], "concat");
+        assert_eq!(i.tree[1], "  start of text");
+        assert!(i.tree[2].starts_with("  class of "), "{}", i.tree[2]);
+    }
+
+    #[test]
+    fn automata_are_sized() {
+        let a = inspected("^ab").automata.unwrap();
+        assert!(a.nfa_states > 0 && a.nfa_memory > 0);
+        assert!(a.nfa_anchored);
+        assert!(a.dfa_memory.is_ok());
+        let a = inspected("ab").automata.unwrap();
+        assert!(!a.nfa_anchored);
+    }
+
+    #[test]
+    fn a_dfa_is_not_built_past_the_dfa_size_limit() {
+        let limits = Limits { dfa_size_limit: 1000, ..Limits::default() };
+        let i = inspect("[a-z]{10}", &Flags::default(), &limits).unwrap();
+        let e = i.automata.unwrap().dfa_memory.unwrap_err();
+        assert!(e.contains("size limit"), "{}", e);
+    }
+
+    #[test]
+    fn pattern_that_does_not_translate_is_an_error() {
+        let e = inspect(r"\p{Nope}", &Flags::default(), &Limits::default()).unwrap_err();
+        assert_eq!(e.kind, "Unicode property not found");
+        assert_eq!(e.span.map(|s| s.range()), Some(0..8));
+    }
+}
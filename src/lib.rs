@@ -0,0 +1,31 @@
+//! Regular expression evaluation shared by the re_tester frontends.
+//!
+//! The GUIs only collect a pattern and a string, hand them to an
+//! [`Evaluation`](eval::Evaluation) and display what comes back, so every
+//! frontend behaves the same way and the logic can be exercised without a
+//! window.
+
+pub mod bench;
+pub mod bytes;
+pub mod codegen;
+pub mod compare;
+pub mod eval;
+pub mod explain;
+pub mod hist;
+pub mod inspect;
+pub mod live;
+pub mod options;
+pub mod render;
+pub mod result;
+pub mod suite;
+pub mod testfile;
+
+pub use bench::Benchmark;
+pub use eval::{Evaluation, Operation};
+pub use hist::{Execution, History};
+pub use live::Live;
+pub use options::{Flags, Limits};
+pub use render::{Format, Page};
+pub use result::{Highlight, Outcome};
+pub use suite::Suite;
+pub use testfile::TestFile;
@@ -0,0 +1,115 @@
+use std::{
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        mpsc::{channel, RecvTimeoutError, Sender},
+        Arc,
+    },
+    thread,
+    time::Duration,
+};
+
+use crate::{eval::Evaluation, result::Outcome};
+
+/// How long the input has to stay unchanged before a live evaluation runs.
+pub const DEBOUNCE: Duration = Duration::from_millis(250);
+
+/// Runs evaluations on a background thread as the user types.
+///
+/// Every [`Live::submit`] supersedes the ones before it: submissions that
+/// arrive within the debounce window are collapsed into the newest, an
+/// evaluation that is overtaken while running is abandoned, and only the
+/// outcome of the newest submission is handed to the callback.  The callback
+/// runs on the worker thread, so frontends use it to post the result back to
+/// their event loop.
+#[derive(Debug)]
+pub struct Live {
+    jobs: Sender<(usize, Evaluation)>,
+    latest: Arc<AtomicUsize>,
+}
+
+impl Live {
+    pub fn new<F>(debounce: Duration, done: F) -> Self
+    where
+        F: Fn(Evaluation, Outcome) + Send + 'static,
+    {
+        let (jobs, rx) = channel::<(usize, Evaluation)>();
+        let latest = Arc::new(AtomicUsize::new(0));
+        let current = latest.clone();
+        thread::spawn(move || {
+            while let Ok(mut job) = rx.recv() {
+                loop {
+                    match rx.recv_timeout(debounce) {
+                        Ok(newer) => job = newer,
+                        Err(RecvTimeoutError::Timeout) => break,
+                        Err(RecvTimeoutError::Disconnected) => return,
+                    }
+                }
+                let (generation, eval) = job;
+                let stale = || current.load(Ordering::SeqCst) != generation;
+                if let Some(outcome) = eval.run_until(&stale) {
+                    if !stale() {
+                        done(eval, outcome);
+                    }
+                }
+            }
+        });
+        Live { jobs, latest }
+    }
+
+    pub fn submit(&self, eval: Evaluation) {
+        let generation = self.latest.fetch_add(1, Ordering::SeqCst) + 1;
+        // the worker only stops early if the callback panicked
+        self.jobs.send((generation, eval)).ok();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::mpsc::Receiver;
+
+    use super::*;
+    use crate::eval::Operation;
+
+    const WAIT: Duration = Duration::from_secs(5);
+
+    fn live(debounce: Duration) -> (Live, Receiver<(Evaluation, Outcome)>) {
+        let (tx, rx) = channel();
+        let live = Live::new(debounce, move |eval, outcome| {
+            tx.send((eval, outcome)).unwrap();
+        });
+        (live, rx)
+    }
+
+    fn find(string: &str) -> Evaluation {
+        Evaluation::new(Operation::Find, r"\d", string)
+    }
+
+    #[test]
+    fn only_the_last_of_quick_submissions_is_delivered() {
+        let (live, results) = live(Duration::from_millis(100));
+        for string in ["1", "12", "123"].iter() {
+            live.submit(find(string));
+        }
+        let (eval, outcome) = results.recv_timeout(WAIT).unwrap();
+        assert_eq!(eval.string, "123");
+        assert_eq!(outcome.rows(), 3);
+        assert!(results.recv_timeout(Duration::from_millis(300)).is_err());
+    }
+
+    #[test]
+    fn a_stale_generation_is_dropped() {
+        let (live, results) = live(Duration::from_millis(50));
+        live.submit(find("1"));
+        // as if a newer submission were on its way
+        live.latest.fetch_add(1, Ordering::SeqCst);
+        assert!(results.recv_timeout(Duration::from_millis(300)).is_err());
+        live.submit(find("12"));
+        let (eval, _) = results.recv_timeout(WAIT).unwrap();
+        assert_eq!(eval.string, "12");
+    }
+
+    #[test]
+    fn a_cancelled_evaluation_gives_up() {
+        assert!(find("1 2 3").run_until(&|| true).is_none());
+    }
+}
@@ -1,107 +1,544 @@
 #![windows_subsystem = "windows"]
 
+use std::{fs, ops::Range, sync::Arc};
+
+use druid::text::{ParseFormatter, RichText, RichTextBuilder, Selection};
 use druid::widget::{
-    Button, Flex,  Label,  SizedBox, Slider,
+    Button, Checkbox, Controller, Flex, Label, LineBreaking, RawLabel, Scroll, SizedBox, Slider,
     Split, TextBox,
 };
 use druid::{
-    AppLauncher,   Data, Env, FontDescriptor, FontFamily, Lens,
-    LocalizedString, Menu, TextAlignment,  Widget, WidgetExt, WindowDesc, WindowId,
+    commands, lens, AppDelegate, AppLauncher, Application, Color, Command, Data, DelegateCtx, Env,
+    Event, EventCtx, FileDialogOptions, FileInfo, FileSpec, FontDescriptor, FontFamily, Handled,
+    Lens, LocalizedString, Menu, Selector, Target, TextAlignment, UpdateCtx, Widget, WidgetExt,
+    WindowDesc, WindowId,
+};
+use re_tester::{
+    bench::DEFAULT_RUNS,
+    bytes::{ByteSearch, Encoding, MAX_BYTE_RESULTS},
+    codegen::{self, snippet, Language},
+    compare::Comparison,
+    eval::MAX_FILE_RESULTS,
+    live::DEBOUNCE,
+    render::PAGE_ROWS,
+    result::PatternError,
+    suite::Case,
+    Benchmark, Evaluation, Flags, Format, Highlight, Limits, Live, Operation, Outcome, Page, Suite,
+    TestFile,
 };
-use regex::Regex;
 
 const WINDOW_TITLE: LocalizedString<AppState> = LocalizedString::new("Regular Expression Tester");
 
+/// Asks the delegate to hand the current inputs to the live evaluator.
+const LIVE_EVALUATE: Selector = Selector::new("re_tester.live-evaluate");
+/// Carries a live evaluation back from the worker thread.
+const LIVE_RESULT: Selector<(Evaluation, Outcome)> = Selector::new("re_tester.live-result");
+/// Sent by a click on a highlight, with the result row it belongs to.
+const JUMP_TO_RESULT: Selector<usize> = Selector::new("re_tester.jump-to-result");
+/// Selects the bytes of the pattern a syntax error points at.
+const SELECT_IN_PATTERN: Selector<Range<usize>> = Selector::new("re_tester.select-in-pattern");
+/// Selects, and so scrolls to, the line of the results a highlight belongs to.
+const SELECT_IN_RESULTS: Selector<Range<usize>> = Selector::new("re_tester.select-in-results");
+/// Sent by the file dialogs for test suites, which must not be taken for
+/// `OPEN_FILE`.
+const LOAD_SUITE: Selector<FileInfo> = Selector::new("re_tester.load-suite");
+const SAVE_SUITE: Selector<FileInfo> = Selector::new("re_tester.save-suite");
+/// Sent by the dialog for a binary file to search with `regex::bytes`.
+const OPEN_BYTES: Selector<FileInfo> = Selector::new("re_tester.open-bytes");
+
+fn suite_dialog() -> FileDialogOptions {
+    FileDialogOptions::new().allowed_types(vec![FileSpec::new("Test suite", &["json"])])
+}
+
+/// Text colours for highlighted input: whole matches first, then capture
+/// groups cycling through the rest.
+const GROUP_COLORS: [Color; 6] = [
+    Color::rgb8(0x40, 0x80, 0xff),
+    Color::rgb8(0xff, 0x40, 0x40),
+    Color::rgb8(0x40, 0xc0, 0x40),
+    Color::rgb8(0xe0, 0x40, 0xe0),
+    Color::rgb8(0x40, 0xc0, 0xc0),
+    Color::rgb8(0xe0, 0xc0, 0x40),
+];
+
+fn group_color(group: usize) -> Color {
+    if group == 0 {
+        GROUP_COLORS[0].clone()
+    } else {
+        GROUP_COLORS[1 + (group - 1) % (GROUP_COLORS.len() - 1)].clone()
+    }
+}
+
+/// A copy of `text` with each highlight coloured and linked to its result
+/// row.  Text is cut wherever a highlight starts or ends, and each piece
+/// takes the colour of the innermost highlight covering it.
+fn highlight(text: &str, highlights: &[Highlight]) -> RichText {
+    let mut cuts = vec![0, text.len()];
+    cuts.extend(
+        highlights
+            .iter()
+            .flat_map(|h| vec![h.span.start, h.span.end]),
+    );
+    cuts.sort_unstable();
+    cuts.dedup();
+    let mut b = RichTextBuilder::new();
+    for w in cuts.windows(2) {
+        let piece = &text[w[0]..w[1]];
+        match Highlight::at(highlights, w[0]) {
+            Some(h) => {
+                b.push(piece)
+                    .text_color(group_color(h.group))
+                    .link(JUMP_TO_RESULT.with(h.row));
+            }
+            None => {
+                b.push(piece);
+            }
+        }
+    }
+    b.build()
+}
+
 #[derive(Clone, Data, Debug, Lens)]
 struct AppState {
     pattern: String,
     string: String,
+    /// Searched instead of `string` when loaded.
+    file: Option<Arc<TestFile>>,
+    replacement: String,
+    #[data(same_fn = "PartialEq::eq")]
+    flags: Flags,
+    #[data(same_fn = "PartialEq::eq")]
+    limits: Limits,
     results: String,
+    /// The input as last evaluated, coloured by match and group.
+    highlighted: RichText,
+    /// `results` before a row was pointed out, and the line of each row.
+    output: String,
+    rows: Arc<Vec<usize>>,
+    /// The result shown, kept to page through.
+    last: Option<Arc<(Evaluation, Outcome)>>,
+    #[data(same_fn = "PartialEq::eq")]
+    page: Page,
+    live: bool,
+    #[data(same_fn = "PartialEq::eq")]
+    last_op: Operation,
+    /// Cases of the test suite; its pattern, flags and limits are the ones
+    /// showing.
+    cases: Arc<Vec<Case>>,
+    case_name: String,
+    /// The pattern compared with the one above, with the same flags.
+    pattern_b: String,
+    /// How many times `Bench` runs the last operation.
+    runs: usize,
+    /// The bytes of the pattern to select once the result shows, when an
+    /// evaluation asked for did not compile.
+    select: Option<(usize, usize)>,
 }
 
 impl AppState {
+    fn evaluation(&self, op: Operation) -> Evaluation {
+        let eval = Evaluation::new(op, &self.pattern, &self.string)
+            .with_replacement(&self.replacement)
+            .with_flags(self.flags)
+            .with_limits(self.limits);
+        match &self.file {
+            Some(f) => eval.with_file(f.clone()).with_max_results(MAX_FILE_RESULTS),
+            None => eval,
+        }
+    }
+
+    /// True when something the evaluation depends on differs from `old`.
+    fn inputs_changed(&self, old: &AppState) -> bool {
+        self.pattern != old.pattern
+            || self.string != old.string
+            || !self.file.same(&old.file)
+            || self.replacement != old.replacement
+            || self.flags != old.flags
+            || self.limits != old.limits
+            || self.live != old.live
+    }
+
+    fn evaluate(&mut self, op: Operation) {
+        self.last_op = op;
+        let eval = self.evaluation(op);
+        let outcome = eval.run();
+        self.show(&eval, &outcome);
+        if let Outcome::PatternError(PatternError { span: Some(span), .. }) = &outcome {
+            self.select = Some((span.start, span.end));
+        }
+    }
+
+    fn show(&mut self, eval: &Evaluation, outcome: &Outcome) {
+        self.page = Page::first(PAGE_ROWS);
+        self.select = None;
+        self.last = Some(Arc::new((eval.clone(), outcome.clone())));
+        self.highlighted = match eval.file {
+            Some(_) => RichText::new("".into()),
+            None => highlight(&eval.string, &outcome.highlights()),
+        };
+        self.render();
+    }
+
+    /// Shows the current page of the last result.
+    fn render(&mut self) {
+        if let Some(last) = &self.last {
+            let (text, rows) = Format::Plain.render_page(&last.0, &last.1, self.page);
+            self.results = text.clone();
+            self.output = text;
+            self.rows = Arc::new(rows);
+        }
+    }
+
+    fn next_page(&mut self) {
+        if let Some(last) = &self.last {
+            self.page = self.page.next(last.1.rows());
+        }
+        self.render();
+    }
+
+    fn prev_page(&mut self) {
+        self.page = self.page.prev();
+        self.render();
+    }
+
+    /// Marks the line of result `row` with an arrow, giving the bytes of
+    /// that line in `results`.
+    fn point_out(&mut self, row: usize) -> Option<Range<usize>> {
+        let line = *row
+            .checked_sub(self.page.start)
+            .and_then(|r| self.rows.get(r))?;
+        let mut marked = None;
+        let mut results = Vec::new();
+        let mut start = 0;
+        for (i, l) in self.output.lines().enumerate() {
+            let l = if i == line {
+                let l = format!("=> {}", l);
+                marked = Some(start..start + l.len());
+                l
+            } else {
+                l.to_string()
+            };
+            start += l.len() + 1;
+            results.push(l);
+        }
+        self.results = results.join("\n");
+        marked
+    }
+
     pub fn matches(&mut self) {
-        self.results.clear();
-        match Regex::new(&self.pattern) {
-            Err(e) => self.results.push_str(&format!("Error with pattern: {}", e)),
-            Ok(res) => {
-                if res.is_match(&self.string) {
-                    self.results.push_str(&format!(
-                        "Matching: \"{}\"\nAgainst: \"{}\"\n\n",
-                        &self.pattern, &self.string
-                    ));
-                    let caps = res.captures(&self.string);
-                    if let Some(caps) = caps {
-                        for (i, c) in caps.iter().enumerate() {
-                            if let Some(cc) = c {
-                                self.results.push_str(&format!(
-                                    "group[{}] = \"{}\"\n",
-                                    i,
-                                    cc.as_str()
-                                ));
-                            } else {
-                                self.results.push_str(&format!("group[{}] = None\n", i));
-                            }
-                        }
-                    } else {
-                        self.results.push_str("There are no group captures");
-                    }
-                } else {
-                    self.results.push_str(&format!(
-                        "String:\n\"{}\"\nDoes not match Pattern:\n\"{}\"",
-                        &self.string, &self.pattern
-                    ));
-                }
+        self.evaluate(Operation::Match);
+    }
+
+    pub fn find(&mut self) {
+        self.evaluate(Operation::Find);
+    }
+
+    pub fn find_captures(&mut self) {
+        self.evaluate(Operation::FindCaptures);
+    }
+
+    pub fn split(&mut self) {
+        self.evaluate(Operation::Split);
+    }
+
+    pub fn replace(&mut self) {
+        self.evaluate(Operation::Replace);
+    }
+
+    pub fn lines(&mut self) {
+        self.evaluate(Operation::Lines);
+    }
+
+    pub fn explain(&mut self) {
+        self.evaluate(Operation::Explain);
+    }
+
+    pub fn inspect(&mut self) {
+        self.evaluate(Operation::Inspect);
+    }
+
+    pub fn set(&mut self) {
+        self.evaluate(Operation::Set);
+    }
+
+    fn suite(&self) -> Suite {
+        let mut suite = Suite::new(&self.pattern, self.flags, self.limits);
+        suite.cases = self.cases.to_vec();
+        suite
+    }
+
+    /// Loads a test suite, putting its pattern, flags and limits in place,
+    /// and runs it.
+    fn load_suite(&mut self, info: &FileInfo) {
+        match Suite::load(info.path()) {
+            Ok(suite) => {
+                self.pattern = suite.pattern;
+                self.flags = suite.flags;
+                self.limits = suite.limits;
+                self.cases = Arc::new(suite.cases);
+                self.run_suite();
             }
+            Err(e) => self.results = format!("Unable to load test suite: {}", e),
         }
     }
-    pub fn find(&mut self) {
-        self.results.clear();
-        match Regex::new(&self.pattern) {
-            Err(e) => self.results.push_str(&format!("Error with pattern: {}", e)),
-            Ok(res) => {
-                self.results.push_str(&format!(
-                    "Find pattern:\n\"{}\"\nIn:\n\"{}\"\n\n",
-                    self.pattern, self.string
-                ));
-                let mut finds = 0;
-                for (i, m) in res.find_iter(&self.string).enumerate() {
-                    finds += 1;
-
-                    self.results.push_str(&format!(
-                        "Iteration {} found \"{}\" at ({:?})\n",
-                        i,
-                        m.as_str(),
-                        m.range()
-                    ));
-                }
-                if finds <= 0 {
-                    self.results.push_str("Found nothing");
-                }
+
+    fn save_suite(&mut self, info: &FileInfo) {
+        if let Err(e) = self.suite().save(info.path()) {
+            self.results = format!("Unable to save test suite: {}", e);
+        }
+    }
+
+    /// Adds the string as a case expecting what the pattern does with it now.
+    fn add_case(&mut self) {
+        let mut suite = self.suite();
+        match suite.record(&self.case_name, &self.string) {
+            Ok(()) => {
+                self.cases = Arc::new(suite.cases);
+                self.case_name.clear();
             }
+            Err(e) => self.results = format!("Unable to add case: {}", e.message),
         }
     }
 
-    pub fn split(&mut self) {
-        self.results.clear();
-        match Regex::new(&self.pattern) {
-            Err(e) => self.results.push_str(&format!("Error with pattern: {}", e)),
-            Ok(res) => {
-                self.results.push_str(&format!(
-                    "Splitting with pattern:\n\"{}\"\nString:\n\"{}\"\n\n",
-                    self.pattern, self.string
-                ));
-                let mut finds = 0;
-                for (i, m) in res.split(&self.string).enumerate() {
-                    finds += 1;
-
-                    self.results
-                        .push_str(&format!("Index {} is \"{}\"\n", i, m));
-                }
-                if finds <= 0 {
-                    self.results.push_str("Found nothing");
-                }
+    /// Shows pass or fail for each case of the suite.
+    fn run_suite(&mut self) {
+        let report = self.suite().run();
+        // the report has no pages, rows or JSON of its own
+        self.last = None;
+        self.rows = Arc::new(vec![]);
+        self.results = report.to_string();
+        self.output = self.results.clone();
+    }
+
+    /// Copies Rust tests of the suite to the clipboard; with no cases yet the
+    /// string is the one tested.
+    fn copy_rust_tests(&mut self) {
+        let mut suite = self.suite();
+        if suite.cases.is_empty() {
+            suite.record("", &self.string).ok();
+        }
+        match codegen::rust_tests(&suite) {
+            Ok(code) => Application::global().clipboard().put_string(code),
+            Err(e) => self.results = format!("Unable to make tests: {}", e.message),
+        }
+    }
+
+    /// Shows a program doing what the last operation did, in `language`, and
+    /// copies it to the clipboard.
+    fn code(&mut self, language: Language) {
+        match snippet(language, &self.evaluation(self.last_op)) {
+            Ok(snippet) => {
+                let code = snippet.to_string();
+                Application::global().clipboard().put_string(&code);
+                self.last = None;
+                self.rows = Arc::new(vec![]);
+                self.results = code.clone();
+                self.output = code;
+            }
+            Err(e) => self.results = format!("Unable to write code: {}", e.message),
+        }
+    }
+
+    /// Times the last operation on the current input and shows the
+    /// statistics in place of the result.
+    fn bench(&mut self) {
+        let eval = self.evaluation(self.last_op);
+        match Benchmark::run(&eval, self.runs) {
+            Ok(b) => {
+                self.last = None;
+                self.rows = Arc::new(vec![]);
+                self.results = b.to_string();
+                self.output = self.results.clone();
             }
+            Err(e) => self.show(&eval, &Outcome::PatternError(e)),
+        }
+    }
+
+    /// Runs the last operation with `regex::bytes` on `haystack`, showing up
+    /// to `MAX_BYTE_RESULTS` matches at their offsets with hex dumps.
+    fn search_bytes(&mut self, haystack: Vec<u8>, source: &str) {
+        let search = match ByteSearch::new(self.last_op, &self.pattern, haystack, source) {
+            Ok(search) => search
+                .with_flags(self.flags)
+                .with_limits(self.limits)
+                .with_max_results(MAX_BYTE_RESULTS),
+            Err(e) => {
+                self.results = format!("Unable to search bytes: {}", e);
+                return;
+            }
+        };
+        match search.run() {
+            Ok(found) => {
+                self.last = None;
+                self.rows = Arc::new(vec![]);
+                self.results = found.to_string();
+                self.output = self.results.clone();
+            }
+            Err(e) => self.show(&search.evaluation(), &Outcome::PatternError(e)),
+        }
+    }
+
+    /// Searches the bytes the string spells out in `encoding`.
+    fn decode_bytes(&mut self, encoding: Encoding) {
+        match encoding.decode(&self.string) {
+            Ok(bytes) => self.search_bytes(bytes, &format!("{} from the string", encoding)),
+            Err(e) => self.results = format!("Unable to read {}: {}", encoding, e),
+        }
+    }
+
+    /// Searches the bytes of a file, which need not be text.
+    fn open_bytes(&mut self, info: &FileInfo) {
+        match fs::read(info.path()) {
+            Ok(bytes) => {
+                let source = format!("file {}", info.path().display());
+                self.search_bytes(bytes, &source)
+            }
+            Err(e) => self.results = format!("Unable to load binary file: {}", e),
+        }
+    }
+
+    /// Tries both patterns on the lines of the input, or on the cases of the
+    /// suite when there are any, and colours the lines they disagree on.
+    fn compare(&mut self) {
+        let a = self.evaluation(Operation::Match);
+        let mut b = a.clone();
+        b.pattern = self.pattern_b.clone();
+        let cases = if self.cases.is_empty() {
+            None
+        } else {
+            Some(&self.cases[..])
+        };
+        let comparison = Comparison::run(&a, &b, cases);
+        self.last = None;
+        self.rows = Arc::new(vec![]);
+        self.highlighted = match a.file {
+            Some(_) => RichText::new("".into()),
+            None => highlight(&a.string, &comparison.highlights()),
+        };
+        self.results = comparison.to_string();
+        self.output = self.results.clone();
+    }
+
+    /// Copies the result shown, as JSON, to the clipboard.
+    fn copy_json(&self) {
+        if let Some(last) = &self.last {
+            Application::global()
+                .clipboard()
+                .put_string(Format::Json.render(&last.0, &last.1));
+        }
+    }
+}
+
+/// Requests a live evaluation whenever the inputs change in live mode.
+struct LiveController;
+
+impl<W: Widget<AppState>> Controller<AppState, W> for LiveController {
+    fn update(
+        &mut self,
+        child: &mut W,
+        ctx: &mut UpdateCtx,
+        old_data: &AppState,
+        data: &AppState,
+        env: &Env,
+    ) {
+        if data.live && data.inputs_changed(old_data) {
+            ctx.submit_command(LIVE_EVALUATE);
+        }
+        child.update(ctx, old_data, data, env)
+    }
+}
+
+/// Points at the part of the pattern an evaluation failed on, as the other
+/// frontends do; live results leave the selection alone so typing goes on.
+struct ErrorController;
+
+impl<W: Widget<AppState>> Controller<AppState, W> for ErrorController {
+    fn update(
+        &mut self,
+        child: &mut W,
+        ctx: &mut UpdateCtx,
+        old_data: &AppState,
+        data: &AppState,
+        env: &Env,
+    ) {
+        if !data.last.same(&old_data.last) {
+            if let Some((start, end)) = data.select {
+                ctx.submit_command(SELECT_IN_PATTERN.with(start..end));
+            }
+        }
+        child.update(ctx, old_data, data, env)
+    }
+}
+
+/// Lets a text box be told what to select.  Setting the selection leaves the
+/// box to scroll it into view, which it does on the next event it handles, so
+/// the command is passed on too.
+struct SelectController(Selector<Range<usize>>);
+
+impl Controller<String, TextBox<String>> for SelectController {
+    fn event(
+        &mut self,
+        child: &mut TextBox<String>,
+        ctx: &mut EventCtx,
+        event: &Event,
+        data: &mut String,
+        env: &Env,
+    ) {
+        match event {
+            Event::Command(cmd) if cmd.is(self.0) => {
+                let range = cmd.get_unchecked(self.0);
+                let selection = Selection::new(range.start, range.end);
+                child.text_mut().borrow_mut().set_selection(selection);
+                ctx.request_focus();
+                child.event(ctx, event, data, env);
+                ctx.request_paint();
+            }
+            _ => child.event(ctx, event, data, env),
+        }
+    }
+}
+
+struct Delegate {
+    live: Live,
+}
+
+impl AppDelegate<AppState> for Delegate {
+    fn command(
+        &mut self,
+        ctx: &mut DelegateCtx,
+        _target: Target,
+        cmd: &Command,
+        data: &mut AppState,
+        _env: &Env,
+    ) -> Handled {
+        if cmd.is(LIVE_EVALUATE) {
+            self.live.submit(data.evaluation(data.last_op));
+            Handled::Yes
+        } else if let Some((eval, outcome)) = cmd.get(LIVE_RESULT) {
+            data.show(eval, outcome);
+            Handled::Yes
+        } else if let Some(row) = cmd.get(JUMP_TO_RESULT) {
+            if let Some(line) = data.point_out(*row) {
+                ctx.submit_command(SELECT_IN_RESULTS.with(line));
+            }
+            Handled::Yes
+        } else if let Some(info) = cmd.get(commands::OPEN_FILE) {
+            match TestFile::open(info.path()) {
+                Ok(f) => data.file = Some(Arc::new(f)),
+                Err(e) => data.results = format!("Unable to load test file: {}", e),
+            }
+            Handled::Yes
+        } else if let Some(info) = cmd.get(LOAD_SUITE) {
+            data.load_suite(info);
+            Handled::Yes
+        } else if let Some(info) = cmd.get(SAVE_SUITE) {
+            data.save_suite(info);
+            Handled::Yes
+        } else if let Some(info) = cmd.get(OPEN_BYTES) {
+            data.open_bytes(info);
+            Handled::Yes
+        } else {
+            Handled::No
         }
     }
 }
@@ -111,17 +548,41 @@ pub fn main() {
     let main_window = WindowDesc::new(build_root_widget())
         .title(WINDOW_TITLE)
         //.menu(make_menu)
-        .window_size((800.0, 400.0));
+        .window_size((800.0, 500.0));
 
     // create the initial app state
     let initial_state = AppState {
         pattern: "".to_string().into(),
         string: "".to_string().into(),
+        file: None,
+        replacement: "".to_string().into(),
+        flags: Flags::default(),
+        limits: Limits::default(),
         results: "".to_string().into(),
+        highlighted: RichText::new("".into()),
+        output: String::new(),
+        rows: Arc::new(vec![]),
+        last: None,
+        page: Page::first(PAGE_ROWS),
+        live: false,
+        last_op: Operation::Match,
+        cases: Arc::new(vec![]),
+        case_name: String::new(),
+        pattern_b: String::new(),
+        runs: DEFAULT_RUNS,
+        select: None,
     };
 
-    // start the application
-    AppLauncher::with_window(main_window)
+    // start the application; live results come back through the launcher's
+    // external handle
+    let launcher = AppLauncher::with_window(main_window);
+    let sink = launcher.get_external_handle();
+    let live = Live::new(DEBOUNCE, move |eval, outcome| {
+        sink.submit_command(LIVE_RESULT, (eval, outcome), Target::Auto)
+            .ok();
+    });
+    launcher
+        .delegate(Delegate { live })
         .launch(initial_state)
         .expect("Failed to launch application");
 }
@@ -130,7 +591,14 @@ fn build_root_widget() -> impl Widget<AppState> {
     /*
     RE      [            ]
     String  [            ]
-    [match] [find] [replace]
+    Matches  colored copy of the string
+    File    [open] file name          [<] [>]
+    Suite   [load] [save] [case name] [add case] [test] [rust]  n cases
+    Code    [Rust] [Python] [Go] [Java]
+    Replace [            ]
+    Flags   [ ]i [ ]m [ ]s [ ]U [ ]x [x]u  (?i)
+    Limits  size [    ] dfa [    ] nest [  ]
+    [match] [find] [captures] [split] [replace] [lines] [json]
     [                     ]
     [                     ]
     [                     ]
@@ -139,24 +607,46 @@ fn build_root_widget() -> impl Widget<AppState> {
 
     let mono_font = FontDescriptor::new(FontFamily::MONOSPACE).with_size(12.0);
 
-    let re_tb = TextBox::new()
-        .with_placeholder("Enter regular expression here")
+    // several lines long, for the patterns of a set
+    let re_tb = TextBox::multiline()
+        .with_placeholder("Enter regular expression here, or one per line for a set")
         .with_font(mono_font.clone())
         .with_text_alignment(TextAlignment::Start)
-        .expand_width()
+        .controller(SelectController(SELECT_IN_PATTERN))
+        .expand()
         .lens(AppState::pattern);
 
     let lb1 = Label::new("Pattern:").expand_width();
 
-    let string_tb = TextBox::new()
+    let string_tb = TextBox::multiline()
         .with_placeholder("Enter text to test regular expression against here")
         .with_font(mono_font.clone())
         .with_text_alignment(TextAlignment::Start)
-        .expand_width()
+        .expand()
         .lens(AppState::string);
 
     let lb2 = Label::new("String:").expand_width();
 
+    let replace_tb = TextBox::new()
+        .with_placeholder("Enter replacement ($1, ${name}, $$) here")
+        .with_font(mono_font.clone())
+        .with_text_alignment(TextAlignment::Start)
+        .expand_width()
+        .lens(AppState::replacement);
+
+    let lb3 = Label::new("Replace:").expand_width();
+
+    // druid's TextBox edits plain text only and cannot colour parts of it, so
+    // the matches are coloured in a read-only copy of the input instead
+    let highlighted = Scroll::new(
+        RawLabel::new()
+            .with_font(mono_font.clone())
+            .with_line_break_mode(LineBreaking::WordWrap)
+            .lens(AppState::highlighted),
+    )
+    .vertical()
+    .expand();
+
     let mut row1 = Flex::row() //cross_axis_alignment(CrossAxisAlignment::Start)
         .with_flex_child(lb1, 1.0)
         .with_default_spacer()
@@ -167,6 +657,164 @@ fn build_root_widget() -> impl Widget<AppState> {
         .with_default_spacer()
         .with_flex_child(string_tb, 16.0);
 
+    let row_highlighted = Flex::row()
+        .with_flex_child(Label::new("Matches:").expand_width(), 1.0)
+        .with_default_spacer()
+        .with_flex_child(highlighted, 16.0);
+
+    let row_file = Flex::row()
+        .with_flex_child(Label::new("File:").expand_width(), 1.0)
+        .with_default_spacer()
+        .with_child(
+            Button::dynamic(|data: &AppState, _| match data.file {
+                Some(_) => "Close".to_string(),
+                None => "Open...".to_string(),
+            })
+            .on_click(|ctx, data: &mut AppState, _| match data.file {
+                Some(_) => data.file = None,
+                None => {
+                    ctx.submit_command(commands::SHOW_OPEN_PANEL.with(FileDialogOptions::new()))
+                }
+            }),
+        )
+        .with_default_spacer()
+        .with_flex_child(
+            Label::dynamic(|data: &AppState, _| match &data.file {
+                Some(f) => f.to_string(),
+                None => String::new(),
+            })
+            .expand_width(),
+            13.0,
+        )
+        .with_child(Button::new("<").on_click(|_, data: &mut AppState, _| data.prev_page()))
+        .with_child(Button::new(">").on_click(|_, data: &mut AppState, _| data.next_page()));
+
+    let row_suite = Flex::row()
+        .with_flex_child(Label::new("Suite:").expand_width(), 1.0)
+        .with_default_spacer()
+        .with_child(Button::new("Load...").on_click(|ctx, _: &mut AppState, _| {
+            ctx.submit_command(
+                commands::SHOW_OPEN_PANEL.with(suite_dialog().accept_command(LOAD_SUITE)),
+            )
+        }))
+        .with_child(Button::new("Save...").on_click(|ctx, _: &mut AppState, _| {
+            ctx.submit_command(
+                commands::SHOW_SAVE_PANEL.with(suite_dialog().accept_command(SAVE_SUITE)),
+            )
+        }))
+        .with_default_spacer()
+        .with_flex_child(
+            TextBox::new()
+                .with_placeholder("case name")
+                .expand_width()
+                .lens(AppState::case_name),
+            4.0,
+        )
+        .with_child(Button::new("Add case").on_click(|_, data: &mut AppState, _| data.add_case()))
+        .with_child(Button::new("Test").on_click(|_, data: &mut AppState, _| data.run_suite()))
+        .with_child(
+            Button::new("Rust").on_click(|_, data: &mut AppState, _| data.copy_rust_tests()),
+        )
+        .with_default_spacer()
+        .with_flex_child(
+            Label::dynamic(|data: &AppState, _| format!("{} cases", data.cases.len()))
+                .expand_width(),
+            4.0,
+        );
+
+    let mut row_code = Flex::row()
+        .with_flex_child(Label::new("Code:").expand_width(), 1.0)
+        .with_default_spacer();
+    for language in Language::ALL.iter().copied() {
+        row_code.add_child(
+            Button::new(language.title())
+                .on_click(move |_, data: &mut AppState, _| data.code(language)),
+        );
+    }
+    row_code.add_flex_spacer(12.0);
+
+    let mut row_bytes = Flex::row()
+        .with_flex_child(Label::new("Bytes:").expand_width(), 1.0)
+        .with_default_spacer();
+    for encoding in Encoding::ALL.iter().copied() {
+        row_bytes.add_child(
+            Button::new(encoding.title())
+                .on_click(move |_, data: &mut AppState, _| data.decode_bytes(encoding)),
+        );
+    }
+    row_bytes.add_child(Button::new("File...").on_click(|ctx, _: &mut AppState, _| {
+        ctx.submit_command(
+            commands::SHOW_OPEN_PANEL.with(FileDialogOptions::new().accept_command(OPEN_BYTES)),
+        )
+    }));
+    row_bytes.add_flex_spacer(12.0);
+
+    let row_compare = Flex::row()
+        .with_flex_child(Label::new("Pattern B:").expand_width(), 1.0)
+        .with_default_spacer()
+        .with_flex_child(
+            TextBox::new()
+                .with_font(mono_font.clone())
+                .expand_width()
+                .lens(AppState::pattern_b),
+            14.0,
+        )
+        .with_child(Button::new("Compare").on_click(|_, data: &mut AppState, _| data.compare()));
+
+    let mut row_replace = Flex::row()
+        .with_flex_child(lb3, 1.0)
+        .with_default_spacer()
+        .with_flex_child(replace_tb, 16.0);
+
+    let flag_box = |label: &str, l| Checkbox::new(label).lens(AppState::flags.then(l));
+    let row_flags = Flex::row()
+        .with_flex_child(Label::new("Flags:").expand_width(), 1.0)
+        .with_default_spacer()
+        .with_child(flag_box("i", lens!(Flags, case_insensitive)))
+        .with_child(flag_box("m", lens!(Flags, multi_line)))
+        .with_child(flag_box("s", lens!(Flags, dot_matches_new_line)))
+        .with_child(flag_box("U", lens!(Flags, swap_greed)))
+        .with_child(flag_box("x", lens!(Flags, ignore_whitespace)))
+        .with_child(flag_box("u", lens!(Flags, unicode)))
+        .with_default_spacer()
+        .with_flex_child(
+            Label::dynamic(|data: &AppState, _| data.flags.inline())
+                .with_font(mono_font.clone())
+                .expand_width(),
+            14.0,
+        );
+
+    let row_limits = Flex::row()
+        .with_flex_child(Label::new("Limits:").expand_width(), 1.0)
+        .with_default_spacer()
+        .with_child(Label::new("size"))
+        .with_flex_child(
+            TextBox::new()
+                .with_formatter(ParseFormatter::new())
+                .update_data_while_editing(true)
+                .lens(AppState::limits.then(lens!(Limits, size_limit))),
+            4.0,
+        )
+        .with_default_spacer()
+        .with_child(Label::new("dfa"))
+        .with_flex_child(
+            TextBox::new()
+                .with_formatter(ParseFormatter::new())
+                .update_data_while_editing(true)
+                .lens(AppState::limits.then(lens!(Limits, dfa_size_limit))),
+            4.0,
+        )
+        .with_default_spacer()
+        .with_child(Label::new("nest"))
+        .with_flex_child(
+            TextBox::new()
+                .with_formatter(ParseFormatter::new())
+                .update_data_while_editing(true)
+                .lens(AppState::limits.then(lens!(Limits, nest_limit))),
+            2.0,
+        )
+        .with_flex_spacer(6.0);
+
     let mut row3 = Flex::row()
         .with_flex_child(
             Button::new("Matches")
@@ -186,6 +834,15 @@ fn build_root_widget() -> impl Widget<AppState> {
             1.0,
         )
         .with_default_spacer()
+        .with_flex_child(
+            Button::new("Captures")
+                .on_click(|ctx, data: &mut AppState, e: &Env| {
+                    data.find_captures();
+                })
+                .expand_width(),
+            1.0,
+        )
+        .with_default_spacer()
         .with_flex_child(
             Button::new("Split")
                 .on_click(|ctx, data: &mut AppState, e: &Env| {
@@ -193,24 +850,115 @@ fn build_root_widget() -> impl Widget<AppState> {
                 })
                 .expand_width(),
             1.0,
-        );
+        )
+        .with_default_spacer()
+        .with_flex_child(
+            Button::new("Replace")
+                .on_click(|ctx, data: &mut AppState, e: &Env| {
+                    data.replace();
+                })
+                .expand_width(),
+            1.0,
+        )
+        .with_default_spacer()
+        .with_flex_child(
+            Button::new("Lines")
+                .on_click(|ctx, data: &mut AppState, e: &Env| {
+                    data.lines();
+                })
+                .expand_width(),
+            1.0,
+        )
+        .with_default_spacer()
+        .with_flex_child(
+            Button::new("Explain")
+                .on_click(|ctx, data: &mut AppState, e: &Env| {
+                    data.explain();
+                })
+                .expand_width(),
+            1.0,
+        )
+        .with_default_spacer()
+        .with_flex_child(
+            Button::new("Inspect")
+                .on_click(|ctx, data: &mut AppState, e: &Env| {
+                    data.inspect();
+                })
+                .expand_width(),
+            1.0,
+        )
+        .with_default_spacer()
+        .with_flex_child(
+            Button::new("Set")
+                .on_click(|ctx, data: &mut AppState, e: &Env| {
+                    data.set();
+                })
+                .expand_width(),
+            1.0,
+        )
+        .with_default_spacer()
+        .with_flex_child(
+            Button::new("Bench")
+                .on_click(|ctx, data: &mut AppState, e: &Env| {
+                    data.bench();
+                })
+                .expand_width(),
+            1.0,
+        )
+        .with_flex_child(
+            TextBox::new()
+                .with_formatter(ParseFormatter::new())
+                .update_data_while_editing(true)
+                .lens(AppState::runs),
+            1.0,
+        )
+        .with_default_spacer()
+        .with_flex_child(
+            Button::new("JSON")
+                .on_click(|ctx, data: &mut AppState, e: &Env| {
+                    data.copy_json();
+                })
+                .expand_width(),
+            1.0,
+        )
+        .with_default_spacer()
+        .with_child(Checkbox::new("Live").lens(AppState::live));
 
     let results_tb = TextBox::multiline()
         .with_placeholder("Results go here")
         .with_text_alignment(TextAlignment::Start)
         .with_font(mono_font.clone())
+        .controller(SelectController(SELECT_IN_RESULTS))
         .expand()
         .lens(AppState::results);
 
     let mut mc = Flex::column();
-    mc.add_flex_child(row1, 1.0);
+    mc.add_flex_child(row1, 2.0);
+    mc.add_default_spacer();
+    mc.add_flex_child(row2, 3.0);
+    mc.add_default_spacer();
+    mc.add_flex_child(row_highlighted, 2.0);
+    mc.add_default_spacer();
+    mc.add_flex_child(row_file, 1.0);
+    mc.add_default_spacer();
+    mc.add_flex_child(row_suite, 1.0);
+    mc.add_default_spacer();
+    mc.add_flex_child(row_code, 1.0);
+    mc.add_default_spacer();
+    mc.add_flex_child(row_bytes, 1.0);
+    mc.add_default_spacer();
+    mc.add_flex_child(row_compare, 1.0);
+    mc.add_default_spacer();
+    mc.add_flex_child(row_replace, 1.0);
+    mc.add_default_spacer();
+    mc.add_flex_child(row_flags, 1.0);
     mc.add_default_spacer();
-    mc.add_flex_child(row2, 1.0);
+    mc.add_flex_child(row_limits, 1.0);
     mc.add_default_spacer();
     mc.add_flex_child(row3, 1.0);
     mc.add_flex_child(results_tb, 8.0);
 
-    mc //.debug_paint_layout()
+    mc.controller(LiveController).controller(ErrorController) //.debug_paint_layout()
 }
 
 #[allow(unused_assignments, unused_mut)]
@@ -6,38 +6,154 @@
 
 extern crate native_windows_gui as nwg;
 use std::borrow::{Borrow, BorrowMut};
+use std::cell::{Cell, RefCell};
+use std::fs;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
 
-use nwg::{NativeUi, simple_message};
-use serde::{Deserialize, Serialize};
-use regex::Regex;
+use nwg::{simple_message, CheckBoxState, NativeUi};
+use re_tester::{
+    bench::DEFAULT_RUNS,
+    bytes::{ByteSearch, Encoding, MAX_BYTE_RESULTS},
+    codegen::{self, snippet, Language},
+    compare::Comparison,
+    eval::MAX_FILE_RESULTS,
+    live::DEBOUNCE,
+    render::PAGE_ROWS,
+    result::PatternError,
+    suite::Case,
+    Benchmark, Evaluation, Execution, Flags, Format, Highlight, History, Limits, Live, Operation,
+    Outcome, Page, Suite, TestFile,
+};
 
-mod hist;
-use hist::{Execution, History};
+/// Text colours for highlighted input: whole matches first, then capture
+/// groups cycling through the rest.
+const GROUP_COLORS: [[u8; 3]; 6] = [
+    [0, 0, 255],
+    [255, 0, 0],
+    [0, 128, 0],
+    [192, 0, 192],
+    [0, 128, 128],
+    [160, 120, 0],
+];
+
+fn group_color(group: usize) -> [u8; 3] {
+    if group == 0 {
+        GROUP_COLORS[0]
+    } else {
+        GROUP_COLORS[1 + (group - 1) % (GROUP_COLORS.len() - 1)]
+    }
+}
 
 #[derive(Default)]
 pub struct ReTesterApp {
     font_lbl: nwg::Font,
     font_io: nwg::Font,
     window: nwg::Window,
-    
+
     col_layout: nwg::FlexboxLayout,
     layout1: nwg::FlexboxLayout,
     layout2: nwg::FlexboxLayout,
     layout3: nwg::FlexboxLayout,
+    layout4: nwg::FlexboxLayout,
+    layout5: nwg::FlexboxLayout,
+    layout6: nwg::FlexboxLayout,
+    layout7: nwg::FlexboxLayout,
+    layout8: nwg::FlexboxLayout,
+    layout9: nwg::FlexboxLayout,
+    layout10: nwg::FlexboxLayout,
+    layout11: nwg::FlexboxLayout,
+    layout12: nwg::FlexboxLayout,
 
     pattern_lb: nwg::Label,
-    pattern_inp: nwg::TextInput,
+    /// Several lines long, for the patterns of a set.
+    pattern_inp: nwg::TextBox,
     string_lb: nwg::Label,
-    string_inp: nwg::TextInput,
+    string_inp: nwg::RichTextBox,
+    replace_lb: nwg::Label,
+    replace_inp: nwg::TextInput,
+
+    file_lb: nwg::Label,
+    open_bt: nwg::Button,
+    file_name_lb: nwg::Label,
+    prev_bt: nwg::Button,
+    next_bt: nwg::Button,
+    file_dialog: nwg::FileDialog,
+
+    suite_lb: nwg::Label,
+    load_suite_bt: nwg::Button,
+    save_suite_bt: nwg::Button,
+    case_name_inp: nwg::TextInput,
+    add_case_bt: nwg::Button,
+    run_suite_bt: nwg::Button,
+    rust_bt: nwg::Button,
+    cases_lb: nwg::Label,
+    load_suite_dialog: nwg::FileDialog,
+    save_suite_dialog: nwg::FileDialog,
+
+    code_lb: nwg::Label,
+    rust_code_bt: nwg::Button,
+    python_code_bt: nwg::Button,
+    go_code_bt: nwg::Button,
+    java_code_bt: nwg::Button,
+
+    bytes_lb: nwg::Label,
+    hex_bt: nwg::Button,
+    base64_bt: nwg::Button,
+    bytes_file_bt: nwg::Button,
+    bytes_dialog: nwg::FileDialog,
+
+    pattern_b_lb: nwg::Label,
+    pattern_b_inp: nwg::TextInput,
+    compare_bt: nwg::Button,
+
+    flags_lb: nwg::Label,
+    case_insensitive_cb: nwg::CheckBox,
+    multi_line_cb: nwg::CheckBox,
+    dot_matches_new_line_cb: nwg::CheckBox,
+    swap_greed_cb: nwg::CheckBox,
+    ignore_whitespace_cb: nwg::CheckBox,
+    unicode_cb: nwg::CheckBox,
+    inline_flags_lb: nwg::Label,
+
+    limits_lb: nwg::Label,
+    size_limit_inp: nwg::TextInput,
+    dfa_size_limit_inp: nwg::TextInput,
+    nest_limit_inp: nwg::TextInput,
 
     match_bt: nwg::Button,
     find_bt: nwg::Button,
+    captures_bt: nwg::Button,
     split_bt: nwg::Button,
+    replace_bt: nwg::Button,
+    lines_bt: nwg::Button,
+    explain_bt: nwg::Button,
+    inspect_bt: nwg::Button,
+    set_bt: nwg::Button,
     history_bt: nwg::Button,
+    bench_bt: nwg::Button,
+    /// How many times `Bench` runs the last operation.
+    runs_inp: nwg::TextInput,
+    json_bt: nwg::Button,
+    live_cb: nwg::CheckBox,
 
     output_tb: nwg::TextBox,
 
     hist: History,
+    /// What the input is coloured with, and the output line of each row.
+    highlights: RefCell<(Vec<Highlight>, Vec<usize>)>,
+    file: RefCell<Option<Arc<TestFile>>>,
+    /// The result shown, kept to page through.
+    last: RefCell<Option<(Evaluation, Outcome)>>,
+    page: Cell<Option<Page>>,
+    /// Cases of the test suite; its pattern, flags and limits are the ones
+    /// showing.
+    cases: RefCell<Vec<Case>>,
+
+    live: Option<Live>,
+    live_notice: nwg::Notice,
+    live_result: Arc<Mutex<Option<(Evaluation, Outcome)>>>,
+    last_op: Cell<Option<Operation>>,
     // button2: nwg::Button,
     // button3: nwg::Button
 }
@@ -46,132 +162,497 @@ impl ReTesterApp {
     fn exit(&self) {
         nwg::stop_thread_dispatch();
     }
-    fn _match(&self) {
-        self.output_tb.set_text("");
-        let mut results = String::with_capacity(128);
-        match Regex::new(&self.pattern_inp.text()) {
-            Err(e) => {
-                results.push_str(&format!("Error with pattern: {}", e))
+    fn flags(&self) -> Flags {
+        let checked = |cb: &nwg::CheckBox| cb.check_state() == CheckBoxState::Checked;
+        Flags {
+            case_insensitive: checked(&self.case_insensitive_cb),
+            multi_line: checked(&self.multi_line_cb),
+            dot_matches_new_line: checked(&self.dot_matches_new_line_cb),
+            swap_greed: checked(&self.swap_greed_cb),
+            ignore_whitespace: checked(&self.ignore_whitespace_cb),
+            unicode: checked(&self.unicode_cb),
+        }
+    }
+
+    /// Limits from the inputs; anything that does not parse falls back to the
+    /// default limit.
+    fn limits(&self) -> Limits {
+        let d = Limits::default();
+        Limits {
+            size_limit: self.size_limit_inp.text().parse().unwrap_or(d.size_limit),
+            dfa_size_limit: self
+                .dfa_size_limit_inp
+                .text()
+                .parse()
+                .unwrap_or(d.dfa_size_limit),
+            nest_limit: self.nest_limit_inp.text().parse().unwrap_or(d.nest_limit),
+        }
+    }
+
+    fn set_flags(&self, f: &Flags) {
+        let state = |on: bool| {
+            if on {
+                CheckBoxState::Checked
+            } else {
+                CheckBoxState::Unchecked
             }
-            Ok(res) => {
-                if res.is_match(&self.string_inp.text()) {
-                    results.push_str(&format!(
-                        "Matching: \"{}\"\r\nAgainst: \"{}\"\r\n",
-                        &self.pattern_inp.text(),
-                        &self.string_inp.text()
-                    ));
-                    let s = self.string_inp.text().clone();
-                    let caps = res.captures(&s);
-                    if let Some(caps) = caps {
-                        for (i, c) in caps.iter().enumerate() {
-                            if let Some(cc) = c {
-                                results.push_str(&format!("group[{}] = \"{}\"\n", i, cc.as_str()));
-                            } else {
-                                results.push_str(&format!("group[{}] = None\n", i));
-                            }
-                        }
-                    } else {
-                        results.push_str("There are no group captures");
+        };
+        self.case_insensitive_cb
+            .set_check_state(state(f.case_insensitive));
+        self.multi_line_cb.set_check_state(state(f.multi_line));
+        self.dot_matches_new_line_cb
+            .set_check_state(state(f.dot_matches_new_line));
+        self.swap_greed_cb.set_check_state(state(f.swap_greed));
+        self.ignore_whitespace_cb
+            .set_check_state(state(f.ignore_whitespace));
+        self.unicode_cb.set_check_state(state(f.unicode));
+        self.show_inline_flags();
+    }
+
+    fn set_limits(&self, l: &Limits) {
+        self.size_limit_inp.set_text(&l.size_limit.to_string());
+        self.dfa_size_limit_inp
+            .set_text(&l.dfa_size_limit.to_string());
+        self.nest_limit_inp.set_text(&l.nest_limit.to_string());
+    }
+
+    fn show_inline_flags(&self) {
+        self.inline_flags_lb.set_text(&self.flags().inline());
+    }
+
+    /// The pattern with `\n` line endings; the edit control uses `\r\n`.
+    fn pattern(&self) -> String {
+        self.pattern_inp.text().replace("\r\n", "\n")
+    }
+
+    fn evaluation(&self, op: Operation) -> Evaluation {
+        // the RichTextBox hands back \r or \r\n line endings; offsets are
+        // reported against \n so they agree with the other frontends, and
+        // since the control counts a line break as one char the char offsets
+        // still line up with it
+        let string = self
+            .string_inp
+            .text()
+            .replace("\r\n", "\n")
+            .replace('\r', "\n");
+        let eval = Evaluation::new(op, &self.pattern(), &string)
+            .with_replacement(&self.replace_inp.text())
+            .with_flags(self.flags())
+            .with_limits(self.limits());
+        match &*self.file.borrow() {
+            Some(f) => eval.with_file(f.clone()).with_max_results(MAX_FILE_RESULTS),
+            None => eval,
+        }
+    }
+
+    /// Loads a file to search in place of the string, or unloads the one
+    /// loaded.
+    fn open_file(&self) {
+        if self.file.borrow_mut().take().is_some() {
+            self.file_name_lb.set_text("");
+            self.open_bt.set_text("&Open...");
+        } else if self.file_dialog.run(Some(&self.window)) {
+            if let Ok(item) = self.file_dialog.get_selected_item() {
+                match TestFile::open(&PathBuf::from(item)) {
+                    Ok(f) => {
+                        self.file_name_lb.set_text(&f.to_string());
+                        self.open_bt.set_text("&Close");
+                        *self.file.borrow_mut() = Some(Arc::new(f));
+                    }
+                    Err(e) => {
+                        simple_message("Error", &format!("Unable to load test file: {}", e));
                     }
-                } else {
-                    results.push_str(&format!(
-                        "String:\r\n\t\"{}\"\r\nDoes not match Pattern:\r\n\t\"{}\"",
-                        &self.string_inp.text(),
-                        &self.pattern_inp.text()
-                    ));
                 }
             }
         }
-        self.output_tb.set_text(&results);
-        
-        self.hist.add(Execution::new(
-            "match",
-            &self.pattern_inp.text(),
-            &self.string_inp.text(),
-        ));
+        self.live_update();
     }
 
-    fn find(&self) {
-        self.output_tb.set_text("");
+    fn page(&self) -> Page {
+        self.page.get().unwrap_or_else(|| Page::first(PAGE_ROWS))
+    }
 
-        let mut results = String::with_capacity(128);
-        let pattern = self.pattern_inp.text().clone();
-        let string = self.string_inp.text().clone();
+    /// Shows the current page of the last result.
+    fn render(&self) {
+        if let Some((eval, outcome)) = &*self.last.borrow() {
+            let (text, rows) = Format::Crlf.render_page(eval, outcome, self.page());
+            self.output_tb.set_text(&text);
+            self.highlights.borrow_mut().1 = rows;
+        }
+    }
 
-        match Regex::new(&pattern) {
-            Err(e) => {
-                // self.out.set_text_color(Color::Red);
-                results.push_str(&format!("Error with pattern: {}", e))
+    fn next_page(&self) {
+        if let Some((_, outcome)) = &*self.last.borrow() {
+            self.page.set(Some(self.page().next(outcome.rows())));
+        }
+        self.render();
+    }
+
+    fn prev_page(&self) {
+        self.page.set(Some(self.page().prev()));
+        self.render();
+    }
+
+    /// Re-runs the last operation in the background when live mode is on.
+    fn live_update(&self) {
+        if self.live_cb.check_state() == CheckBoxState::Checked {
+            let op = self.last_op.get().unwrap_or(Operation::Match);
+            if let Some(live) = &self.live {
+                live.submit(self.evaluation(op));
+            }
+        }
+    }
+
+    fn show_live_result(&self) {
+        if let Some((eval, outcome)) = self.live_result.lock().unwrap().take() {
+            self.show(&eval, &outcome);
+        }
+    }
+
+    fn show(&self, eval: &Evaluation, outcome: &Outcome) {
+        self.page.set(None);
+        *self.last.borrow_mut() = Some((eval.clone(), outcome.clone()));
+        // a live result can arrive after the input changed again
+        if eval.file.is_none() && eval.string == self.evaluation(eval.operation).string {
+            let highlights = outcome.highlights();
+            let selection = self.string_inp.selection();
+            self.paint(&highlights);
+            self.string_inp.set_selection(selection);
+            self.highlights.borrow_mut().0 = highlights;
+        }
+        self.render();
+    }
+
+    /// Colours the input, resetting whatever was coloured before.
+    fn paint(&self, highlights: &[Highlight]) {
+        let color = |c: [u8; 3]| nwg::CharFormat { text_color: Some(c), ..Default::default() };
+        self.string_inp.set_selection(0..self.string_inp.len());
+        self.string_inp.set_char_format(&color([0, 0, 0]));
+        for h in highlights.iter() {
+            let r = h.span.char_range();
+            self.string_inp.set_selection(r.start as u32..r.end as u32);
+            self.string_inp
+                .set_char_format(&color(group_color(h.group)));
+        }
+    }
+
+    /// Drops the colouring once the input is edited, as the offsets no
+    /// longer line up.
+    fn clear_highlights(&self) {
+        if !self.highlights.borrow().0.is_empty() {
+            let selection = self.string_inp.selection();
+            self.paint(&[]);
+            self.string_inp.set_selection(selection);
+            *self.highlights.borrow_mut() = (vec![], vec![]);
+        }
+    }
+
+    /// Selects and scrolls to the result row of the highlight under the
+    /// input's caret.
+    fn jump_to_result(&self) {
+        let caret = self.string_inp.selection().start as usize;
+        let string = self.evaluation(Operation::Match).string;
+        let byte = string
+            .char_indices()
+            .nth(caret)
+            .map_or(string.len(), |(b, _)| b);
+        let line = {
+            let h = self.highlights.borrow();
+            let start = self.page().start;
+            let row = Highlight::at(&h.0, byte).and_then(|hl| hl.row.checked_sub(start));
+            match row.and_then(|r| h.1.get(r)) {
+                Some(line) => *line,
+                None => return,
             }
-            Ok(res) => {
-                results.push_str(&format!(
-                    "Find pattern:\r\n\"{}\"\r\nIn:\r\n\"{}\"\r\n",
-                    pattern, string
-                ));
-                let mut finds = 0;
-                for (i, m) in res.find_iter(&string).enumerate() {
-                    finds += 1;
-
-                    results.push_str(&format!(
-                        "Iteration {} found \"{}\" at ({:?})\r\n",
-                        i,
-                        m.as_str(),
-                        m.range()
-                    ));
+        };
+        let text = self.output_tb.text();
+        let mut lines = text.split("\r\n");
+        let start: usize = lines
+            .by_ref()
+            .take(line)
+            .map(|l| l.chars().count() + 2)
+            .sum();
+        let end = start + lines.next().map_or(0, |l| l.chars().count());
+        self.output_tb.set_selection(start as u32..end as u32);
+        if let Some(hwnd) = self.output_tb.handle.hwnd() {
+            use winapi::um::winuser::{SendMessageW, EM_SCROLLCARET};
+            unsafe { SendMessageW(hwnd, EM_SCROLLCARET, 0, 0) };
+        }
+    }
+
+    fn evaluate(&self, op: Operation) {
+        self.last_op.set(Some(op));
+        let eval = self.evaluation(op);
+        let outcome = eval.run();
+        self.show(&eval, &outcome);
+        if let Outcome::PatternError(PatternError { span: Some(span), .. }) = &outcome {
+            // each line break before the error is two chars in the control
+            let r = span.char_range();
+            let breaks = span.line as u32 - 1;
+            self.pattern_inp
+                .set_selection(r.start as u32 + breaks..r.end as u32 + breaks);
+            self.pattern_inp.set_focus();
+        }
+        if let Err(e) = self.hist.add(Execution::from(&eval)) {
+            simple_message("Error", &format!("Unable to save history: {}", e));
+        }
+    }
+
+    fn _match(&self) {
+        self.evaluate(Operation::Match);
+    }
+
+    fn find(&self) {
+        self.evaluate(Operation::Find);
+    }
+
+    fn find_captures(&self) {
+        self.evaluate(Operation::FindCaptures);
+    }
+
+    fn split(&self) {
+        self.evaluate(Operation::Split);
+    }
+
+    fn replace(&self) {
+        self.evaluate(Operation::Replace);
+    }
+
+    fn lines(&self) {
+        self.evaluate(Operation::Lines);
+    }
+
+    fn explain(&self) {
+        self.evaluate(Operation::Explain);
+    }
+
+    fn inspect(&self) {
+        self.evaluate(Operation::Inspect);
+    }
+
+    fn set(&self) {
+        self.evaluate(Operation::Set);
+    }
+
+    fn suite(&self) -> Suite {
+        let mut suite = Suite::new(&self.pattern(), self.flags(), self.limits());
+        suite.cases = self.cases.borrow().clone();
+        suite
+    }
+
+    fn set_cases(&self, cases: Vec<Case>) {
+        self.cases_lb.set_text(&format!("{} cases", cases.len()));
+        *self.cases.borrow_mut() = cases;
+    }
+
+    /// Loads a test suite, putting its pattern, flags and limits in place,
+    /// and runs it.
+    fn load_suite(&self) {
+        if !self.load_suite_dialog.run(Some(&self.window)) {
+            return;
+        }
+        if let Ok(item) = self.load_suite_dialog.get_selected_item() {
+            match Suite::load(&PathBuf::from(item)) {
+                Ok(suite) => {
+                    self.pattern_inp
+                        .set_text(&suite.pattern.replace('\n', "\r\n"));
+                    self.set_flags(&suite.flags);
+                    self.set_limits(&suite.limits);
+                    self.set_cases(suite.cases);
+                    self.run_suite();
                 }
-                if finds <= 0 {
-                    // self.out.set_text_color(Color::Red);
-                    results.push_str("Found nothing");
+                Err(e) => {
+                    simple_message("Error", &format!("Unable to load test suite: {}", e));
                 }
             }
         }
-        self.output_tb.set_text(&results);
-        self.hist
-            .add(Execution::new("find", &self.pattern_inp.text(), &self.string_inp.text()));
     }
 
-    fn split(&self) {
-        self.output_tb.set_text("");
+    fn save_suite(&self) {
+        if !self.save_suite_dialog.run(Some(&self.window)) {
+            return;
+        }
+        if let Ok(item) = self.save_suite_dialog.get_selected_item() {
+            if let Err(e) = self.suite().save(&PathBuf::from(item)) {
+                simple_message("Error", &format!("Unable to save test suite: {}", e));
+            }
+        }
+    }
 
-        let mut results = String::with_capacity(128);
-        let pattern = self.pattern_inp.text().clone();
-        let string = self.string_inp.text().clone();
+    /// Adds the string as a case expecting what the pattern does with it now.
+    fn add_case(&self) {
+        let mut suite = self.suite();
+        let string = self.evaluation(Operation::Match).string;
+        match suite.record(&self.case_name_inp.text(), &string) {
+            Ok(()) => {
+                self.set_cases(suite.cases);
+                self.case_name_inp.set_text("");
+            }
+            Err(e) => {
+                simple_message("Error", &format!("Unable to add case: {}", e.message));
+            }
+        }
+    }
+
+    /// Shows pass or fail for each case of the suite.
+    fn run_suite(&self) {
+        let report = self.suite().run();
+        // the report has no pages, rows or JSON of its own
+        *self.last.borrow_mut() = None;
+        self.highlights.borrow_mut().1.clear();
+        self.output_tb
+            .set_text(&report.to_string().replace('\n', "\r\n"));
+    }
+
+    /// Tries the pattern and pattern B on each line of the string, or on the
+    /// cases of the suite when it has any, colouring the lines they disagree
+    /// on.
+    fn compare(&self) {
+        let a = self.evaluation(Operation::Match);
+        let mut b = a.clone();
+        b.pattern = self.pattern_b_inp.text();
+        let comparison = {
+            let cases = self.cases.borrow();
+            let cases = if cases.is_empty() {
+                None
+            } else {
+                Some(cases.as_slice())
+            };
+            Comparison::run(&a, &b, cases)
+        };
+        *self.last.borrow_mut() = None;
+        if a.file.is_none() {
+            let highlights = comparison.highlights();
+            let selection = self.string_inp.selection();
+            self.paint(&highlights);
+            self.string_inp.set_selection(selection);
+            *self.highlights.borrow_mut() = (highlights, vec![]);
+        }
+        self.output_tb
+            .set_text(&comparison.to_string().replace('\n', "\r\n"));
+    }
 
-        match Regex::new(&pattern) {
+    /// Copies Rust tests of the suite to the clipboard; with no cases yet the
+    /// string is the one tested.
+    fn copy_rust_tests(&self) {
+        let mut suite = self.suite();
+        if suite.cases.is_empty() {
+            suite
+                .record("", &self.evaluation(Operation::Match).string)
+                .ok();
+        }
+        match codegen::rust_tests(&suite) {
+            Ok(code) => nwg::Clipboard::set_data_text(&self.window, &code.replace('\n', "\r\n")),
             Err(e) => {
-                results.push_str(&format!("Error with pattern: {}", e))
+                simple_message("Error", &format!("Unable to make tests: {}", e.message));
             }
-            Ok(res) => {
-                results.push_str(&format!(
-                    "Splitting with pattern:\r\n\"{}\"\r\nString:\r\n\"{}\"\r\n\n",
-                    pattern, string
-                ));
-                let mut finds = 0;
-                for (i, m) in res.split(&string).enumerate() {
-                    finds += 1;
-                    results.push_str(&format!("Index {} is \"{}\"\r\n", i, m));
-                }
-                if finds <= 0 {
-                    results.push_str("Found nothing");
+        }
+    }
+
+    /// Shows a program doing what the last operation did, in `language`, and
+    /// copies it to the clipboard.
+    fn code(&self, language: Language) {
+        let op = self.last_op.get().unwrap_or(Operation::Match);
+        match snippet(language, &self.evaluation(op)) {
+            Ok(snippet) => {
+                let code = snippet.to_string().replace('\n', "\r\n");
+                nwg::Clipboard::set_data_text(&self.window, &code);
+                *self.last.borrow_mut() = None;
+                self.highlights.borrow_mut().1.clear();
+                self.output_tb.set_text(&code);
+            }
+            Err(e) => {
+                simple_message("Error", &format!("Unable to write code: {}", e.message));
+            }
+        }
+    }
+
+    /// Runs the last operation with `regex::bytes` on `haystack`, showing up
+    /// to `MAX_BYTE_RESULTS` matches at their offsets with hex dumps.
+    fn search_bytes(&self, haystack: Vec<u8>, source: &str) {
+        let op = self.last_op.get().unwrap_or(Operation::Match);
+        let search = match ByteSearch::new(op, &self.pattern(), haystack, source) {
+            Ok(search) => search
+                .with_flags(self.flags())
+                .with_limits(self.limits())
+                .with_max_results(MAX_BYTE_RESULTS),
+            Err(e) => {
+                simple_message("Error", &format!("Unable to search bytes: {}", e));
+                return;
+            }
+        };
+        match search.run() {
+            Ok(found) => {
+                *self.last.borrow_mut() = None;
+                self.highlights.borrow_mut().1.clear();
+                self.output_tb
+                    .set_text(&found.to_string().replace('\n', "\r\n"));
+            }
+            Err(e) => self.show(&search.evaluation(), &Outcome::PatternError(e)),
+        }
+    }
+
+    /// Searches the bytes the string spells out in `encoding`.
+    fn decode_bytes(&self, encoding: Encoding) {
+        match encoding.decode(&self.string_inp.text()) {
+            Ok(bytes) => self.search_bytes(bytes, &format!("{} from the string", encoding)),
+            Err(e) => {
+                simple_message("Error", &format!("Unable to read {}: {}", encoding, e));
+            }
+        }
+    }
+
+    /// Searches the bytes of a file, which need not be text.
+    fn open_bytes(&self) {
+        if !self.bytes_dialog.run(Some(&self.window)) {
+            return;
+        }
+        if let Ok(item) = self.bytes_dialog.get_selected_item() {
+            match fs::read(&item) {
+                Ok(bytes) => self.search_bytes(bytes, &format!("file {}", item)),
+                Err(e) => {
+                    simple_message("Error", &format!("Unable to load binary file: {}", e));
                 }
             }
         }
-        self.output_tb.set_text(&results);
-        self.hist.add(Execution::new(
-            "split",
-            &self.pattern_inp.text(),
-            &self.string_inp.text(),
-        ));
+    }
+
+    /// Times the last operation on the current input and shows the
+    /// statistics in place of the result.
+    fn bench(&self) {
+        let eval = self.evaluation(self.last_op.get().unwrap_or(Operation::Match));
+        let runs = self.runs_inp.text().parse().unwrap_or(DEFAULT_RUNS);
+        match Benchmark::run(&eval, runs) {
+            Ok(b) => {
+                *self.last.borrow_mut() = None;
+                self.highlights.borrow_mut().1.clear();
+                self.output_tb
+                    .set_text(&b.to_string().replace('\n', "\r\n"));
+            }
+            Err(e) => self.show(&eval, &Outcome::PatternError(e)),
+        }
+    }
+
+    /// Copies the result shown, as JSON, to the clipboard.
+    fn copy_json(&self) {
+        if let Some((eval, outcome)) = &*self.last.borrow() {
+            nwg::Clipboard::set_data_text(&self.window, &Format::Json.render(eval, outcome));
+        }
     }
 
     fn history(&self) {
-        self.output_tb.set_text(&self.hist.to_str());
+        self.output_tb.set_text(&self.hist.to_str("\r\n"));
     }
 
     fn load_history(&self) {
         match self.hist.load_from() {
-            Err(e) => {simple_message("Error loading history", &format!("error loading history: {}", e));},
-            _ => {},
+            Err(e) => {
+                simple_message(
+                    "Error loading history",
+                    &format!("error loading history: {}", e),
+                );
+            }
+            _ => {}
         }
     }
 }
@@ -183,7 +664,9 @@ mod retester_app_ui {
     use super::*;
     use native_windows_gui as nwg;
     use nwg::stretch::style::AlignContent;
-    use nwg::{ButtonFlags, Font, ImageDecoder, TextBoxFlags, TextInputFlags, VTextAlign};
+    use nwg::{
+        ButtonFlags, Font, ImageDecoder, RichTextBoxFlags, TextBoxFlags, TextInputFlags, VTextAlign,
+    };
     use std::cell::RefCell;
     use std::ops::{BitAnd, BitXorAssign, Deref};
     use std::rc::Rc;
@@ -204,13 +687,21 @@ mod retester_app_ui {
             //     Ok(i) => Some(i),
             // };
             // let im2 = Some(&im.unwrap());
-        
-            Font::builder().family("Courier New").size_absolute(14).build(&mut data.font_io);
+
+            Font::builder()
+                .family("Courier New")
+                .size_absolute(14)
+                .build(&mut data.font_io);
             // Controls
             nwg::Window::builder()
                 .icon(Some(&nwg::Icon::from_bin(icon_bytes).unwrap()))
-                .flags(nwg::WindowFlags::WINDOW | nwg::WindowFlags::VISIBLE | nwg::WindowFlags::MAIN_WINDOW | nwg::WindowFlags::RESIZABLE)
-                .size((500, 300))
+                .flags(
+                    nwg::WindowFlags::WINDOW
+                        | nwg::WindowFlags::VISIBLE
+                        | nwg::WindowFlags::MAIN_WINDOW
+                        | nwg::WindowFlags::RESIZABLE,
+                )
+                .size((600, 535))
                 .position((300, 300))
                 .title("Regular Expression Tester")
                 .build(&mut data.window)?;
@@ -221,13 +712,16 @@ mod retester_app_ui {
                 // .focus(fa)
                 .build(&mut data.pattern_lb)?;
 
-            nwg::TextInput::builder()
-                // .text("RE goes here")
+            nwg::TextBox::builder()
                 .parent(&data.window)
                 .font(Some(&data.font_io))
                 .focus(true)
-                // .flags(TextInputFlags::TAB_STOP|TextInputFlags::VISIBLE|TextInputFlags::)
-                .placeholder_text(Some("Regex goes here"))
+                .flags(
+                    TextBoxFlags::VISIBLE
+                        | TextBoxFlags::TAB_STOP
+                        | TextBoxFlags::AUTOVSCROLL
+                        | TextBoxFlags::VSCROLL,
+                )
                 .build(&mut data.pattern_inp)?;
 
             nwg::Label::builder()
@@ -236,15 +730,229 @@ mod retester_app_ui {
                 // .focus(fa)
                 .build(&mut data.string_lb)?;
 
-            nwg::TextInput::builder()
-                // .text("Test String goes here")
+            nwg::RichTextBox::builder()
                 .parent(&data.window)
                 .font(Some(&data.font_io))
-                // .focus(true)
-                // .flags(TextInputFlags::TAB_STOP|TextInputFlags::VISIBLE)
-                .placeholder_text(Some("Test string goes here"))
+                .flags(
+                    RichTextBoxFlags::VISIBLE
+                        | RichTextBoxFlags::TAB_STOP
+                        | RichTextBoxFlags::AUTOVSCROLL
+                        | RichTextBoxFlags::VSCROLL,
+                )
                 .build(&mut data.string_inp)?;
 
+            nwg::Label::builder()
+                .text("Replace")
+                .parent(&data.window)
+                .build(&mut data.replace_lb)?;
+
+            nwg::TextInput::builder()
+                .parent(&data.window)
+                .font(Some(&data.font_io))
+                .placeholder_text(Some("Replacement ($1, ${name}, $$) goes here"))
+                .build(&mut data.replace_inp)?;
+
+            nwg::Label::builder()
+                .text("File")
+                .parent(&data.window)
+                .build(&mut data.file_lb)?;
+
+            nwg::Button::builder()
+                .text("&Open...")
+                .parent(&data.window)
+                .build(&mut data.open_bt)?;
+
+            nwg::Label::builder()
+                .text("")
+                .parent(&data.window)
+                .build(&mut data.file_name_lb)?;
+
+            nwg::Button::builder()
+                .text("<")
+                .parent(&data.window)
+                .build(&mut data.prev_bt)?;
+
+            nwg::Button::builder()
+                .text(">")
+                .parent(&data.window)
+                .build(&mut data.next_bt)?;
+
+            nwg::FileDialog::builder()
+                .title("Open test file")
+                .action(nwg::FileDialogAction::Open)
+                .build(&mut data.file_dialog)?;
+
+            nwg::Label::builder()
+                .text("Suite")
+                .parent(&data.window)
+                .build(&mut data.suite_lb)?;
+
+            nwg::Button::builder()
+                .text("Loa&d...")
+                .parent(&data.window)
+                .build(&mut data.load_suite_bt)?;
+
+            nwg::Button::builder()
+                .text("Sa&ve...")
+                .parent(&data.window)
+                .build(&mut data.save_suite_bt)?;
+
+            nwg::TextInput::builder()
+                .parent(&data.window)
+                .placeholder_text(Some("case name"))
+                .build(&mut data.case_name_inp)?;
+
+            nwg::Button::builder()
+                .text("&Add case")
+                .parent(&data.window)
+                .build(&mut data.add_case_bt)?;
+
+            nwg::Button::builder()
+                .text("&Test")
+                .parent(&data.window)
+                .build(&mut data.run_suite_bt)?;
+
+            nwg::Button::builder()
+                .text("R&ust")
+                .parent(&data.window)
+                .build(&mut data.rust_bt)?;
+
+            nwg::Label::builder()
+                .text("0 cases")
+                .parent(&data.window)
+                .build(&mut data.cases_lb)?;
+
+            nwg::FileDialog::builder()
+                .title("Load test suite")
+                .action(nwg::FileDialogAction::Open)
+                .filters("Test suite(*.json)|Any(*.*)")
+                .build(&mut data.load_suite_dialog)?;
+
+            nwg::FileDialog::builder()
+                .title("Save test suite")
+                .action(nwg::FileDialogAction::Save)
+                .filters("Test suite(*.json)|Any(*.*)")
+                .build(&mut data.save_suite_dialog)?;
+
+            nwg::Label::builder()
+                .text("Code")
+                .parent(&data.window)
+                .build(&mut data.code_lb)?;
+
+            let code_buttons = [
+                (Language::Rust, &mut data.rust_code_bt),
+                (Language::Python, &mut data.python_code_bt),
+                (Language::Go, &mut data.go_code_bt),
+                (Language::Java, &mut data.java_code_bt),
+            ];
+            for (language, bt) in code_buttons.iter_mut() {
+                nwg::Button::builder()
+                    .text(language.title())
+                    .parent(&data.window)
+                    .build(bt)?;
+            }
+
+            nwg::Label::builder()
+                .text("Bytes")
+                .parent(&data.window)
+                .build(&mut data.bytes_lb)?;
+
+            let bytes_buttons = [
+                (Encoding::Hex, &mut data.hex_bt),
+                (Encoding::Base64, &mut data.base64_bt),
+            ];
+            for (encoding, bt) in bytes_buttons.iter_mut() {
+                nwg::Button::builder()
+                    .text(encoding.title())
+                    .parent(&data.window)
+                    .build(bt)?;
+            }
+
+            nwg::Button::builder()
+                .text("File...")
+                .parent(&data.window)
+                .build(&mut data.bytes_file_bt)?;
+
+            nwg::FileDialog::builder()
+                .title("Open binary file")
+                .action(nwg::FileDialogAction::Open)
+                .build(&mut data.bytes_dialog)?;
+
+            nwg::Label::builder()
+                .text("Pattern B")
+                .parent(&data.window)
+                .build(&mut data.pattern_b_lb)?;
+
+            nwg::TextInput::builder()
+                .parent(&data.window)
+                .font(Some(&data.font_io))
+                .placeholder_text(Some("pattern to compare with"))
+                .build(&mut data.pattern_b_inp)?;
+
+            nwg::Button::builder()
+                .text("Com&pare")
+                .parent(&data.window)
+                .build(&mut data.compare_bt)?;
+
+            nwg::Label::builder()
+                .text("Flags")
+                .parent(&data.window)
+                .build(&mut data.flags_lb)?;
+
+            let flag_boxes = [
+                ("i", CheckBoxState::Unchecked, &mut data.case_insensitive_cb),
+                ("m", CheckBoxState::Unchecked, &mut data.multi_line_cb),
+                (
+                    "s",
+                    CheckBoxState::Unchecked,
+                    &mut data.dot_matches_new_line_cb,
+                ),
+                ("U", CheckBoxState::Unchecked, &mut data.swap_greed_cb),
+                (
+                    "x",
+                    CheckBoxState::Unchecked,
+                    &mut data.ignore_whitespace_cb,
+                ),
+                ("u", CheckBoxState::Checked, &mut data.unicode_cb),
+            ];
+            for (text, state, cb) in flag_boxes.iter_mut() {
+                nwg::CheckBox::builder()
+                    .text(text)
+                    .check_state(*state)
+                    .parent(&data.window)
+                    .build(cb)?;
+            }
+
+            nwg::Label::builder()
+                .text("")
+                .font(Some(&data.font_io))
+                .parent(&data.window)
+                .build(&mut data.inline_flags_lb)?;
+
+            nwg::Label::builder()
+                .text("Limits")
+                .parent(&data.window)
+                .build(&mut data.limits_lb)?;
+
+            let d = Limits::default();
+            let limit_inputs = [
+                (d.size_limit.to_string(), "size", &mut data.size_limit_inp),
+                (
+                    d.dfa_size_limit.to_string(),
+                    "dfa size",
+                    &mut data.dfa_size_limit_inp,
+                ),
+                (d.nest_limit.to_string(), "nest", &mut data.nest_limit_inp),
+            ];
+            for (text, placeholder, inp) in limit_inputs.iter_mut() {
+                nwg::TextInput::builder()
+                    .text(text)
+                    .placeholder_text(Some(*placeholder))
+                    .flags(TextInputFlags::VISIBLE | TextInputFlags::NUMBER)
+                    .font(Some(&data.font_io))
+                    .parent(&data.window)
+                    .build(inp)?;
+            }
 
             nwg::Button::builder()
                 .text("&Match")
@@ -258,19 +966,91 @@ mod retester_app_ui {
                 .parent(&data.window)
                 .build(&mut data.find_bt)?;
 
+            nwg::Button::builder()
+                .text("&Captures")
+                .flags(ButtonFlags::VISIBLE)
+                .parent(&data.window)
+                .build(&mut data.captures_bt)?;
+
             nwg::Button::builder()
                 .text("&Split")
                 .flags(ButtonFlags::VISIBLE)
                 .parent(&data.window)
                 .build(&mut data.split_bt)?;
 
+            nwg::Button::builder()
+                .text("&Replace")
+                .flags(ButtonFlags::VISIBLE)
+                .parent(&data.window)
+                .build(&mut data.replace_bt)?;
+
+            nwg::Button::builder()
+                .text("Li&nes")
+                .flags(ButtonFlags::VISIBLE)
+                .parent(&data.window)
+                .build(&mut data.lines_bt)?;
+
+            nwg::Button::builder()
+                .text("E&xplain")
+                .flags(ButtonFlags::VISIBLE)
+                .parent(&data.window)
+                .build(&mut data.explain_bt)?;
+
+            nwg::Button::builder()
+                .text("&Inspect")
+                .flags(ButtonFlags::VISIBLE)
+                .parent(&data.window)
+                .build(&mut data.inspect_bt)?;
+
+            nwg::Button::builder()
+                .text("S&et")
+                .flags(ButtonFlags::VISIBLE)
+                .parent(&data.window)
+                .build(&mut data.set_bt)?;
+
             nwg::Button::builder()
                 .text("&History")
                 .flags(ButtonFlags::VISIBLE)
                 .parent(&data.window)
                 .build(&mut data.history_bt)?;
 
-                // use winapi::um::winuser::{WS_DISABLED, BS_ICON, BS_BITMAP, BS_NOTIFY, WS_VISIBLE, WS_TABSTOP, WS_CHILD};
+            nwg::Button::builder()
+                .text("&Bench")
+                .flags(ButtonFlags::VISIBLE)
+                .parent(&data.window)
+                .build(&mut data.bench_bt)?;
+
+            nwg::TextInput::builder()
+                .text(&DEFAULT_RUNS.to_string())
+                .placeholder_text(Some("runs"))
+                .flags(TextInputFlags::VISIBLE | TextInputFlags::NUMBER)
+                .font(Some(&data.font_io))
+                .parent(&data.window)
+                .build(&mut data.runs_inp)?;
+
+            nwg::Button::builder()
+                .text("&JSON")
+                .flags(ButtonFlags::VISIBLE)
+                .parent(&data.window)
+                .build(&mut data.json_bt)?;
+
+            nwg::CheckBox::builder()
+                .text("&Live")
+                .parent(&data.window)
+                .build(&mut data.live_cb)?;
+
+            nwg::Notice::builder()
+                .parent(&data.window)
+                .build(&mut data.live_notice)?;
+
+            let notice = data.live_notice.sender();
+            let slot = data.live_result.clone();
+            data.live = Some(Live::new(DEBOUNCE, move |eval, outcome| {
+                *slot.lock().unwrap() = Some((eval, outcome));
+                notice.notice();
+            }));
+
+            // use winapi::um::winuser::{WS_DISABLED, BS_ICON, BS_BITMAP, BS_NOTIFY, WS_VISIBLE, WS_TABSTOP, WS_CHILD};
 
             // for b in [data.match_bt, data.find_bt, data.split_bt].iter_mut() {
             //     let mut bits = b.flags();
@@ -278,13 +1058,12 @@ mod retester_app_ui {
             //     b.forced_flags();
             // }
 
-
-                use nwg::TextBoxFlags as TB;
+            use nwg::TextBoxFlags as TB;
             nwg::TextBox::builder()
                 .parent(&data.window)
                 .focus(true)
                 .font(Some(&data.font_io))
-                .flags(TB::VISIBLE|TB::AUTOHSCROLL|TB::AUTOVSCROLL|TB::VSCROLL)
+                .flags(TB::VISIBLE | TB::AUTOHSCROLL | TB::AUTOVSCROLL | TB::VSCROLL)
                 .readonly(true)
                 .build(&mut data.output_tb)?;
 
@@ -299,11 +1078,112 @@ mod retester_app_ui {
                             if &handle == &evt_ui.window {
                                 ReTesterApp::exit(&evt_ui);
                             }
-                        },
-                        E::OnButtonClick if &handle == &evt_ui.match_bt => ReTesterApp::_match(&evt_ui),
-                        E::OnButtonClick if &handle == &evt_ui.find_bt => ReTesterApp::find(&evt_ui),
-                        E::OnButtonClick if &handle == &evt_ui.split_bt => ReTesterApp::split(&evt_ui),
-                        E::OnButtonClick if &handle == &evt_ui.history_bt => ReTesterApp::history(&evt_ui),
+                        }
+                        E::OnButtonClick if &handle == &evt_ui.match_bt => {
+                            ReTesterApp::_match(&evt_ui)
+                        }
+                        E::OnButtonClick if &handle == &evt_ui.find_bt => {
+                            ReTesterApp::find(&evt_ui)
+                        }
+                        E::OnButtonClick if &handle == &evt_ui.captures_bt => {
+                            ReTesterApp::find_captures(&evt_ui)
+                        }
+                        E::OnButtonClick if &handle == &evt_ui.split_bt => {
+                            ReTesterApp::split(&evt_ui)
+                        }
+                        E::OnButtonClick if &handle == &evt_ui.replace_bt => {
+                            ReTesterApp::replace(&evt_ui)
+                        }
+                        E::OnButtonClick if &handle == &evt_ui.lines_bt => {
+                            ReTesterApp::lines(&evt_ui)
+                        }
+                        E::OnButtonClick if &handle == &evt_ui.explain_bt => {
+                            ReTesterApp::explain(&evt_ui)
+                        }
+                        E::OnButtonClick if &handle == &evt_ui.inspect_bt => {
+                            ReTesterApp::inspect(&evt_ui)
+                        }
+                        E::OnButtonClick if &handle == &evt_ui.set_bt => ReTesterApp::set(&evt_ui),
+                        E::OnButtonClick if &handle == &evt_ui.history_bt => {
+                            ReTesterApp::history(&evt_ui)
+                        }
+                        E::OnButtonClick if &handle == &evt_ui.bench_bt => {
+                            ReTesterApp::bench(&evt_ui)
+                        }
+                        E::OnButtonClick if &handle == &evt_ui.json_bt => {
+                            ReTesterApp::copy_json(&evt_ui)
+                        }
+                        E::OnButtonClick if &handle == &evt_ui.open_bt => {
+                            ReTesterApp::open_file(&evt_ui)
+                        }
+                        E::OnButtonClick if &handle == &evt_ui.prev_bt => {
+                            ReTesterApp::prev_page(&evt_ui)
+                        }
+                        E::OnButtonClick if &handle == &evt_ui.next_bt => {
+                            ReTesterApp::next_page(&evt_ui)
+                        }
+                        E::OnButtonClick if &handle == &evt_ui.load_suite_bt => {
+                            ReTesterApp::load_suite(&evt_ui)
+                        }
+                        E::OnButtonClick if &handle == &evt_ui.save_suite_bt => {
+                            ReTesterApp::save_suite(&evt_ui)
+                        }
+                        E::OnButtonClick if &handle == &evt_ui.add_case_bt => {
+                            ReTesterApp::add_case(&evt_ui)
+                        }
+                        E::OnButtonClick if &handle == &evt_ui.run_suite_bt => {
+                            ReTesterApp::run_suite(&evt_ui)
+                        }
+                        E::OnButtonClick if &handle == &evt_ui.compare_bt => {
+                            ReTesterApp::compare(&evt_ui)
+                        }
+                        E::OnButtonClick if &handle == &evt_ui.rust_bt => {
+                            ReTesterApp::copy_rust_tests(&evt_ui)
+                        }
+                        E::OnButtonClick if &handle == &evt_ui.rust_code_bt => {
+                            ReTesterApp::code(&evt_ui, Language::Rust)
+                        }
+                        E::OnButtonClick if &handle == &evt_ui.python_code_bt => {
+                            ReTesterApp::code(&evt_ui, Language::Python)
+                        }
+                        E::OnButtonClick if &handle == &evt_ui.go_code_bt => {
+                            ReTesterApp::code(&evt_ui, Language::Go)
+                        }
+                        E::OnButtonClick if &handle == &evt_ui.java_code_bt => {
+                            ReTesterApp::code(&evt_ui, Language::Java)
+                        }
+                        E::OnButtonClick if &handle == &evt_ui.hex_bt => {
+                            ReTesterApp::decode_bytes(&evt_ui, Encoding::Hex)
+                        }
+                        E::OnButtonClick if &handle == &evt_ui.base64_bt => {
+                            ReTesterApp::decode_bytes(&evt_ui, Encoding::Base64)
+                        }
+                        E::OnButtonClick if &handle == &evt_ui.bytes_file_bt => {
+                            ReTesterApp::open_bytes(&evt_ui)
+                        }
+                        E::OnButtonClick => {
+                            ReTesterApp::show_inline_flags(&evt_ui);
+                            ReTesterApp::live_update(&evt_ui);
+                        }
+                        E::OnTextInput if &handle == &evt_ui.string_inp => {
+                            ReTesterApp::clear_highlights(&evt_ui);
+                            ReTesterApp::live_update(&evt_ui);
+                        }
+                        E::OnTextInput
+                            if &handle != &evt_ui.output_tb
+                                && &handle != &evt_ui.case_name_inp
+                                && &handle != &evt_ui.pattern_b_inp =>
+                        {
+                            ReTesterApp::live_update(&evt_ui)
+                        }
+                        E::OnMousePress(nwg::MousePressEvent::MousePressLeftUp)
+                            if &handle == &evt_ui.string_inp =>
+                        {
+                            ReTesterApp::jump_to_result(&evt_ui)
+                        }
+                        E::OnNotice if &handle == &evt_ui.live_notice => {
+                            ReTesterApp::show_live_result(&evt_ui)
+                        }
                         _ => {}
                     }
                 }
@@ -322,6 +1202,8 @@ mod retester_app_ui {
             const LBL_WIDTH: D = D::Points(55.);
             const BUTTON_WIDTH: D = D::Points(65.);
             const HEIGHT_INP: D = D::Points(25.);
+            const HEIGHT_STRING: D = D::Points(80.);
+            const HEIGHT_PATTERN: D = D::Points(45.);
             const HEIGHT_BT: D = D::Points(25.);
             const PT_10: D = D::Points(5.0);
             const MIN_PAD: Rect<D> = Rect { start: PT_10, end: PT_10, top: PT_10, bottom: PT_10 };
@@ -331,10 +1213,9 @@ mod retester_app_ui {
                 .flex_direction(FlexDirection::Row)
                 .padding(MIN_PAD)
                 .child(&ui.pattern_lb)
-                
                 .child_size(Size { width: LBL_WIDTH, height: HEIGHT_INP })
                 .child(&ui.pattern_inp)
-                .child_size(Size { width: D::Auto, height: HEIGHT_INP })
+                .child_size(Size { width: D::Auto, height: HEIGHT_PATTERN })
                 .child_flex_grow(1.0)
                 .build_partial(&ui.layout1)?;
 
@@ -345,10 +1226,142 @@ mod retester_app_ui {
                 .child(&ui.string_lb)
                 .child_size(Size { width: LBL_WIDTH, height: HEIGHT_INP })
                 .child(&ui.string_inp)
-                .child_size(Size { width: D::Auto, height: HEIGHT_INP })
+                .child_size(Size { width: D::Auto, height: HEIGHT_STRING })
                 .child_flex_grow(1.0)
                 .build_partial(&ui.layout2)?;
 
+            nwg::FlexboxLayout::builder()
+                .parent(&ui.window)
+                .flex_direction(FlexDirection::Row)
+                .padding(MIN_PAD)
+                .child(&ui.replace_lb)
+                .child_size(Size { width: LBL_WIDTH, height: HEIGHT_INP })
+                .child(&ui.replace_inp)
+                .child_size(Size { width: D::Auto, height: HEIGHT_INP })
+                .child_flex_grow(1.0)
+                .build_partial(&ui.layout4)?;
+
+            nwg::FlexboxLayout::builder()
+                .parent(&ui.window)
+                .flex_direction(FlexDirection::Row)
+                .padding(MIN_PAD)
+                .child(&ui.file_lb)
+                .child_size(Size { width: LBL_WIDTH, height: HEIGHT_INP })
+                .child(&ui.open_bt)
+                .child_size(Size { width: BUTTON_WIDTH, height: HEIGHT_BT })
+                .child(&ui.file_name_lb)
+                .child_size(Size { width: D::Auto, height: HEIGHT_INP })
+                .child_flex_grow(1.0)
+                .child(&ui.prev_bt)
+                .child_size(Size { width: D::Points(30.), height: HEIGHT_BT })
+                .child(&ui.next_bt)
+                .child_size(Size { width: D::Points(30.), height: HEIGHT_BT })
+                .build_partial(&ui.layout7)?;
+
+            nwg::FlexboxLayout::builder()
+                .parent(&ui.window)
+                .flex_direction(FlexDirection::Row)
+                .padding(MIN_PAD)
+                .child(&ui.suite_lb)
+                .child_size(Size { width: LBL_WIDTH, height: HEIGHT_INP })
+                .child(&ui.load_suite_bt)
+                .child_size(Size { width: BUTTON_WIDTH, height: HEIGHT_BT })
+                .child(&ui.save_suite_bt)
+                .child_size(Size { width: BUTTON_WIDTH, height: HEIGHT_BT })
+                .child(&ui.case_name_inp)
+                .child_size(Size { width: D::Points(120.), height: HEIGHT_INP })
+                .child(&ui.add_case_bt)
+                .child_size(Size { width: BUTTON_WIDTH, height: HEIGHT_BT })
+                .child(&ui.run_suite_bt)
+                .child_size(Size { width: BUTTON_WIDTH, height: HEIGHT_BT })
+                .child(&ui.rust_bt)
+                .child_size(Size { width: BUTTON_WIDTH, height: HEIGHT_BT })
+                .child(&ui.cases_lb)
+                .child_size(Size { width: D::Auto, height: HEIGHT_INP })
+                .child_flex_grow(1.0)
+                .build_partial(&ui.layout8)?;
+
+            nwg::FlexboxLayout::builder()
+                .parent(&ui.window)
+                .flex_direction(FlexDirection::Row)
+                .padding(MIN_PAD)
+                .child(&ui.code_lb)
+                .child_size(Size { width: LBL_WIDTH, height: HEIGHT_INP })
+                .child(&ui.rust_code_bt)
+                .child_size(Size { width: BUTTON_WIDTH, height: HEIGHT_BT })
+                .child(&ui.python_code_bt)
+                .child_size(Size { width: BUTTON_WIDTH, height: HEIGHT_BT })
+                .child(&ui.go_code_bt)
+                .child_size(Size { width: BUTTON_WIDTH, height: HEIGHT_BT })
+                .child(&ui.java_code_bt)
+                .child_size(Size { width: BUTTON_WIDTH, height: HEIGHT_BT })
+                .build_partial(&ui.layout9)?;
+
+            nwg::FlexboxLayout::builder()
+                .parent(&ui.window)
+                .flex_direction(FlexDirection::Row)
+                .padding(MIN_PAD)
+                .child(&ui.bytes_lb)
+                .child_size(Size { width: LBL_WIDTH, height: HEIGHT_INP })
+                .child(&ui.hex_bt)
+                .child_size(Size { width: BUTTON_WIDTH, height: HEIGHT_BT })
+                .child(&ui.base64_bt)
+                .child_size(Size { width: BUTTON_WIDTH, height: HEIGHT_BT })
+                .child(&ui.bytes_file_bt)
+                .child_size(Size { width: BUTTON_WIDTH, height: HEIGHT_BT })
+                .build_partial(&ui.layout11)?;
+
+            nwg::FlexboxLayout::builder()
+                .parent(&ui.window)
+                .flex_direction(FlexDirection::Row)
+                .padding(MIN_PAD)
+                .child(&ui.pattern_b_lb)
+                .child_size(Size { width: LBL_WIDTH, height: HEIGHT_INP })
+                .child(&ui.pattern_b_inp)
+                .child_size(Size { width: D::Auto, height: HEIGHT_INP })
+                .child_flex_grow(1.0)
+                .child(&ui.compare_bt)
+                .child_size(Size { width: BUTTON_WIDTH, height: HEIGHT_BT })
+                .build_partial(&ui.layout10)?;
+
+            const CB_WIDTH: D = D::Points(35.);
+            nwg::FlexboxLayout::builder()
+                .parent(&ui.window)
+                .flex_direction(FlexDirection::Row)
+                .padding(MIN_PAD)
+                .child(&ui.flags_lb)
+                .child_size(Size { width: LBL_WIDTH, height: HEIGHT_INP })
+                .child(&ui.case_insensitive_cb)
+                .child_size(Size { width: CB_WIDTH, height: HEIGHT_INP })
+                .child(&ui.multi_line_cb)
+                .child_size(Size { width: CB_WIDTH, height: HEIGHT_INP })
+                .child(&ui.dot_matches_new_line_cb)
+                .child_size(Size { width: CB_WIDTH, height: HEIGHT_INP })
+                .child(&ui.swap_greed_cb)
+                .child_size(Size { width: CB_WIDTH, height: HEIGHT_INP })
+                .child(&ui.ignore_whitespace_cb)
+                .child_size(Size { width: CB_WIDTH, height: HEIGHT_INP })
+                .child(&ui.unicode_cb)
+                .child_size(Size { width: CB_WIDTH, height: HEIGHT_INP })
+                .child(&ui.inline_flags_lb)
+                .child_size(Size { width: D::Auto, height: HEIGHT_INP })
+                .child_flex_grow(1.0)
+                .build_partial(&ui.layout5)?;
+
+            nwg::FlexboxLayout::builder()
+                .parent(&ui.window)
+                .flex_direction(FlexDirection::Row)
+                .padding(MIN_PAD)
+                .child(&ui.limits_lb)
+                .child_size(Size { width: LBL_WIDTH, height: HEIGHT_INP })
+                .child(&ui.size_limit_inp)
+                .child_size(Size { width: D::Points(90.), height: HEIGHT_INP })
+                .child(&ui.dfa_size_limit_inp)
+                .child_size(Size { width: D::Points(90.), height: HEIGHT_INP })
+                .child(&ui.nest_limit_inp)
+                .child_size(Size { width: D::Points(50.), height: HEIGHT_INP })
+                .build_partial(&ui.layout6)?;
+
             nwg::FlexboxLayout::builder()
                 .parent(&ui.window)
                 .flex_direction(FlexDirection::Row)
@@ -358,23 +1371,69 @@ mod retester_app_ui {
                 .child_size(Size { width: BUTTON_WIDTH, height: HEIGHT_BT })
                 .child(&ui.find_bt)
                 .child_size(Size { width: BUTTON_WIDTH, height: HEIGHT_BT })
+                .child(&ui.captures_bt)
+                .child_size(Size { width: BUTTON_WIDTH, height: HEIGHT_BT })
                 .child(&ui.split_bt)
                 .child_size(Size { width: BUTTON_WIDTH, height: HEIGHT_BT })
+                .child(&ui.replace_bt)
+                .child_size(Size { width: BUTTON_WIDTH, height: HEIGHT_BT })
+                .child(&ui.lines_bt)
+                .child_size(Size { width: BUTTON_WIDTH, height: HEIGHT_BT })
+                .child(&ui.explain_bt)
+                .child_size(Size { width: BUTTON_WIDTH, height: HEIGHT_BT })
+                .build_partial(&ui.layout3)?;
+
+            // the rest of the buttons do not fit on one row with the operations
+            nwg::FlexboxLayout::builder()
+                .parent(&ui.window)
+                .flex_direction(FlexDirection::Row)
+                .auto_spacing(None)
+                .padding(MIN_PAD)
+                .child(&ui.inspect_bt)
+                .child_size(Size { width: BUTTON_WIDTH, height: HEIGHT_BT })
+                .child(&ui.set_bt)
+                .child_size(Size { width: BUTTON_WIDTH, height: HEIGHT_BT })
                 .child(&ui.history_bt)
                 .child_size(Size { width: BUTTON_WIDTH, height: HEIGHT_BT })
+                .child(&ui.bench_bt)
+                .child_size(Size { width: BUTTON_WIDTH, height: HEIGHT_BT })
+                .child(&ui.runs_inp)
+                .child_size(Size { width: D::Points(50.), height: HEIGHT_INP })
+                .child(&ui.json_bt)
+                .child_size(Size { width: BUTTON_WIDTH, height: HEIGHT_BT })
+                .child(&ui.live_cb)
+                .child_size(Size { width: BUTTON_WIDTH, height: HEIGHT_BT })
                 // .child_flex_grow(1.0)
-                .build_partial(&ui.layout3)?;
+                .build_partial(&ui.layout12)?;
 
             nwg::FlexboxLayout::builder()
                 .parent(&ui.window)
-                .min_size(Size{width: D::Points(300.), height: D::Points(200.)})
+                .min_size(Size { width: D::Points(300.), height: D::Points(200.) })
                 .flex_direction(FlexDirection::Column)
                 .child_layout(&ui.layout1)
                 .child_flex_grow(0.)
                 .child_layout(&ui.layout2)
                 .child_flex_grow(0.)
+                .child_layout(&ui.layout7)
+                .child_flex_grow(0.)
+                .child_layout(&ui.layout8)
+                .child_flex_grow(0.)
+                .child_layout(&ui.layout9)
+                .child_flex_grow(0.)
+                .child_layout(&ui.layout11)
+                .child_flex_grow(0.)
+                .child_layout(&ui.layout10)
+                .child_flex_grow(0.)
+                .child_layout(&ui.layout4)
+                .child_flex_grow(0.)
+                .child_layout(&ui.layout5)
+                .child_flex_grow(0.)
+                .child_layout(&ui.layout6)
+                .child_flex_grow(0.)
                 .child_layout(&ui.layout3)
                 .child_flex_grow(0.)
+                .child_layout(&ui.layout12)
+                .child_flex_grow(0.)
                 .child(&ui.output_tb)
                 .child_flex_grow(1.)
                 .child_margin(MIN_PAD)
@@ -410,7 +1469,6 @@ mod retester_app_ui {
 fn main() {
     nwg::init().expect("Failed to init Native Windows GUI");
     nwg::Font::set_global_family("Segoe UI").expect("Failed to set default font");
-    
 
     let _ui = ReTesterApp::build_ui(Default::default()).expect("Failed to build UI");
 
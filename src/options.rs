@@ -0,0 +1,205 @@
+use regex::{bytes, RegexBuilder, RegexSetBuilder};
+use serde::{Deserialize, Serialize};
+
+/// The `RegexBuilder` flags the frontends expose as toggles.  Each one has an
+/// inline equivalent, shown by [`Flags::inline`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Flags {
+    /// `i`
+    pub case_insensitive: bool,
+    /// `m`: `^` and `$` match at line boundaries.
+    pub multi_line: bool,
+    /// `s`: `.` also matches `\n`.
+    pub dot_matches_new_line: bool,
+    /// `U`: `a*` is lazy and `a*?` greedy.
+    pub swap_greed: bool,
+    /// `x`: whitespace and `#` comments in the pattern are ignored.
+    pub ignore_whitespace: bool,
+    /// `u`: on by default, so only shown when turned off.
+    pub unicode: bool,
+}
+
+impl Default for Flags {
+    fn default() -> Self {
+        Flags {
+            case_insensitive: false,
+            multi_line: false,
+            dot_matches_new_line: false,
+            swap_greed: false,
+            ignore_whitespace: false,
+            unicode: true,
+        }
+    }
+}
+
+impl Flags {
+    /// The inline group that sets these flags, e.g. `(?im)` or `(?s-u)`, or
+    /// an empty string when every flag has its default value.  Prefixing the
+    /// pattern with it gives a pattern that behaves the same under a plain
+    /// `Regex::new`.
+    pub fn inline(&self) -> String {
+        let mut on = String::new();
+        for (set, c) in [
+            (self.case_insensitive, 'i'),
+            (self.multi_line, 'm'),
+            (self.dot_matches_new_line, 's'),
+            (self.swap_greed, 'U'),
+            (self.ignore_whitespace, 'x'),
+        ]
+        .iter()
+        {
+            if *set {
+                on.push(*c);
+            }
+        }
+        let off = if self.unicode { "" } else { "-u" };
+        if on.is_empty() && off.is_empty() {
+            String::new()
+        } else {
+            format!("(?{}{})", on, off)
+        }
+    }
+
+    pub fn builder(&self, pattern: &str) -> RegexBuilder {
+        let mut b = RegexBuilder::new(pattern);
+        b.case_insensitive(self.case_insensitive)
+            .multi_line(self.multi_line)
+            .dot_matches_new_line(self.dot_matches_new_line)
+            .swap_greed(self.swap_greed)
+            .ignore_whitespace(self.ignore_whitespace)
+            .unicode(self.unicode);
+        b
+    }
+
+    /// Like [`Flags::builder`], for a `RegexSet` of `patterns`.
+    pub fn set_builder(&self, patterns: &[&str]) -> RegexSetBuilder {
+        let mut b = RegexSetBuilder::new(patterns);
+        b.case_insensitive(self.case_insensitive)
+            .multi_line(self.multi_line)
+            .dot_matches_new_line(self.dot_matches_new_line)
+            .swap_greed(self.swap_greed)
+            .ignore_whitespace(self.ignore_whitespace)
+            .unicode(self.unicode);
+        b
+    }
+
+    /// Like [`Flags::builder`], for a `regex::bytes::Regex`.  With the `u`
+    /// flag off such a pattern may match bytes that are not UTF-8.
+    pub fn bytes_builder(&self, pattern: &str) -> bytes::RegexBuilder {
+        let mut b = bytes::RegexBuilder::new(pattern);
+        b.case_insensitive(self.case_insensitive)
+            .multi_line(self.multi_line)
+            .dot_matches_new_line(self.dot_matches_new_line)
+            .swap_greed(self.swap_greed)
+            .ignore_whitespace(self.ignore_whitespace)
+            .unicode(self.unicode);
+        b
+    }
+
+    /// A `regex-syntax` parser builder configured the same way as
+    /// [`Flags::builder`], for looking inside a pattern.
+    pub fn parser(&self) -> regex_syntax::ParserBuilder {
+        let mut p = regex_syntax::ParserBuilder::new();
+        p.case_insensitive(self.case_insensitive)
+            .multi_line(self.multi_line)
+            .dot_matches_new_line(self.dot_matches_new_line)
+            .swap_greed(self.swap_greed)
+            .ignore_whitespace(self.ignore_whitespace)
+            .unicode(self.unicode);
+        p
+    }
+
+    /// The same configuration for the parser `regex-automata` brings along.
+    pub fn syntax(&self) -> regex_automata::util::syntax::Config {
+        regex_automata::util::syntax::Config::new()
+            .case_insensitive(self.case_insensitive)
+            .multi_line(self.multi_line)
+            .dot_matches_new_line(self.dot_matches_new_line)
+            .swap_greed(self.swap_greed)
+            .ignore_whitespace(self.ignore_whitespace)
+            .unicode(self.unicode)
+    }
+}
+
+/// The compile time resource limits of `RegexBuilder`.  The defaults are the
+/// ones `Regex::new` uses.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Limits {
+    /// Upper bound in bytes on the size of the compiled program.
+    pub size_limit: usize,
+    /// Upper bound in bytes on the lazy DFA's cache.  Exceeding it makes
+    /// searches fall back to slower engines rather than fail.
+    pub dfa_size_limit: usize,
+    /// How deeply groups and repetitions may nest.
+    pub nest_limit: u32,
+}
+
+impl Default for Limits {
+    fn default() -> Self {
+        Limits { size_limit: 10 * (1 << 20), dfa_size_limit: 2 * (1 << 20), nest_limit: 250 }
+    }
+}
+
+impl Limits {
+    pub fn apply<'a>(&self, b: &'a mut RegexBuilder) -> &'a mut RegexBuilder {
+        b.size_limit(self.size_limit)
+            .dfa_size_limit(self.dfa_size_limit)
+            .nest_limit(self.nest_limit)
+    }
+
+    pub fn apply_bytes<'a>(&self, b: &'a mut bytes::RegexBuilder) -> &'a mut bytes::RegexBuilder {
+        b.size_limit(self.size_limit)
+            .dfa_size_limit(self.dfa_size_limit)
+            .nest_limit(self.nest_limit)
+    }
+
+    pub fn apply_set<'a>(&self, b: &'a mut RegexSetBuilder) -> &'a mut RegexSetBuilder {
+        b.size_limit(self.size_limit)
+            .dfa_size_limit(self.dfa_size_limit)
+            .nest_limit(self.nest_limit)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn default_flags_need_nothing_inline() {
+        assert_eq!(Flags::default().inline(), "");
+    }
+
+    #[test]
+    fn inline_flags_list_what_is_on_and_unicode_when_off() {
+        let mut f = Flags { case_insensitive: true, multi_line: true, ..Flags::default() };
+        assert_eq!(f.inline(), "(?im)");
+        f = Flags { unicode: false, ..Flags::default() };
+        assert_eq!(f.inline(), "(?-u)");
+        f = Flags {
+            case_insensitive: true,
+            multi_line: true,
+            dot_matches_new_line: true,
+            swap_greed: true,
+            ignore_whitespace: true,
+            unicode: false,
+        };
+        assert_eq!(f.inline(), "(?imsUx-u)");
+    }
+
+    #[test]
+    fn inline_flags_behave_as_the_builder_does() {
+        let f = Flags { case_insensitive: true, swap_greed: true, ..Flags::default() };
+        let pattern = "a+";
+        let built = f.builder(pattern).build().unwrap();
+        let inline = regex::Regex::new(&format!("{}{}", f.inline(), pattern)).unwrap();
+        for text in ["AAA", "baa"].iter() {
+            assert_eq!(
+                built.find(text).map(|m| m.range()),
+                inline.find(text).map(|m| m.range())
+            );
+        }
+        assert_eq!(inline.find("AAA").map(|m| m.range()), Some(0..1));
+    }
+}
@@ -0,0 +1,629 @@
+use std::ops::Range;
+
+use serde::Serialize;
+
+use crate::{
+    eval::{Evaluation, Operation},
+    result::{Inspection, Match, Node, Outcome, SetMatches, Span},
+};
+
+/// The ways an [`Outcome`] can be shown.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    /// Text with `\n` line endings, for FLTK and druid.
+    Plain,
+    /// Text with `\r\n` line endings, which the Windows `TextBox` needs.
+    /// Line endings inside the pattern and input are converted too.
+    Crlf,
+    /// The evaluation and its outcome as pretty printed JSON.
+    Json,
+}
+
+/// How many result rows the frontends show at a time.
+pub const PAGE_ROWS: usize = 1000;
+
+/// How much of a long input or replaced text the headers quote.
+const PREVIEW_BYTES: usize = 1024;
+
+/// The rows of a result that text output lists, so that a file with
+/// thousands of matches can be shown a screenful at a time.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Page {
+    pub start: usize,
+    pub len: usize,
+}
+
+impl Page {
+    pub fn all() -> Self {
+        Page { start: 0, len: usize::MAX }
+    }
+
+    pub fn first(len: usize) -> Self {
+        Page { start: 0, len }
+    }
+
+    /// The page after this one, or this one when it already reaches `total`.
+    pub fn next(&self, total: usize) -> Self {
+        match self.start.checked_add(self.len) {
+            Some(start) if start < total => Page { start, ..*self },
+            _ => *self,
+        }
+    }
+
+    pub fn prev(&self) -> Self {
+        Page { start: self.start.saturating_sub(self.len), ..*self }
+    }
+
+    /// The rows shown when there are `total` of them.
+    pub fn range(&self, total: usize) -> Range<usize> {
+        self.start.min(total)..self.start.saturating_add(self.len).min(total)
+    }
+}
+
+#[derive(Serialize)]
+struct Report<'a> {
+    #[serde(flatten)]
+    evaluation: &'a Evaluation,
+    outcome: &'a Outcome,
+}
+
+impl Format {
+    pub fn render(&self, eval: &Evaluation, outcome: &Outcome) -> String {
+        self.render_rows(eval, outcome).0
+    }
+
+    /// Renders like [`Format::render`], also returning the line of the output
+    /// each result row starts on, indexed by [`Highlight::row`].  JSON output
+    /// has no rows.
+    ///
+    /// [`Highlight::row`]: crate::result::Highlight::row
+    pub fn render_rows(&self, eval: &Evaluation, outcome: &Outcome) -> (String, Vec<usize>) {
+        self.render_page(eval, outcome, Page::all())
+    }
+
+    /// Renders like [`Format::render_rows`] but lists only the rows of `page`,
+    /// so the returned lines are indexed from `page.start`.  JSON output
+    /// always holds every row.
+    pub fn render_page(
+        &self,
+        eval: &Evaluation,
+        outcome: &Outcome,
+        page: Page,
+    ) -> (String, Vec<usize>) {
+        match self {
+            Format::Plain => text(eval, outcome, page),
+            Format::Crlf => {
+                let (s, rows) = text(eval, outcome, page);
+                (s.replace("\r\n", "\n").replace('\n', "\r\n"), rows)
+            }
+            Format::Json => {
+                let report = Report { evaluation: eval, outcome };
+                let s = serde_json::to_string_pretty(&report).expect("results always serialize");
+                (s, vec![])
+            }
+        }
+    }
+}
+
+/// The line the next push to `s` starts on.
+fn line_of(s: &str) -> usize {
+    s.matches('\n').count()
+}
+
+/// `s` in quotes, cut short when it is long.
+fn quoted(s: &str) -> String {
+    if s.len() <= PREVIEW_BYTES {
+        return format!("\"{}\"", s);
+    }
+    let mut end = PREVIEW_BYTES;
+    while !s.is_char_boundary(end) {
+        end -= 1;
+    }
+    format!("\"{}\"... ({} bytes in all)", &s[..end], s.len())
+}
+
+/// What the headers call the text searched.
+fn subject(eval: &Evaluation) -> String {
+    match &eval.file {
+        Some(f) => f.to_string(),
+        None => quoted(&eval.string),
+    }
+}
+
+fn text(eval: &Evaluation, outcome: &Outcome, page: Page) -> (String, Vec<usize>) {
+    let mut s = String::with_capacity(128);
+    let mut rows = vec![];
+    let multi_line = eval.haystack().contains('\n');
+    let shown = page.range(outcome.rows());
+    if let Outcome::PatternError(e) = outcome {
+        match &e.span {
+            Some(span) => {
+                s.push_str(&format!("Error with pattern: {}\n\n", e.kind));
+                caret_diagram(&mut s, &eval.pattern, span);
+            }
+            None => s.push_str(&format!("Error with pattern: {}", e.message)),
+        }
+        if let Some(size) = e.required_size_limit {
+            s.push_str(&format!(
+                "\n\nIt compiles with a size_limit of {} bytes or more",
+                size,
+            ));
+        }
+        return (s, rows);
+    }
+    match eval.operation {
+        Operation::Match => match outcome {
+            Outcome::Matched(m) => {
+                s.push_str(&format!(
+                    "Matching: \"{}\"\nAgainst: {}\n\n",
+                    eval.effective_pattern(),
+                    subject(eval),
+                ));
+                for g in m.groups[shown.clone()].iter() {
+                    rows.push(line_of(&s));
+                    match &g.text {
+                        Some(t) => s.push_str(&format!("{} = \"{}\"\n", g.label(), t)),
+                        None => s.push_str(&format!("{} = None\n", g.label())),
+                    }
+                }
+            }
+            _ => s.push_str(&format!(
+                "String:\n{}\nDoes not match Pattern:\n\"{}\"",
+                subject(eval),
+                eval.effective_pattern(),
+            )),
+        },
+        Operation::Find => {
+            s.push_str(&format!(
+                "Find pattern:\n\"{}\"\nIn:\n{}\n\n",
+                eval.effective_pattern(),
+                subject(eval),
+            ));
+            match outcome {
+                Outcome::Found { matches: v, .. } if !v.is_empty() => {
+                    for (i, m) in v.iter().enumerate().skip(shown.start).take(shown.len()) {
+                        rows.push(line_of(&s));
+                        s.push_str(&format!(
+                            "Iteration {} found \"{}\" at {}\n",
+                            i,
+                            m.text,
+                            at(&m.span, multi_line)
+                        ));
+                    }
+                }
+                _ => s.push_str("Found nothing"),
+            }
+        }
+        Operation::FindCaptures => {
+            s.push_str(&format!(
+                "Find captures of pattern:\n\"{}\"\nIn:\n{}\n\n",
+                eval.effective_pattern(),
+                subject(eval),
+            ));
+            match outcome {
+                Outcome::Found { matches: v, .. } if !v.is_empty() => {
+                    let keyed = v[shown.clone()]
+                        .iter()
+                        .zip(shown.clone())
+                        .map(|(m, i)| (i.to_string(), m));
+                    capture_table(&mut s, &mut rows, "#", keyed.collect())
+                }
+                _ => s.push_str("Found nothing"),
+            }
+        }
+        Operation::Split => {
+            s.push_str(&format!(
+                "Splitting with pattern:\n\"{}\"\nString:\n{}\n\n",
+                eval.effective_pattern(),
+                subject(eval),
+            ));
+            match outcome {
+                Outcome::Pieces { pieces: v, .. } if !v.is_empty() => {
+                    for (i, p) in v.iter().enumerate().skip(shown.start).take(shown.len()) {
+                        s.push_str(&format!("Index {} is \"{}\"\n", i, p.text));
+                    }
+                }
+                _ => s.push_str("Found nothing"),
+            }
+        }
+        Operation::Replace => {
+            s.push_str(&format!(
+                "Replacing pattern:\n\"{}\"\nWith:\n\"{}\"\nIn:\n{}\n\n",
+                eval.effective_pattern(),
+                eval.replacement,
+                subject(eval),
+            ));
+            match outcome {
+                Outcome::Replaced(r) if !r.substitutions.is_empty() => {
+                    s.push_str(&format!("Result:\n{}\n\n", quoted(&r.text)));
+                    let subs = r.substitutions.iter().enumerate();
+                    for (i, sub) in subs.skip(shown.start).take(shown.len()) {
+                        rows.push(line_of(&s));
+                        s.push_str(&format!(
+                            "Replacement {} at {} \"{}\" => \"{}\"\n",
+                            i,
+                            at(&sub.span, multi_line),
+                            sub.text,
+                            sub.replacement
+                        ));
+                    }
+                }
+                _ => s.push_str("Found nothing"),
+            }
+        }
+        Operation::Lines => {
+            s.push_str(&format!(
+                "Lines matching pattern:\n\"{}\"\nIn:\n{}\n\n",
+                eval.effective_pattern(),
+                subject(eval),
+            ));
+            if let Outcome::Lines(l) = outcome {
+                s.push_str(&format!(
+                    "{} lines match, {} do not\n\n",
+                    l.matched, l.unmatched
+                ));
+                if !l.lines.is_empty() {
+                    let keyed = l.lines[shown.clone()]
+                        .iter()
+                        .map(|line| (line.number.to_string(), &line.first));
+                    capture_table(&mut s, &mut rows, "line", keyed.collect());
+                }
+            }
+        }
+        Operation::Explain => {
+            s.push_str(&format!(
+                "Explaining pattern:\n\"{}\"\n\n",
+                eval.effective_pattern()
+            ));
+            if let Outcome::Explained(nodes) = outcome {
+                explanation(&mut s, &mut rows, &nodes[shown.clone()]);
+            }
+        }
+        Operation::Inspect => {
+            s.push_str(&format!(
+                "Inspecting pattern:\n\"{}\"\n\n",
+                eval.effective_pattern()
+            ));
+            if let Outcome::Inspected(i) = outcome {
+                inspection(&mut s, &mut rows, i, shown.clone());
+            }
+        }
+        Operation::Set => {
+            s.push_str("Set of patterns:\n");
+            for (i, p) in eval.patterns().iter().enumerate() {
+                s.push_str(&format!("{}: \"{}{}\"\n", i, eval.flags.inline(), p));
+            }
+            s.push_str(&format!("In:\n{}\n\n", subject(eval)));
+            if let Outcome::Set(set) = outcome {
+                set_matches(&mut s, &mut rows, set, shown.clone());
+            }
+        }
+    }
+    footer(&mut s, eval, outcome, shown);
+    (s, rows)
+}
+
+/// Says which rows are shown when not all of them are, and when the results
+/// were cut off by `max_results`.
+fn footer(s: &mut String, eval: &Evaluation, outcome: &Outcome, shown: Range<usize>) {
+    let total = outcome.rows();
+    if shown.len() < total {
+        s.push_str(&format!("\nShowing rows {:?} of {}", shown, total));
+    }
+    if let (Some(max), true) = (eval.max_results, outcome.cut_off()) {
+        s.push_str(&format!("\nStopped after the first {} results", max));
+    }
+}
+
+/// Describes where a match is: its byte range, plus the line and column when
+/// the input spans several lines.
+fn at(span: &Span, multi_line: bool) -> String {
+    if multi_line {
+        format!(
+            "({:?}) line {} col {}",
+            span.range(),
+            span.line,
+            span.column
+        )
+    } else {
+        format!("({:?})", span.range())
+    }
+}
+
+/// Prints the line of `pattern` holding the start of `span`, with carets
+/// under the part of it that `span` covers.  Multi-line patterns, as used
+/// with the `x` flag, get line numbers.
+fn caret_diagram(s: &mut String, pattern: &str, span: &Span) {
+    let line_start = pattern[..span.start].rfind('\n').map_or(0, |i| i + 1);
+    let line_end = pattern[span.start..]
+        .find('\n')
+        .map_or(pattern.len(), |i| span.start + i);
+    let line = &pattern[line_start..line_end];
+    let prefix = if pattern.contains('\n') {
+        format!("{}: ", pattern[..line_start].matches('\n').count() + 1)
+    } else {
+        String::new()
+    };
+    let pad = prefix.chars().count() + pattern[line_start..span.start].chars().count();
+    let width = pattern[span.start..span.end.min(line_end)]
+        .chars()
+        .count()
+        .max(1);
+    s.push_str(&format!("    {}{}\n", prefix, line));
+    s.push_str(&format!("    {}{}\n", " ".repeat(pad), "^".repeat(width)));
+}
+
+/// How much of a part of the pattern an explanation quotes next to it.
+const SOURCE_CHARS: usize = 40;
+
+/// Lists the explanation indented as a tree, with the part of the pattern
+/// each line is about lined up to the right of it.
+fn explanation(s: &mut String, rows: &mut Vec<usize>, nodes: &[Node]) {
+    let lines: Vec<String> = nodes
+        .iter()
+        .map(|n| format!("{}{}", "  ".repeat(n.depth), n.text))
+        .collect();
+    let width = lines.iter().map(|l| l.chars().count()).max().unwrap_or(0);
+    for (line, node) in lines.iter().zip(nodes) {
+        let mut source: String = node.source.chars().take(SOURCE_CHARS).collect();
+        if source.len() < node.source.len() {
+            source.push_str("...");
+        }
+        rows.push(line_of(s));
+        s.push_str(&format!(
+            "{:<w$}  {}\n",
+            line,
+            source.replace('\n', " "),
+            w = width
+        ));
+    }
+}
+
+fn yes_no(b: bool) -> &'static str {
+    if b {
+        "yes"
+    } else {
+        "no"
+    }
+}
+
+/// Lists the properties, the literals and the HIR, then the rows of its tree
+/// in `shown`.
+fn inspection(s: &mut String, rows: &mut Vec<usize>, i: &Inspection, shown: Range<usize>) {
+    for (what, b) in [
+        ("Anchored at the start of the text", i.anchored_start),
+        ("Anchored at the end of the text", i.anchored_end),
+        ("Anchored at the start of a line", i.line_anchored_start),
+        ("Anchored at the end of a line", i.line_anchored_end),
+        ("Matches the empty string", i.matches_empty),
+        ("Plain literal", i.literal),
+        ("Alternation of literals", i.alternation_literal),
+    ]
+    .iter()
+    {
+        s.push_str(&format!("{}: {}\n", what, yes_no(*b)));
+    }
+    for (what, lits) in [("Prefixes", &i.prefixes), ("Suffixes", &i.suffixes)].iter() {
+        if lits.is_empty() {
+            s.push_str(&format!("\n{}: none\n", what));
+            continue;
+        }
+        s.push_str(&format!("\n{} ({}):\n", what, lits.len()));
+        for lit in lits.iter() {
+            let exact = if lit.exact { "  (whole match)" } else { "" };
+            s.push_str(&format!("    {:?}{}\n", lit.text, exact));
+        }
+    }
+    s.push_str("\nregex-automata:\n");
+    match &i.automata {
+        Ok(a) => {
+            s.push_str(&format!(
+                "    NFA: {} states, {} bytes\n",
+                a.nfa_states, a.nfa_memory
+            ));
+            s.push_str(&format!("    NFA anchored: {}\n", yes_no(a.nfa_anchored)));
+            match &a.dfa_memory {
+                Ok(bytes) => s.push_str(&format!("    DFA: {} bytes\n", bytes)),
+                Err(e) => s.push_str(&format!("    DFA: not built, {}\n", e)),
+            }
+        }
+        Err(e) => s.push_str(&format!("    Not built: {}\n", e)),
+    }
+    s.push_str(&format!("\nHIR:\n{}\n\nHIR tree:\n", quoted(&i.hir)));
+    for line in i.tree[shown].iter() {
+        rows.push(line_of(s));
+        s.push_str(line);
+        s.push('\n');
+    }
+}
+
+/// Lists how many lines each pattern matches, then the rows of `shown` with
+/// the patterns matching each line.
+fn set_matches(s: &mut String, rows: &mut Vec<usize>, set: &SetMatches, shown: Range<usize>) {
+    s.push_str(&format!(
+        "{} lines match, {} do not\n\n",
+        set.matched, set.unmatched
+    ));
+    s.push_str("Lines matched by each pattern:\n");
+    for (i, count) in set.counts.iter().enumerate() {
+        s.push_str(&format!("{}: {}\n", i, count));
+    }
+    let lines = &set.lines[shown];
+    if lines.is_empty() {
+        return;
+    }
+    let width = lines
+        .iter()
+        .map(|l| l.number.to_string().len())
+        .fold(4, usize::max);
+    s.push_str(&format!("\n{:<w$}  patterns\n", "line", w = width));
+    for line in lines.iter() {
+        let patterns: Vec<String> = line.patterns.iter().map(|p| p.to_string()).collect();
+        let patterns = if patterns.is_empty() {
+            "none".to_string()
+        } else {
+            patterns.join(", ")
+        };
+        rows.push(line_of(s));
+        s.push_str(&format!("{:<w$}  {}\n", line.number, patterns, w = width));
+    }
+}
+
+/// Lays out one row per match and one column per group, each cell holding
+/// the group's text and byte range.  The first column holds the key paired
+/// with each match, under the heading `key`.
+fn capture_table(
+    s: &mut String,
+    line_rows: &mut Vec<usize>,
+    key: &str,
+    matches: Vec<(String, &Match)>,
+) {
+    if matches.is_empty() {
+        return;
+    }
+    let mut rows = vec![];
+    let mut header = vec![key.to_string()];
+    header.extend(matches[0].1.groups.iter().map(|g| g.label()));
+    rows.push(header);
+    for (k, m) in matches {
+        let mut row = vec![k];
+        row.extend(m.groups.iter().map(|g| match (&g.text, &g.span) {
+            (Some(t), Some(sp)) => format!("\"{}\" ({:?})", t, sp.range()),
+            _ => "None".to_string(),
+        }));
+        rows.push(row);
+    }
+
+    let mut widths = vec![0; rows[0].len()];
+    for row in rows.iter() {
+        for (w, cell) in widths.iter_mut().zip(row.iter()) {
+            *w = (*w).max(cell.chars().count());
+        }
+    }
+    for (i, row) in rows.iter().enumerate() {
+        if i > 0 {
+            line_rows.push(line_of(s));
+        }
+        let line = row
+            .iter()
+            .zip(widths.iter())
+            .map(|(cell, w)| format!("{:<w$}", cell, w = w))
+            .collect::<Vec<_>>()
+            .join("  ");
+        s.push_str(line.trim_end());
+        s.push('\n');
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::eval::Operation;
+
+    #[test]
+    fn page_past_the_end_renders_no_rows() {
+        for op in [Operation::FindCaptures, Operation::Lines] {
+            let eval = Evaluation::new(op, r"(\d)", "1 2\n3");
+            let outcome = eval.run();
+            let (s, rows) = Format::Plain.render_page(&eval, &outcome, Page { start: 10, len: 5 });
+            assert!(rows.is_empty(), "{}", s);
+        }
+    }
+
+    fn found() -> (Evaluation, Outcome) {
+        let eval = Evaluation::new(Operation::Find, r"a\d", "a1 b\na2");
+        let outcome = eval.run();
+        (eval, outcome)
+    }
+
+    #[test]
+    fn crlf_ends_every_line_with_crlf() {
+        let (eval, outcome) = found();
+        let plain = Format::Plain.render(&eval, &outcome);
+        let crlf = Format::Crlf.render(&eval, &outcome);
+        assert_eq!(crlf.matches("\r\n").count(), plain.matches('\n').count());
+        assert_eq!(crlf.replace("\r\n", "\n"), plain);
+    }
+
+    #[test]
+    fn json_holds_the_evaluation_and_every_row() {
+        let (eval, outcome) = found();
+        let (s, rows) = Format::Json.render_page(&eval, &outcome, Page::first(1));
+        assert!(rows.is_empty());
+        let v: serde_json::Value = serde_json::from_str(&s).unwrap();
+        assert_eq!(v["operation"], "find");
+        assert_eq!(v["pattern"], r"a\d");
+        assert_eq!(v["outcome"]["kind"], "found");
+        assert_eq!(v["outcome"]["result"]["matches"][1]["span"]["line"], 2);
+        assert_eq!(v["outcome"]["result"]["cut_off"], false);
+        assert_eq!(
+            v["outcome"]["result"]["matches"]
+                .as_array()
+                .map(|r| r.len()),
+            Some(2)
+        );
+    }
+
+    #[test]
+    fn plain_lists_each_match_with_the_line_it_starts() {
+        let (eval, outcome) = found();
+        let (s, rows) = Format::Plain.render_rows(&eval, &outcome);
+        let lines: Vec<&str> = s.lines().collect();
+        assert_eq!(rows.len(), 2);
+        assert!(lines[rows[0]].contains("\"a1\" at (0..2)"), "{}", s);
+        assert!(lines[rows[1]].contains("\"a2\" at (5..7)"), "{}", s);
+        assert!(!s.contains('\r'));
+    }
+
+    #[test]
+    fn a_page_lists_only_its_rows() {
+        let (eval, outcome) = found();
+        let (s, rows) = Format::Plain.render_page(&eval, &outcome, Page { start: 1, len: 1 });
+        assert_eq!(rows.len(), 1);
+        assert!(!s.contains("\"a1\""), "{}", s);
+        assert!(s.contains("\"a2\""), "{}", s);
+        assert!(s.contains("Showing rows 1..2 of 2"), "{}", s);
+    }
+
+    #[test]
+    fn pages_step_through_the_rows() {
+        let page = Page::first(10);
+        assert_eq!(page.range(25), 0..10);
+        let page = page.next(25).next(25);
+        assert_eq!(page, Page { start: 20, len: 10 });
+        assert_eq!(page.range(25), 20..25);
+        // the last page stays put, and the first goes no further back
+        assert_eq!(page.next(25), page);
+        assert_eq!(page.prev().prev().prev(), Page::first(10));
+        assert_eq!(Page::all().next(25), Page::all());
+        assert_eq!(Page::all().range(25), 0..25);
+        assert_eq!(Page { start: 30, len: 10 }.range(25), 25..25);
+    }
+
+    #[test]
+    fn an_inspection_shows_the_automata_and_the_tree() {
+        let eval = Evaluation::new(Operation::Inspect, r"\bab", "");
+        let s = Format::Plain.render(&eval, &eval.run());
+        assert!(s.contains("regex-automata:\n    NFA: "), "{}", s);
+        assert!(s.contains("    DFA: not built, "), "{}", s);
+        assert!(
+            s.ends_with("HIR tree:\nconcat\n  word boundary\n  literal \"ab\"\n"),
+            "{}",
+            s
+        );
+    }
+
+    #[test]
+    fn the_cap_is_mentioned_only_when_it_left_results_out() {
+        let footer = |op, pattern, max| {
+            let eval = Evaluation::new(op, pattern, "a1 b2").with_max_results(max);
+            let s = Format::Plain.render(&eval, &eval.run());
+            s.contains("Stopped after the first")
+        };
+        // a match has a row per group, but nothing to cut off
+        assert!(!footer(Operation::Match, r"(\w)(\d)", 2));
+        assert!(!footer(Operation::Find, r"\w\d", 2));
+        assert!(footer(Operation::Find, r"\w\d", 1));
+        assert!(!footer(Operation::Lines, r"\d", 1));
+    }
+}
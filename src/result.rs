@@ -0,0 +1,541 @@
+use std::ops::Range;
+
+use serde::Serialize;
+
+use crate::options::{Flags, Limits};
+
+/// A region of the haystack (or of the pattern, for errors) given both as
+/// byte offsets, which is what `regex` reports, and as char offsets, which is
+/// what text widgets usually want.  `line` and `column` locate the start for
+/// people reading multi-line text; both count from 1 and columns are in chars.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct Span {
+    pub start: usize,
+    pub end: usize,
+    pub char_start: usize,
+    pub char_end: usize,
+    pub line: usize,
+    pub column: usize,
+}
+
+impl Span {
+    pub fn range(&self) -> Range<usize> {
+        self.start..self.end
+    }
+
+    pub fn char_range(&self) -> Range<usize> {
+        self.char_start..self.char_end
+    }
+}
+
+/// Turns byte ranges into [`Span`]s.  The char and line counts are carried
+/// from one lookup to the next, so walking through the matches in order only
+/// counts the text once.
+pub struct Spans<'a> {
+    text: &'a str,
+    byte: usize,
+    chars: usize,
+    /// Newlines before `byte`.
+    lines: usize,
+    /// Char offset of the start of the line holding `byte`.
+    line_start: usize,
+}
+
+impl<'a> Spans<'a> {
+    pub fn new(text: &'a str) -> Self {
+        Spans { text, byte: 0, chars: 0, lines: 0, line_start: 0 }
+    }
+
+    fn seek(&mut self, byte: usize) {
+        if byte >= self.byte {
+            let skipped = &self.text[self.byte..byte];
+            if let Some(nl) = skipped.rfind('\n') {
+                self.lines += skipped.matches('\n').count();
+                self.line_start = self.chars + skipped[..=nl].chars().count();
+            }
+            self.chars += skipped.chars().count();
+        } else {
+            let skipped = &self.text[byte..self.byte];
+            self.chars -= skipped.chars().count();
+            if skipped.contains('\n') {
+                self.lines -= skipped.matches('\n').count();
+                self.line_start = match self.text[..byte].rfind('\n') {
+                    Some(nl) => self.chars - self.text[nl + 1..byte].chars().count(),
+                    None => 0,
+                };
+            }
+        }
+        self.byte = byte;
+    }
+
+    pub fn span(&mut self, r: Range<usize>) -> Span {
+        self.seek(r.start);
+        let (char_start, line, column) =
+            (self.chars, self.lines + 1, self.chars - self.line_start + 1);
+        self.seek(r.end);
+        Span { start: r.start, end: r.end, char_start, char_end: self.chars, line, column }
+    }
+}
+
+/// One capture group of a match.  `span` and `text` are `None` when the group
+/// did not participate in the match.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Group {
+    pub index: usize,
+    pub name: Option<String>,
+    pub span: Option<Span>,
+    pub text: Option<String>,
+}
+
+impl Group {
+    /// How the group is labelled in text output: `group[1]`, or
+    /// `group[1]<year>` for `(?P<year>...)`.
+    pub fn label(&self) -> String {
+        match &self.name {
+            Some(n) => format!("group[{}]<{}>", self.index, n),
+            None => format!("group[{}]", self.index),
+        }
+    }
+}
+
+/// A single match.  `groups` holds every capture group, group 0 included,
+/// when the operation asked for captures and is empty otherwise.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Match {
+    pub span: Span,
+    pub text: String,
+    pub groups: Vec<Group>,
+}
+
+impl Match {
+    /// The group declared as `(?P<name>...)`.
+    pub fn named(&self, name: &str) -> Option<&Group> {
+        self.groups.iter().find(|g| g.name.as_deref() == Some(name))
+    }
+}
+
+/// A piece of the haystack between two matches, as produced by split.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Piece {
+    pub span: Span,
+    pub text: String,
+}
+
+/// One match that `Replace` substituted, and what it became.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Substitution {
+    pub span: Span,
+    pub text: String,
+    pub replacement: String,
+}
+
+/// The text produced by `Replace` along with each substitution made.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Replaced {
+    pub text: String,
+    pub substitutions: Vec<Substitution>,
+    /// `max_results` left matches unreplaced.
+    pub cut_off: bool,
+}
+
+/// A stretch of the haystack to colour in the input editor.  `group` is 0
+/// for a whole match and the capture group index otherwise; `row` is the
+/// result the stretch belongs to, in the order the text output lists them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct Highlight {
+    pub span: Span,
+    pub group: usize,
+    pub row: usize,
+}
+
+impl Highlight {
+    /// The innermost highlight covering byte `offset`.
+    pub fn at(highlights: &[Highlight], offset: usize) -> Option<&Highlight> {
+        highlights
+            .iter()
+            .rev()
+            .find(|h| h.span.start <= offset && offset < h.span.end)
+    }
+}
+
+/// A line that matched in line mode, with the first match on it.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct MatchedLine {
+    /// Counting from 1.
+    pub number: usize,
+    pub span: Span,
+    pub text: String,
+    /// Carries every capture group, so the fields of the line can be read
+    /// off it.
+    pub first: Match,
+}
+
+/// The result of `Lines`: how many lines matched and how many did not, and
+/// the lines that did.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Lines {
+    pub matched: usize,
+    pub unmatched: usize,
+    pub lines: Vec<MatchedLine>,
+}
+
+/// A line tried against the patterns of a set.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct SetLine {
+    /// Counting from 1.
+    pub number: usize,
+    pub span: Span,
+    pub text: String,
+    /// The index of each pattern matching the line, in order.
+    pub patterns: Vec<usize>,
+}
+
+/// The result of `Set`: how many lines each pattern matched, how many lines
+/// any of them matched and how many none did, and each line with the
+/// patterns matching it.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct SetMatches {
+    pub counts: Vec<usize>,
+    pub matched: usize,
+    pub unmatched: usize,
+    pub lines: Vec<SetLine>,
+}
+
+/// One line of the explanation of a pattern: what the part of the pattern at
+/// `span` does.  Parts inside a group or repetition come after it, one level
+/// deeper.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Node {
+    pub depth: usize,
+    /// Where the part is in the pattern.
+    pub span: Span,
+    pub source: String,
+    pub text: String,
+}
+
+/// A literal that a match must start or end with.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Extracted {
+    pub text: String,
+    /// True when the literal is a whole match and not just part of one.
+    pub exact: bool,
+}
+
+/// What `Inspect` found out about how `regex` sees the pattern.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Inspection {
+    /// The HIR written back as a pattern, with the flags applied and the
+    /// classes spelt out.
+    pub hir: String,
+    /// The HIR, one line per node, indented by two spaces per level.
+    pub tree: Vec<String>,
+    /// Only matches at the start of the text.
+    pub anchored_start: bool,
+    pub anchored_end: bool,
+    /// Only matches at the start of a line.
+    pub line_anchored_start: bool,
+    pub line_anchored_end: bool,
+    pub matches_empty: bool,
+    /// The pattern is a plain string.
+    pub literal: bool,
+    /// The pattern is an alternation of plain strings.
+    pub alternation_literal: bool,
+    /// What searches look for before running the matcher; empty when there
+    /// is no prefilter.
+    pub prefixes: Vec<Extracted>,
+    /// Literals every match ends with.
+    pub suffixes: Vec<Extracted>,
+    /// The automata `regex-automata` compiles the pattern to, or why it
+    /// could not.
+    pub automata: Result<Automata, String>,
+}
+
+/// The sizes of what `regex-automata` builds for a pattern.  `regex` 1.5 has
+/// its own compiler, so these are the automata later `regex` releases search
+/// with rather than the ones searches here run.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Automata {
+    /// States of the Thompson NFA.
+    pub nfa_states: usize,
+    /// Heap used by the NFA, in bytes.
+    pub nfa_memory: usize,
+    /// Every match of the NFA starts at the start of the text.
+    pub nfa_anchored: bool,
+    /// Heap used by the fully built forward DFA, in bytes, or why it was not
+    /// built, such as growing past the DFA size limit.
+    pub dfa_memory: Result<usize, String>,
+}
+
+/// Why a pattern failed to compile, with the offending part of the pattern
+/// when the parser can point at it.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct PatternError {
+    /// What went wrong in a few words, e.g. `unclosed group`.
+    pub kind: String,
+    /// The full message from `regex`.
+    pub message: String,
+    pub span: Option<Span>,
+    /// When the compiled program was too big, the smallest `size_limit` that
+    /// lets it compile.
+    pub required_size_limit: Option<usize>,
+}
+
+impl PatternError {
+    pub fn new(pattern: &str, flags: &Flags, limits: &Limits, err: &regex::Error) -> Self {
+        let parser = flags.parser().nest_limit(limits.nest_limit).build();
+        PatternError::parsed(pattern, parser, err)
+    }
+
+    /// Like [`PatternError::new`] for a pattern compiled by `regex::bytes`,
+    /// which may match bytes that are not UTF-8.
+    pub fn new_bytes(pattern: &str, flags: &Flags, limits: &Limits, err: &regex::Error) -> Self {
+        let mut parser = flags.parser();
+        parser
+            .allow_invalid_utf8(true)
+            .nest_limit(limits.nest_limit);
+        PatternError::parsed(pattern, parser.build(), err)
+    }
+
+    /// Looks for what `parser` finds wrong with `pattern`, falling back on
+    /// `err` when it finds nothing.
+    fn parsed(pattern: &str, mut parser: regex_syntax::Parser, err: &regex::Error) -> Self {
+        let (kind, span) = match parser.parse(pattern) {
+            Err(regex_syntax::Error::Parse(e)) => (e.kind().to_string(), Some(*e.span())),
+            Err(regex_syntax::Error::Translate(e)) => (e.kind().to_string(), Some(*e.span())),
+            _ => match err {
+                regex::Error::CompiledTooBig(_) => ("compiled regex too big".to_string(), None),
+                _ => ("syntax error".to_string(), None),
+            },
+        };
+        PatternError {
+            kind,
+            message: err.to_string(),
+            span: span.map(|s| Spans::new(pattern).span(s.start.offset..s.end.offset)),
+            required_size_limit: None,
+        }
+    }
+}
+
+/// What came out of running an [`Evaluation`](crate::eval::Evaluation).
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "kind", content = "result", rename_all = "snake_case")]
+pub enum Outcome {
+    /// The pattern did not compile.
+    PatternError(PatternError),
+    /// `Match` found nothing.
+    NoMatch,
+    /// `Match` succeeded, with the captures of the first match.
+    Matched(Match),
+    /// Every non-overlapping match of `Find` or `FindCaptures`, and whether
+    /// `max_results` left any out.
+    Found { matches: Vec<Match>, cut_off: bool },
+    /// The pieces produced by `Split`, and whether `max_results` left any
+    /// out.
+    Pieces { pieces: Vec<Piece>, cut_off: bool },
+    /// The result of `Replace`.
+    Replaced(Replaced),
+    /// The result of `Lines`.
+    Lines(Lines),
+    /// What `Explain` made of the pattern.
+    Explained(Vec<Node>),
+    /// What `Inspect` found in the pattern.
+    Inspected(Inspection),
+    /// The result of `Set`.
+    Set(SetMatches),
+}
+
+impl Outcome {
+    /// True when the frontends should flag the result as a failure.
+    pub fn is_failure(&self) -> bool {
+        match self {
+            Outcome::PatternError(_) | Outcome::NoMatch => true,
+            Outcome::Found { matches, .. } => matches.is_empty(),
+            Outcome::Pieces { pieces, .. } => pieces.is_empty(),
+            Outcome::Replaced(r) => r.substitutions.is_empty(),
+            Outcome::Lines(l) => l.matched == 0,
+            Outcome::Set(s) => s.matched == 0,
+            Outcome::Matched(_) | Outcome::Explained(_) | Outcome::Inspected(_) => false,
+        }
+    }
+
+    /// True when `max_results` left out results the operation would
+    /// otherwise have listed.
+    pub fn cut_off(&self) -> bool {
+        match self {
+            Outcome::Found { cut_off, .. } | Outcome::Pieces { cut_off, .. } => *cut_off,
+            Outcome::Replaced(r) => r.cut_off,
+            Outcome::Lines(l) => l.matched > l.lines.len(),
+            Outcome::Set(s) => s.matched + s.unmatched > s.lines.len(),
+            Outcome::PatternError(_)
+            | Outcome::NoMatch
+            | Outcome::Matched(_)
+            | Outcome::Explained(_)
+            | Outcome::Inspected(_) => false,
+        }
+    }
+
+    /// How many rows the text output lists, which is what a [`Page`] counts.
+    ///
+    /// [`Page`]: crate::render::Page
+    pub fn rows(&self) -> usize {
+        match self {
+            Outcome::PatternError(_) | Outcome::NoMatch => 0,
+            Outcome::Matched(m) => m.groups.len(),
+            Outcome::Found { matches, .. } => matches.len(),
+            Outcome::Pieces { pieces, .. } => pieces.len(),
+            Outcome::Replaced(r) => r.substitutions.len(),
+            Outcome::Lines(l) => l.lines.len(),
+            Outcome::Explained(v) => v.len(),
+            Outcome::Inspected(i) => i.tree.len(),
+            Outcome::Set(s) => s.lines.len(),
+        }
+    }
+
+    /// The parts of the haystack to colour, outer before inner so a frontend
+    /// painting them in order leaves each group visible inside its match.
+    /// `Match` lists one row per group, `Lines` one per matching line, `Set`
+    /// one per line with the lines some pattern matches coloured whole, and
+    /// the other operations one per match; split has nothing to colour since its
+    /// pieces are what lies between, nor explain and inspect since they look
+    /// at the pattern alone.
+    pub fn highlights(&self) -> Vec<Highlight> {
+        let mut v = vec![];
+        match self {
+            Outcome::Matched(m) => {
+                for g in m.groups.iter() {
+                    if let Some(span) = g.span {
+                        v.push(Highlight { span, group: g.index, row: g.index });
+                    }
+                }
+            }
+            Outcome::Found { matches, .. } => {
+                for (row, m) in matches.iter().enumerate() {
+                    v.push(Highlight { span: m.span, group: 0, row });
+                    for g in m.groups.iter().skip(1) {
+                        if let Some(span) = g.span {
+                            v.push(Highlight { span, group: g.index, row });
+                        }
+                    }
+                }
+            }
+            Outcome::Replaced(r) => {
+                for (row, sub) in r.substitutions.iter().enumerate() {
+                    v.push(Highlight { span: sub.span, group: 0, row });
+                }
+            }
+            Outcome::Lines(l) => {
+                for (row, line) in l.lines.iter().enumerate() {
+                    for g in line.first.groups.iter() {
+                        if let Some(span) = g.span {
+                            v.push(Highlight { span, group: g.index, row });
+                        }
+                    }
+                }
+            }
+            Outcome::Set(s) => {
+                for (row, line) in s.lines.iter().enumerate() {
+                    if !line.patterns.is_empty() {
+                        v.push(Highlight { span: line.span, group: 0, row });
+                    }
+                }
+            }
+            Outcome::PatternError(_)
+            | Outcome::NoMatch
+            | Outcome::Pieces { .. }
+            | Outcome::Explained(_)
+            | Outcome::Inspected(_) => {}
+        }
+        v
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn error(pattern: &str, limits: &Limits) -> PatternError {
+        let flags = Flags::default();
+        let err = flags
+            .builder(pattern)
+            .size_limit(limits.size_limit)
+            .build()
+            .unwrap_err();
+        PatternError::new(pattern, &flags, limits, &err)
+    }
+
+    #[test]
+    fn pattern_errors_point_at_the_offending_part() {
+        let e = error("é(b", &Limits::default());
+        assert_eq!(e.kind, "unclosed group");
+        let span = e.span.unwrap();
+        assert_eq!((span.range(), span.char_range()), (2..3, 1..2));
+        assert!(e.message.contains("unclosed group"));
+
+        let e = error("a\n\\p{Nope}", &Limits::default());
+        assert_eq!(e.kind, "Unicode property not found");
+        let span = e.span.unwrap();
+        assert_eq!((span.range(), span.line, span.column), (2..10, 2, 1));
+    }
+
+    #[test]
+    fn a_pattern_too_big_has_no_span() {
+        let limits = Limits { size_limit: 10, ..Limits::default() };
+        let e = error("a{50}", &limits);
+        assert_eq!(e.kind, "compiled regex too big");
+        assert_eq!(e.span, None);
+    }
+
+    // bytes: a=0 é=1..3 \n=3 b=4 c=5 \n=6 d=7 é=8..10
+    const TEXT: &str = "aé\nbc\ndé";
+
+    #[test]
+    fn spans_count_chars_lines_and_columns() {
+        let mut spans = Spans::new(TEXT);
+        let s = spans.span(1..3);
+        assert_eq!(
+            (s.range(), s.char_range(), s.line, s.column),
+            (1..3, 1..2, 1, 2)
+        );
+        let s = spans.span(4..6);
+        assert_eq!(
+            (s.range(), s.char_range(), s.line, s.column),
+            (4..6, 3..5, 2, 1)
+        );
+        let s = spans.span(8..10);
+        assert_eq!(
+            (s.range(), s.char_range(), s.line, s.column),
+            (8..10, 7..8, 3, 2)
+        );
+        let s = spans.span(10..10);
+        assert_eq!((s.char_range(), s.line, s.column), (8..8, 3, 3));
+    }
+
+    #[test]
+    fn spans_can_go_back() {
+        let mut spans = Spans::new(TEXT);
+        spans.span(8..10);
+        let s = spans.span(5..6);
+        assert_eq!((s.char_range(), s.line, s.column), (4..5, 2, 2));
+        let s = spans.span(0..1);
+        assert_eq!((s.char_range(), s.line, s.column), (0..1, 1, 1));
+        // and on again from where it went back to
+        assert_eq!(spans.span(7..8), Spans::new(TEXT).span(7..8));
+    }
+
+    #[test]
+    fn a_span_may_cross_lines() {
+        let s = Spans::new(TEXT).span(3..8);
+        assert_eq!((s.char_range(), s.line, s.column), (2..7, 1, 3));
+    }
+
+    #[test]
+    fn the_innermost_highlight_wins() {
+        let mut spans = Spans::new("abcd");
+        let h = |spans: &mut Spans, r, group| Highlight { span: spans.span(r), group, row: 0 };
+        let highlights = [h(&mut spans, 0..4, 0), h(&mut spans, 1..3, 1)];
+        assert_eq!(Highlight::at(&highlights, 0).map(|h| h.group), Some(0));
+        assert_eq!(Highlight::at(&highlights, 2).map(|h| h.group), Some(1));
+        assert_eq!(Highlight::at(&highlights, 3).map(|h| h.group), Some(0));
+        assert_eq!(Highlight::at(&highlights, 4), None);
+    }
+}
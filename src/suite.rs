@@ -0,0 +1,355 @@
+use std::{
+    collections::BTreeMap,
+    fmt,
+    fs::File,
+    io::{BufReader, BufWriter},
+    path::Path,
+};
+
+use anyhow::Context;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    eval::{Evaluation, Operation},
+    options::{Flags, Limits},
+    result::PatternError,
+};
+
+/// What a case expects the pattern to do with its input.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Expect {
+    Match,
+    NoMatch,
+    /// A match whose groups, keyed by index or name, hold these texts; `null`
+    /// for a group that must not take part.  Groups left out are not checked.
+    Captures(BTreeMap<String, Option<String>>),
+}
+
+/// One input of a [`Suite`] and what should happen to it.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Case {
+    #[serde(default)]
+    pub name: String,
+    pub input: String,
+    pub expect: Expect,
+}
+
+impl Case {
+    /// A case expecting whatever `re` does with `input` now, with every
+    /// group when it has any.
+    pub fn observe(re: &Regex, name: &str, input: &str) -> Self {
+        let expect = match re.captures(input) {
+            None => Expect::NoMatch,
+            Some(caps) if caps.len() == 1 => Expect::Match,
+            Some(caps) => Expect::Captures(
+                re.capture_names()
+                    .enumerate()
+                    .skip(1)
+                    .map(|(i, name)| {
+                        let key = name.map_or_else(|| i.to_string(), |n| n.to_string());
+                        (key, caps.get(i).map(|m| m.as_str().to_string()))
+                    })
+                    .collect(),
+            ),
+        };
+        Case { name: name.into(), input: input.into(), expect }
+    }
+
+    /// Checks the case against `re`, describing the first way it fails.
+    pub fn check(&self, re: &Regex) -> Result<(), String> {
+        let caps = re.captures(&self.input);
+        match (&self.expect, caps) {
+            (Expect::NoMatch, None) | (Expect::Match, Some(_)) => Ok(()),
+            (Expect::NoMatch, Some(caps)) => {
+                Err(format!("expected no match, matched \"{}\"", &caps[0]))
+            }
+            (_, None) => Err("expected a match".to_string()),
+            (Expect::Captures(want), Some(caps)) => {
+                for (key, want) in want.iter() {
+                    let got = match key.parse::<usize>() {
+                        Ok(i) if i < re.captures_len() => caps.get(i),
+                        Err(_) if re.capture_names().any(|n| n == Some(key)) => caps.name(key),
+                        _ => return Err(format!("the pattern has no group {}", key)),
+                    };
+                    let got = got.map(|m| m.as_str());
+                    if got != want.as_deref() {
+                        return Err(format!(
+                            "expected group {} to be {}, got {}",
+                            key,
+                            described(want.as_deref()),
+                            described(got)
+                        ));
+                    }
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+fn described(group: Option<&str>) -> String {
+    match group {
+        Some(t) => format!("\"{}\"", t),
+        None => "None".to_string(),
+    }
+}
+
+/// A pattern together with the inputs it is meant to accept and reject,
+/// kept as JSON so it can be reloaded instead of retyped.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Suite {
+    pub pattern: String,
+    #[serde(default)]
+    pub flags: Flags,
+    #[serde(default)]
+    pub limits: Limits,
+    #[serde(default)]
+    pub cases: Vec<Case>,
+}
+
+impl Suite {
+    pub fn new(pattern: &str, flags: Flags, limits: Limits) -> Self {
+        Suite { pattern: pattern.into(), flags, limits, cases: vec![] }
+    }
+
+    pub fn load(path: &Path) -> Result<Self, Box<dyn std::error::Error>> {
+        let f = File::open(path).with_context(|| format!("Unable to open {}", path.display()))?;
+        let suite = serde_json::from_reader(BufReader::new(f))
+            .with_context(|| format!("{} is not a test suite", path.display()))?;
+        Ok(suite)
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), Box<dyn std::error::Error>> {
+        let f =
+            File::create(path).with_context(|| format!("Unable to create {}", path.display()))?;
+        serde_json::to_writer_pretty(BufWriter::new(f), self)?;
+        Ok(())
+    }
+
+    /// A match of the pattern with the flags and limits of the suite.
+    pub fn evaluation(&self) -> Evaluation {
+        Evaluation::new(Operation::Match, &self.pattern, "")
+            .with_flags(self.flags)
+            .with_limits(self.limits)
+    }
+
+    pub fn compile(&self) -> Result<Regex, PatternError> {
+        self.evaluation().compile()
+    }
+
+    /// Adds a case expecting what the pattern does with `input` now.
+    pub fn record(&mut self, name: &str, input: &str) -> Result<(), PatternError> {
+        let re = self.compile()?;
+        self.cases.push(Case::observe(&re, name, input));
+        Ok(())
+    }
+
+    pub fn run(&self) -> Report {
+        let eval = self.evaluation();
+        let mut report = Report { pattern: eval.effective_pattern(), error: None, cases: vec![] };
+        let re = match eval.compile() {
+            Ok(re) => Some(re),
+            Err(e) => {
+                report.error = Some(e);
+                None
+            }
+        };
+        for (i, case) in self.cases.iter().enumerate() {
+            let failure = match &re {
+                Some(re) => case.check(re).err(),
+                None => Some("the pattern does not compile".to_string()),
+            };
+            let name = if case.name.is_empty() {
+                format!("case {}", i)
+            } else {
+                case.name.clone()
+            };
+            report
+                .cases
+                .push(CaseResult { name, input: case.input.clone(), failure });
+        }
+        report
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct CaseResult {
+    pub name: String,
+    pub input: String,
+    /// Why the case failed, or `None` when it passed.
+    pub failure: Option<String>,
+}
+
+/// Pass or fail for every case of a [`Suite`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Report {
+    /// The pattern with its flags inline.
+    pub pattern: String,
+    pub error: Option<PatternError>,
+    pub cases: Vec<CaseResult>,
+}
+
+impl Report {
+    pub fn passed(&self) -> usize {
+        self.cases.iter().filter(|c| c.failure.is_none()).count()
+    }
+
+    pub fn all_passed(&self) -> bool {
+        self.error.is_none() && self.passed() == self.cases.len()
+    }
+}
+
+impl fmt::Display for Report {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Suite for pattern:\n\"{}\"\n", self.pattern)?;
+        if let Some(e) = &self.error {
+            writeln!(f, "Error with pattern: {}\n", e.message)?;
+        }
+        for c in self.cases.iter() {
+            match &c.failure {
+                None => writeln!(f, "PASS  {}: {:?}", c.name, c.input)?,
+                Some(why) => writeln!(f, "FAIL  {}: {:?} - {}", c.name, c.input, why)?,
+            }
+        }
+        write!(f, "\n{} of {} cases pass", self.passed(), self.cases.len())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn dates() -> Suite {
+        let mut suite = Suite::new(
+            r"(?P<year>\d{4})-(\d\d)",
+            Flags::default(),
+            Limits::default(),
+        );
+        suite.record("iso", "on 2021-07").unwrap();
+        suite.record("", "no date").unwrap();
+        suite
+    }
+
+    fn captures(groups: &[(&str, Option<&str>)]) -> Expect {
+        Expect::Captures(
+            groups
+                .iter()
+                .map(|(k, v)| (k.to_string(), v.map(str::to_string)))
+                .collect(),
+        )
+    }
+
+    #[test]
+    fn observe_records_every_group_by_name_or_index() {
+        let re = Regex::new(r"(?P<year>\d{4})-(\d\d)?").unwrap();
+        let case = Case::observe(&re, "x", "2021-");
+        assert_eq!(
+            case.expect,
+            captures(&[("year", Some("2021")), ("2", None)])
+        );
+        assert_eq!(Case::observe(&re, "", "none").expect, Expect::NoMatch);
+        let re = Regex::new(r"\d").unwrap();
+        assert_eq!(Case::observe(&re, "", "a1").expect, Expect::Match);
+    }
+
+    #[test]
+    fn check_says_how_a_case_fails() {
+        let re = Regex::new(r"(?P<year>\d{4})-(\d\d)").unwrap();
+        let case = |input: &str, expect| Case { name: String::new(), input: input.into(), expect };
+        assert_eq!(case("2021-07", Expect::Match).check(&re), Ok(()));
+        assert_eq!(case("x", Expect::NoMatch).check(&re), Ok(()));
+        assert_eq!(
+            case("2021-07", Expect::NoMatch).check(&re),
+            Err("expected no match, matched \"2021-07\"".to_string())
+        );
+        assert_eq!(
+            case("x", Expect::Match).check(&re),
+            Err("expected a match".to_string())
+        );
+        assert_eq!(
+            case("2021-07", captures(&[("year", Some("2020"))])).check(&re),
+            Err("expected group year to be \"2020\", got \"2021\"".to_string())
+        );
+        assert_eq!(
+            case("2021-07", captures(&[("3", None)])).check(&re),
+            Err("the pattern has no group 3".to_string())
+        );
+        assert_eq!(
+            case("2021-07", captures(&[("2", Some("07"))])).check(&re),
+            Ok(())
+        );
+    }
+
+    #[test]
+    fn recorded_cases_pass() {
+        let report = dates().run();
+        assert!(report.all_passed(), "{}", report);
+        assert_eq!(report.cases[0].name, "iso");
+        assert_eq!(report.cases[1].name, "case 1");
+        assert!(report.to_string().ends_with("2 of 2 cases pass"));
+    }
+
+    #[test]
+    fn a_changed_pattern_fails_its_cases() {
+        let mut suite = dates();
+        suite.pattern = r"(?P<year>\d{4})/(\d\d)".to_string();
+        let report = suite.run();
+        assert!(!report.all_passed());
+        assert_eq!(report.passed(), 1);
+        assert_eq!(report.cases[0].failure.as_deref(), Some("expected a match"));
+        assert!(report
+            .to_string()
+            .contains("FAIL  iso: \"on 2021-07\" - expected a match"));
+    }
+
+    #[test]
+    fn a_bad_pattern_fails_every_case() {
+        let mut suite = dates();
+        suite.pattern = "(".to_string();
+        let report = suite.run();
+        assert!(report.error.is_some());
+        assert_eq!(report.passed(), 0);
+        assert!(report
+            .cases
+            .iter()
+            .all(|c| c.failure.as_deref() == Some("the pattern does not compile")));
+        assert!(suite.record("", "x").is_err());
+    }
+
+    #[test]
+    fn flags_are_inline_in_the_report() {
+        let flags = Flags { case_insensitive: true, ..Flags::default() };
+        let mut suite = Suite::new("abc", flags, Limits::default());
+        suite
+            .cases
+            .push(Case { name: String::new(), input: "ABC".into(), expect: Expect::Match });
+        let report = suite.run();
+        assert_eq!(report.pattern, "(?i)abc");
+        assert!(report.all_passed());
+    }
+
+    #[test]
+    fn save_and_load_round_trip() {
+        let path =
+            std::env::temp_dir().join(format!("re_tester_suite_{}.json", std::process::id()));
+        let suite = dates();
+        suite.save(&path).unwrap();
+        let loaded = Suite::load(&path);
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(loaded.unwrap(), suite);
+        assert!(Suite::load(&path)
+            .unwrap_err()
+            .to_string()
+            .starts_with("Unable to open"));
+    }
+
+    #[test]
+    fn cases_and_settings_may_be_left_out() {
+        let suite: Suite = serde_json::from_str(r#"{"pattern": "a"}"#).unwrap();
+        assert_eq!(suite, Suite::new("a", Flags::default(), Limits::default()));
+        let case: Case = serde_json::from_str(r#"{"input": "a", "expect": "no_match"}"#).unwrap();
+        assert_eq!(case.expect, Expect::NoMatch);
+    }
+}
@@ -0,0 +1,101 @@
+use std::{
+    fmt,
+    fs::File,
+    io::{BufReader, Read},
+    path::{Path, PathBuf},
+};
+
+use anyhow::Context;
+use memmap2::Mmap;
+use serde::{Serialize, Serializer};
+
+/// Files at least this big are memory-mapped rather than read in.
+pub const MAP_THRESHOLD: u64 = 16 * (1 << 20);
+
+enum Contents {
+    Read(String),
+    Mapped(Mmap),
+}
+
+/// Text to test against loaded from a file, for input too big to type or
+/// paste.  The frontends hand it to an [`Evaluation`](crate::eval::Evaluation)
+/// in place of the string they would take from the input box.
+pub struct TestFile {
+    path: PathBuf,
+    contents: Contents,
+}
+
+impl TestFile {
+    /// Reads `path`, or maps it once it reaches [`MAP_THRESHOLD`].  The file
+    /// must be UTF-8.
+    pub fn open(path: &Path) -> Result<Self, Box<dyn std::error::Error>> {
+        let f = File::open(path).with_context(|| format!("Unable to open {}", path.display()))?;
+        let contents = if f.metadata()?.len() >= MAP_THRESHOLD {
+            // the map is only ever read; like any map it can change under us
+            // if another process rewrites the file while it is open
+            let map = unsafe { Mmap::map(&f) }
+                .with_context(|| format!("Unable to map {}", path.display()))?;
+            std::str::from_utf8(&map)
+                .with_context(|| format!("{} is not UTF-8 text", path.display()))?;
+            Contents::Mapped(map)
+        } else {
+            let mut s = String::new();
+            BufReader::new(f)
+                .read_to_string(&mut s)
+                .with_context(|| format!("{} is not UTF-8 text", path.display()))?;
+            Contents::Read(s)
+        };
+        Ok(TestFile { path: path.to_path_buf(), contents })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn text(&self) -> &str {
+        match &self.contents {
+            Contents::Read(s) => s,
+            // checked to be UTF-8 when opened
+            Contents::Mapped(m) => unsafe { std::str::from_utf8_unchecked(m) },
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.text().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl fmt::Debug for TestFile {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TestFile")
+            .field("path", &self.path)
+            .field("len", &self.len())
+            .finish()
+    }
+}
+
+impl fmt::Display for TestFile {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "file {} ({} bytes)", self.path.display(), self.len())
+    }
+}
+
+/// Two loads of the same file are taken to be the same test input.
+impl PartialEq for TestFile {
+    fn eq(&self, other: &Self) -> bool {
+        self.path == other.path && self.len() == other.len()
+    }
+}
+
+impl Eq for TestFile {}
+
+/// Only the path is recorded; the contents stay on disk.
+impl Serialize for TestFile {
+    fn serialize<S: Serializer>(&self, s: S) -> Result<S::Ok, S::Error> {
+        self.path.serialize(s)
+    }
+}
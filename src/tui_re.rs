@@ -0,0 +1,496 @@
+use std::{error::Error, io};
+
+use crossterm::{
+    event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers},
+    execute,
+    terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
+};
+use ratatui::{
+    backend::{Backend, CrosstermBackend},
+    layout::{Constraint, Direction, Layout, Rect},
+    style::{Color, Style},
+    text::{Span, Spans, Text},
+    widgets::{Block, Borders, Paragraph},
+    Frame, Terminal,
+};
+use re_tester::{
+    bench::DEFAULT_RUNS,
+    bytes::{ByteSearch, Encoding, MAX_BYTE_RESULTS},
+    Benchmark, Evaluation, Execution, Flags, Format, Highlight, History, Limits, Operation,
+    Outcome,
+};
+
+const HELP: &str = "F1 match  F2 find  F3 captures  F4 split  F5 replace  F6 lines  F7 history  \
+                    Shift+F7 restore older entry  \
+                    F8 explain  F9 inspect  F10 bench  F11 set  F12/Shift+F12 bytes from hex/base64  \
+                    Tab next  Alt+i/m/s/U/x/u flags  Alt+Enter new pattern line  \
+                    PgUp/PgDn scroll  Esc quit";
+
+/// The most lines of the pattern, as used by sets, shown at once.
+const PATTERN_LINES: u16 = 6;
+
+/// Text colours for highlighted input: whole matches first, then capture
+/// groups cycling through the rest.
+const GROUP_COLORS: [Color; 6] = [
+    Color::Blue,
+    Color::Red,
+    Color::Green,
+    Color::Magenta,
+    Color::Cyan,
+    Color::Yellow,
+];
+
+fn group_color(group: usize) -> Color {
+    if group == 0 {
+        GROUP_COLORS[0]
+    } else {
+        GROUP_COLORS[1 + (group - 1) % (GROUP_COLORS.len() - 1)]
+    }
+}
+
+/// A text being edited, with the cursor as a byte offset into it.
+#[derive(Debug, Default)]
+struct Field {
+    text: String,
+    cursor: usize,
+}
+
+impl Field {
+    fn new(text: &str) -> Self {
+        Field { text: text.into(), cursor: text.len() }
+    }
+
+    fn insert(&mut self, c: char) {
+        self.text.insert(self.cursor, c);
+        self.cursor += c.len_utf8();
+    }
+
+    fn backspace(&mut self) {
+        if let Some(c) = self.text[..self.cursor].chars().next_back() {
+            self.cursor -= c.len_utf8();
+            self.text.remove(self.cursor);
+        }
+    }
+
+    fn left(&mut self) {
+        if let Some(c) = self.text[..self.cursor].chars().next_back() {
+            self.cursor -= c.len_utf8();
+        }
+    }
+
+    fn right(&mut self) {
+        if let Some(c) = self.text[self.cursor..].chars().next() {
+            self.cursor += c.len_utf8();
+        }
+    }
+
+    fn home(&mut self) {
+        self.cursor = self.text[..self.cursor].rfind('\n').map_or(0, |i| i + 1);
+    }
+
+    fn end(&mut self) {
+        self.cursor = self.text[self.cursor..]
+            .find('\n')
+            .map_or(self.text.len(), |i| self.cursor + i);
+    }
+
+    /// Column and line of the cursor, both counting from 0.
+    fn position(&self) -> (u16, u16) {
+        let before = &self.text[..self.cursor];
+        let line = before.matches('\n').count();
+        let column = before.rsplit('\n').next().unwrap_or("").chars().count();
+        (column as u16, line as u16)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Focus {
+    Pattern,
+    String,
+    Replacement,
+}
+
+struct App {
+    pattern: Field,
+    string: Field,
+    replacement: Field,
+    flags: Flags,
+    /// Not editable here, but kept from the history so runs record the
+    /// limits the GUIs set.
+    limits: Limits,
+    focus: Focus,
+    results: String,
+    failed: bool,
+    scroll: u16,
+    /// The string as last evaluated and what to colour in it; only shown
+    /// while the string is unchanged.
+    highlights: (String, Vec<Highlight>),
+    last_op: Operation,
+    hist: History,
+    /// The history entry the fields were last restored from, until the next
+    /// run reorders the history.
+    restored: Option<usize>,
+    status: String,
+}
+
+impl App {
+    fn new(hist: History, status: String) -> Self {
+        let last = hist.last();
+        let field = |f: fn(&Execution) -> &str| Field::new(last.as_ref().map_or("", f));
+        App {
+            pattern: field(|e| e.pattern.as_str()),
+            string: field(|e| e.string.as_str()),
+            replacement: field(|e| e.replacement.as_str()),
+            flags: last.as_ref().map_or_else(Flags::default, |e| e.flags),
+            limits: last.as_ref().map_or_else(Limits::default, |e| e.limits),
+            focus: Focus::Pattern,
+            results: String::new(),
+            failed: false,
+            scroll: 0,
+            highlights: (String::new(), vec![]),
+            last_op: Operation::Match,
+            hist,
+            restored: None,
+            status,
+        }
+    }
+
+    fn field(&mut self) -> &mut Field {
+        match self.focus {
+            Focus::Pattern => &mut self.pattern,
+            Focus::String => &mut self.string,
+            Focus::Replacement => &mut self.replacement,
+        }
+    }
+
+    fn evaluate(&mut self, op: Operation) {
+        self.last_op = op;
+        let eval = Evaluation::new(op, &self.pattern.text, &self.string.text)
+            .with_replacement(&self.replacement.text)
+            .with_flags(self.flags)
+            .with_limits(self.limits);
+        let outcome = eval.run();
+        self.results = Format::Plain.render(&eval, &outcome);
+        self.failed = outcome.is_failure();
+        self.scroll = 0;
+        self.highlights = (eval.string.clone(), outcome.highlights());
+        self.restored = None;
+        self.status = match self.hist.add(Execution::from(&eval)) {
+            Err(e) => format!("Unable to save result: {}", e),
+            Ok(()) => String::new(),
+        };
+    }
+
+    /// Times the last operation and shows the statistics as the results.
+    fn bench(&mut self) {
+        let eval = Evaluation::new(self.last_op, &self.pattern.text, &self.string.text)
+            .with_replacement(&self.replacement.text)
+            .with_flags(self.flags)
+            .with_limits(self.limits);
+        let (results, failed) = match Benchmark::run(&eval, DEFAULT_RUNS) {
+            Ok(b) => (b.to_string(), false),
+            Err(e) => (Format::Plain.render(&eval, &Outcome::PatternError(e)), true),
+        };
+        self.results = results;
+        self.failed = failed;
+        self.scroll = 0;
+    }
+
+    /// Runs the last operation with `regex::bytes` on the bytes the string
+    /// spells out in `encoding`, showing each match with a hex dump.
+    fn search_bytes(&mut self, encoding: Encoding) {
+        let source = format!("{} from the string", encoding);
+        let search = encoding
+            .decode(&self.string.text)
+            .map_err(|e| format!("Unable to read {}: {}", encoding, e))
+            .and_then(|bytes| ByteSearch::new(self.last_op, &self.pattern.text, bytes, &source));
+        let search = match search {
+            Ok(search) => search
+                .with_flags(self.flags)
+                .with_limits(self.limits)
+                .with_max_results(MAX_BYTE_RESULTS),
+            Err(e) => {
+                self.status = e;
+                return;
+            }
+        };
+        let (results, failed) = match search.run() {
+            Ok(found) => (found.to_string(), found.matches.is_empty()),
+            Err(e) => {
+                let outcome = Outcome::PatternError(e);
+                (Format::Plain.render(&search.evaluation(), &outcome), true)
+            }
+        };
+        self.results = results;
+        self.failed = failed;
+        self.scroll = 0;
+        self.status = String::new();
+    }
+
+    fn history(&mut self) {
+        self.results = self.hist.to_str("\n");
+        self.failed = false;
+        self.scroll = 0;
+    }
+
+    /// Fills the fields from the history entry before the one last restored,
+    /// starting from the newest and wrapping round after the oldest.
+    fn restore_older(&mut self) {
+        let len = self.hist.len();
+        if len == 0 {
+            self.status = "No history as yet".into();
+            return;
+        }
+        let i = match self.restored {
+            Some(i) if i > 0 => i - 1,
+            _ => len - 1,
+        };
+        let exe = match self.hist.get(i) {
+            Some(exe) => exe,
+            None => return,
+        };
+        self.pattern = Field::new(&exe.pattern);
+        self.string = Field::new(&exe.string);
+        self.replacement = Field::new(&exe.replacement);
+        self.flags = exe.flags;
+        self.limits = exe.limits;
+        if let Ok(op) = exe.operation.parse() {
+            self.last_op = op;
+        }
+        self.restored = Some(i);
+        self.status = match &exe.file {
+            Some(path) => format!(
+                "Restored entry {}, which searched {}; only strings are searched here",
+                i,
+                path.display()
+            ),
+            None => format!("Restored entry {}, a {} run", i, exe.operation),
+        };
+    }
+
+    fn toggle_flag(&mut self, c: char) {
+        let f = &mut self.flags;
+        match c {
+            'i' => f.case_insensitive = !f.case_insensitive,
+            'm' => f.multi_line = !f.multi_line,
+            's' => f.dot_matches_new_line = !f.dot_matches_new_line,
+            'U' => f.swap_greed = !f.swap_greed,
+            'x' => f.ignore_whitespace = !f.ignore_whitespace,
+            'u' => f.unicode = !f.unicode,
+            _ => {}
+        }
+    }
+
+    /// Handles a key press, returning false once the user asks to quit.
+    fn key(&mut self, key: KeyEvent) -> bool {
+        let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+        let alt = key.modifiers.contains(KeyModifiers::ALT);
+        match key.code {
+            KeyCode::Esc => return false,
+            KeyCode::Char('c') | KeyCode::Char('q') if ctrl => return false,
+            KeyCode::F(1) => self.evaluate(Operation::Match),
+            KeyCode::F(2) => self.evaluate(Operation::Find),
+            KeyCode::F(3) => self.evaluate(Operation::FindCaptures),
+            KeyCode::F(4) => self.evaluate(Operation::Split),
+            KeyCode::F(5) => self.evaluate(Operation::Replace),
+            KeyCode::F(6) => self.evaluate(Operation::Lines),
+            KeyCode::F(7) if key.modifiers.contains(KeyModifiers::SHIFT) => self.restore_older(),
+            KeyCode::F(7) => self.history(),
+            KeyCode::F(8) => self.evaluate(Operation::Explain),
+            KeyCode::F(9) => self.evaluate(Operation::Inspect),
+            KeyCode::F(10) => self.bench(),
+            KeyCode::F(11) => self.evaluate(Operation::Set),
+            KeyCode::F(12) if key.modifiers.contains(KeyModifiers::SHIFT) => {
+                self.search_bytes(Encoding::Base64)
+            }
+            KeyCode::F(12) => self.search_bytes(Encoding::Hex),
+            KeyCode::Tab => {
+                self.focus = match self.focus {
+                    Focus::Pattern => Focus::String,
+                    Focus::String => Focus::Replacement,
+                    Focus::Replacement => Focus::Pattern,
+                }
+            }
+            KeyCode::BackTab => {
+                self.focus = match self.focus {
+                    Focus::Pattern => Focus::Replacement,
+                    Focus::String => Focus::Pattern,
+                    Focus::Replacement => Focus::String,
+                }
+            }
+            KeyCode::PageUp => self.scroll = self.scroll.saturating_sub(10),
+            KeyCode::PageDown => self.scroll = self.scroll.saturating_add(10),
+            KeyCode::Up => self.scroll = self.scroll.saturating_sub(1),
+            KeyCode::Down => self.scroll = self.scroll.saturating_add(1),
+            KeyCode::Enter if self.focus == Focus::String => self.string.insert('\n'),
+            KeyCode::Enter if alt && self.focus == Focus::Pattern => self.pattern.insert('\n'),
+            KeyCode::Enter => self.evaluate(self.last_op),
+            KeyCode::Char(c) if alt => self.toggle_flag(c),
+            KeyCode::Char(c) => self.field().insert(c),
+            KeyCode::Backspace => self.field().backspace(),
+            KeyCode::Left => self.field().left(),
+            KeyCode::Right => self.field().right(),
+            KeyCode::Home => self.field().home(),
+            KeyCode::End => self.field().end(),
+            _ => {}
+        }
+        true
+    }
+
+    /// The string, coloured by the last evaluation when it still applies.
+    fn string_text(&self) -> Text<'_> {
+        let (evaluated, highlights) = &self.highlights;
+        if *evaluated == self.string.text {
+            highlighted(&self.string.text, highlights)
+        } else {
+            Text::raw(self.string.text.as_str())
+        }
+    }
+}
+
+/// `text` split into lines with each highlight coloured.  Text is cut
+/// wherever a highlight starts or ends, and each piece takes the colour of
+/// the innermost highlight covering it.
+fn highlighted<'a>(text: &'a str, highlights: &[Highlight]) -> Text<'a> {
+    let mut cuts = vec![0, text.len()];
+    cuts.extend(
+        highlights
+            .iter()
+            .flat_map(|h| vec![h.span.start, h.span.end]),
+    );
+    cuts.sort_unstable();
+    cuts.dedup();
+    let mut lines = vec![Spans::from(vec![])];
+    for w in cuts.windows(2) {
+        let style = match Highlight::at(highlights, w[0]) {
+            Some(h) => Style::default().fg(group_color(h.group)),
+            None => Style::default(),
+        };
+        for (i, part) in text[w[0]..w[1]].split('\n').enumerate() {
+            if i > 0 {
+                lines.push(Spans::from(vec![]));
+            }
+            if !part.is_empty() {
+                lines.last_mut().unwrap().0.push(Span::styled(part, style));
+            }
+        }
+    }
+    Text::from(lines)
+}
+
+fn draw<B: Backend>(f: &mut Frame<B>, app: &App) {
+    let pattern_lines = (app.pattern.text.matches('\n').count() as u16 + 1).min(PATTERN_LINES);
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints(
+            [
+                Constraint::Length(pattern_lines + 2),
+                Constraint::Length(8),
+                Constraint::Length(3),
+                Constraint::Min(5),
+                Constraint::Length(1),
+            ]
+            .as_ref(),
+        )
+        .split(f.size());
+    let block = |title: String, focus: Focus| {
+        let border = if app.focus == focus {
+            Style::default().fg(Color::Yellow)
+        } else {
+            Style::default()
+        };
+        Block::default()
+            .borders(Borders::ALL)
+            .title(title)
+            .border_style(border)
+    };
+
+    // keep the cursor line of the string in view
+    let inner = |area: Rect| area.height.saturating_sub(2).max(1);
+    let (_, line) = app.string.position();
+    let string_top = line.saturating_sub(inner(chunks[1]) - 1);
+    let (_, line) = app.pattern.position();
+    let pattern_top = line.saturating_sub(inner(chunks[0]) - 1);
+
+    f.render_widget(
+        Paragraph::new(app.pattern.text.as_str())
+            .block(block(
+                format!("Pattern {}", app.flags.inline()),
+                Focus::Pattern,
+            ))
+            .scroll((pattern_top, 0)),
+        chunks[0],
+    );
+    f.render_widget(
+        Paragraph::new(app.string_text())
+            .block(block("String".into(), Focus::String))
+            .scroll((string_top, 0)),
+        chunks[1],
+    );
+    f.render_widget(
+        Paragraph::new(app.replacement.text.as_str())
+            .block(block("Replace".into(), Focus::Replacement)),
+        chunks[2],
+    );
+    let results = if app.failed {
+        Style::default().fg(Color::Red)
+    } else {
+        Style::default()
+    };
+    f.render_widget(
+        Paragraph::new(app.results.as_str())
+            .style(results)
+            .block(Block::default().borders(Borders::ALL).title("Results"))
+            .scroll((app.scroll, 0)),
+        chunks[3],
+    );
+    let status = if app.status.is_empty() {
+        HELP
+    } else {
+        app.status.as_str()
+    };
+    f.render_widget(Paragraph::new(status), chunks[4]);
+
+    let (area, (column, line), top) = match app.focus {
+        Focus::Pattern => (chunks[0], app.pattern.position(), pattern_top),
+        Focus::String => (chunks[1], app.string.position(), string_top),
+        Focus::Replacement => (chunks[2], app.replacement.position(), 0),
+    };
+    f.set_cursor(area.x + 1 + column, area.y + 1 + line - top);
+}
+
+fn run<B: Backend>(terminal: &mut Terminal<B>, mut app: App) -> io::Result<()> {
+    loop {
+        terminal.draw(|f| draw(f, &app))?;
+        if let Event::Key(key) = event::read()? {
+            // Windows reports releases too
+            if key.kind == KeyEventKind::Press && !app.key(key) {
+                return Ok(());
+            }
+        }
+    }
+}
+
+fn main() -> Result<(), Box<dyn Error>> {
+    let hist = History::new();
+    // no state file just means nothing has been run yet
+    let status = match hist.load_from() {
+        Err(e) => match e.downcast_ref::<io::Error>() {
+            Some(io) if io.kind() == io::ErrorKind::NotFound => String::new(),
+            _ => format!("Could not load prior state/history: {}", e),
+        },
+        Ok(()) => String::new(),
+    };
+
+    enable_raw_mode()?;
+    let mut stdout = io::stdout();
+    execute!(stdout, EnterAlternateScreen)?;
+    let mut terminal = Terminal::new(CrosstermBackend::new(stdout))?;
+
+    let res = run(&mut terminal, App::new(hist, status));
+
+    disable_raw_mode()?;
+    execute!(terminal.backend_mut(), LeaveAlternateScreen)?;
+    terminal.show_cursor()?;
+    Ok(res?)
+}